@@ -0,0 +1,103 @@
+use cess_rust_sdk::utils::account::AddressBook;
+
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cess_rust_sdk::utils::account::IntoAccount;
+
+    #[test]
+    fn test_resolve_alias_and_address() {
+        let json = format!(r#"{{"gateway-sgp": "{}", "cold-1": "{}"}}"#, ALICE, BOB);
+        let book = AddressBook::from_json(&json).unwrap();
+
+        let alice = ALICE.into_account().unwrap();
+        assert_eq!(book.resolve("gateway-sgp").unwrap(), alice);
+        assert_eq!(book.resolve("Gateway-SGP").unwrap(), alice);
+        assert_eq!(book.resolve(BOB).unwrap(), BOB.into_account().unwrap());
+        assert_eq!(
+            book.alias("cold-1").into_account().unwrap(),
+            BOB.into_account().unwrap()
+        );
+        assert_eq!(book.label_for(&alice), Some("gateway-sgp"));
+        assert!(book.resolve("unknown").is_err());
+    }
+
+    #[test]
+    fn test_toml_book_keeps_labels() {
+        let toml = format!("Gateway-SGP = \"{}\"\n\"cold-1\" = \"{}\"\n", ALICE, BOB);
+        let book = AddressBook::from_toml(&toml).unwrap();
+
+        let alice = ALICE.into_account().unwrap();
+        assert_eq!(book.resolve("gateway-sgp").unwrap(), alice);
+        assert_eq!(book.label_for(&alice), Some("Gateway-SGP"));
+        assert!(AddressBook::from_toml("cold-1 = [").is_err());
+    }
+
+    #[test]
+    fn test_ambiguous_aliases() {
+        let json = format!(r#"{{"cold-1": "{}", "COLD-1": "{}"}}"#, ALICE, BOB);
+        assert!(AddressBook::from_json(&json).is_err());
+
+        let json = format!(r#"{{"{}": "{}"}}"#, ALICE, BOB);
+        assert!(AddressBook::from_json(&json).is_err());
+    }
+
+    #[test]
+    fn test_malformed_files() {
+        assert!(AddressBook::from_json("not json").is_err());
+        assert!(AddressBook::from_json(r#"["cold-1"]"#).is_err());
+        assert!(AddressBook::from_json(r#"{"cold-1": 42}"#).is_err());
+        assert!(AddressBook::from_json(r#"{"cold-1": "not-an-address"}"#).is_err());
+        assert!(AddressBook::load("does-not-exist.json").is_err());
+    }
+
+    #[test]
+    fn test_ed25519_signer_account() {
+        use cess_rust_sdk::chain::signer::dyn_signer_from_ed25519_seed;
+        use cess_rust_sdk::subxt::tx::Signer;
+        use cess_rust_sdk::utils::account::encode_ed25519_public_key_as_cess_account;
+
+        let signer = dyn_signer_from_ed25519_seed(&[7u8; 32]).unwrap();
+        let account = signer.account_id();
+        let address = encode_ed25519_public_key_as_cess_account(&account.0).unwrap();
+        assert!(address.starts_with("c"));
+        assert_eq!(address.as_str().into_account().unwrap(), account);
+    }
+
+    #[test]
+    fn test_ecdsa_signer_from_hex() {
+        use cess_rust_sdk::chain::ecdsa_signer::ecdsa_signer_from_hex;
+
+        let signer = ecdsa_signer_from_hex(
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(signer.eth_address()),
+            "2c7536e3605d9c16a7a3d7b1898e529396a65c23"
+        );
+        let sig = signer.sign_eth_message(b"payload");
+        assert!(sig[64] <= 1);
+        assert!(ecdsa_signer_from_hex("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_into_account_inputs() {
+        let alice = ALICE.into_account().unwrap();
+        assert_eq!(alice.clone().into_account().unwrap(), alice);
+        assert_eq!(alice.0.as_slice().into_account().unwrap(), alice);
+        assert_eq!(alice.0.into_account().unwrap(), alice);
+
+        assert!(matches!(
+            "not-an-address".into_account(),
+            Err(cess_rust_sdk::core::Error::InvalidInput(_))
+        ));
+
+        let sec1 = [2u8; 33];
+        assert!(sec1.as_slice().into_account().is_err());
+        assert!(alice.0[..31].into_account().is_err());
+    }
+}
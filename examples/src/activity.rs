@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod test {
+    use cess_rust_sdk::chain::activity::call_references;
+    use cess_rust_sdk::chain::debug::bundled_metadata;
+    use cess_rust_sdk::polkadot::{
+        self, runtime_types::bounded_collections::bounded_vec::BoundedVec,
+    };
+    use cess_rust_sdk::subxt::tx::Payload;
+    use cess_rust_sdk::subxt::utils::{AccountId32, MultiAddress};
+    use cess_rust_sdk::utils::hash_from_string;
+
+    const BOB: [u8; 32] = [
+        0x8e, 0xaf, 0x04, 0x15, 0x16, 0x87, 0x73, 0x63, 0x26, 0xc9, 0xfe, 0xa1, 0x7e, 0x25, 0xfc,
+        0x52, 0x87, 0x61, 0x36, 0x93, 0xc9, 0x12, 0x90, 0x9c, 0xb2, 0x26, 0xaa, 0x47, 0x94, 0xf2,
+        0x6a, 0x48,
+    ];
+    const FID: &str = "48609e0f30979f40f838deeed66da835086f787fe6dae2f8dbe364afd28793b6";
+
+    fn call_data(call: &impl Payload) -> Vec<u8> {
+        call.encode_call_data(&bundled_metadata().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_transfer_references() {
+        let call = polkadot::tx()
+            .balances()
+            .transfer_keep_alive(MultiAddress::Id(AccountId32(BOB)), 1_500_000_000_000);
+        let found = call_references(&call_data(&call)).unwrap();
+        assert_eq!(found.accounts, vec![AccountId32(BOB)]);
+        assert_eq!(found.amounts, vec![1_500_000_000_000]);
+        assert!(found.fids.is_empty());
+    }
+
+    #[test]
+    fn test_file_and_territory_references() {
+        let call = polkadot::tx()
+            .file_bank()
+            .delete_file(AccountId32(BOB), hash_from_string(FID).unwrap());
+        let found = call_references(&call_data(&call)).unwrap();
+        assert_eq!(found.accounts, vec![AccountId32(BOB)]);
+        assert_eq!(found.fids, vec![FID.to_string()]);
+
+        let call =
+            polkadot::tx()
+                .storage_handler()
+                .mint_territory(2, BoundedVec(b"photos".to_vec()), 30);
+        let found = call_references(&call_data(&call)).unwrap();
+        assert_eq!(found.territories, vec!["photos".to_string()]);
+        assert!(found.amounts.is_empty());
+    }
+
+    #[test]
+    fn test_malformed_call_data_fails() {
+        let mut data = call_data(&polkadot::tx().storage_handler().mint_territory(
+            2,
+            BoundedVec(b"photos".to_vec()),
+            30,
+        ));
+        data.push(0);
+        assert!(call_references(&data).is_err());
+        assert!(call_references(&[0xff, 0xff]).is_err());
+    }
+}
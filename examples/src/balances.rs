@@ -1,5 +1,7 @@
 use cess_rust_sdk::{
-    chain::balances::transaction::StorageTransaction, polkadot::balances::events::Transfer,
+    chain::balances::{query::StorageQuery, transaction::StorageTransaction},
+    polkadot::balances::events::Transfer,
+    utils::format_cess,
 };
 
 const MNEMONIC: &str =
@@ -15,6 +17,11 @@ pub async fn transfer_token() -> Result<(String, Transfer), Box<dyn std::error::
         .await
 }
 
+pub async fn free_balance(account: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let free = StorageQuery::free_balance(account, None).await?;
+    Ok(format_cess(free))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -35,4 +42,113 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_tracked_transfer_is_included() {
+        use cess_rust_sdk::chain::tracker::{TxStatus, TxTracker};
+        use cess_rust_sdk::chain::{Call, TxOptions};
+        use cess_rust_sdk::polkadot;
+        use cess_rust_sdk::subxt::config::{substrate::BlakeTwo256, Hasher};
+        use cess_rust_sdk::utils::account::IntoAccount;
+
+        dotenv().ok();
+
+        let storage = StorageTransaction::new(MNEMONIC);
+        let bob = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty"
+            .into_account()
+            .unwrap();
+        let tx = polkadot::tx()
+            .balances()
+            .transfer_allow_death(cess_rust_sdk::subxt::utils::MultiAddress::Id(bob), 1);
+        let tracked = StorageTransaction::sign_and_submit_tracked(
+            &tx,
+            &storage.get_pair_signer(),
+            &TxOptions::default(),
+            TxTracker::new(),
+        )
+        .await
+        .unwrap();
+
+        let (block, success) = match tracked.status.await.unwrap() {
+            TxStatus::Included { block, success } => (block, success),
+            TxStatus::Dropped => panic!("Transfer {:?} was dropped", tracked.tx_hash),
+        };
+        assert!(success);
+
+        // The reported block must hold the transfer.
+        let api = cess_rust_sdk::init_api().await.unwrap();
+        let extrinsics = api
+            .blocks()
+            .at(block)
+            .await
+            .unwrap()
+            .extrinsics()
+            .await
+            .unwrap();
+        assert!(extrinsics
+            .iter()
+            .any(|extrinsic| BlakeTwo256::hash(extrinsic.unwrap().bytes()) == tracked.tx_hash));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_extrinsic_is_dropped() {
+        use cess_rust_sdk::chain::tracker::{TxStatus, TxTracker};
+        use cess_rust_sdk::chain::Chain;
+
+        dotenv().ok();
+
+        // Never submitted, so it cannot show up in any block.
+        let tx_hash = cess_rust_sdk::subxt::utils::H256::repeat_byte(0xab);
+        let latest = StorageTransaction::get_latest_block().await.unwrap();
+        let status = TxTracker::new()
+            .max_blocks(2)
+            .track(tx_hash, latest)
+            .await
+            .unwrap();
+        assert_eq!(status, TxStatus::Dropped);
+    }
+
+    #[tokio::test]
+    async fn test_free_balance() {
+        dotenv().ok();
+        let result = free_balance("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty").await;
+        match result {
+            Ok(free) => println!("{}", free),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_estimate_transfer_fee() {
+        dotenv().ok();
+        let fee = StorageTransaction::new(MNEMONIC)
+            .estimate_transfer_fee("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty", 1)
+            .await
+            .unwrap();
+        assert!(fee > 0);
+        println!("Transfer fee: {} CESS", format_cess(fee));
+    }
+
+    #[test]
+    fn test_format_cess() {
+        assert_eq!(format_cess(0), "0");
+        assert_eq!(format_cess(1_500_000_000_000), "1.5");
+        assert_eq!(format_cess(1), "0.000000000001");
+    }
+
+    #[test]
+    fn test_tip_schedule_is_capped() {
+        use cess_rust_sdk::chain::TipSchedule;
+
+        let schedule = TipSchedule::default();
+        assert_eq!(schedule.tip_for(500), 0);
+        assert_eq!(schedule.tip_for(100), 100_000_000);
+        assert_eq!(schedule.tip_for(10), 10_000_000_000);
+        assert!(schedule.tip_for(0) <= schedule.max_tip);
+
+        let schedule = schedule.with_max_tip(15_000_000_000);
+        assert_eq!(schedule.bump(10_000_000_000, 10), Some(15_000_000_000));
+        assert_eq!(schedule.bump(15_000_000_000, 10), None);
+        assert_eq!(schedule.bump(0, 500), Some(1));
+    }
 }
@@ -0,0 +1,29 @@
+//! Pretty prints SCALE encoded hex against the bundled CESS metadata.
+//!
+//! ```text
+//! cargo run --bin decode -- call 0x0600...
+//! cargo run --bin decode -- event 0x0602...
+//! cargo run --bin decode -- storage:StorageHandler:UnitPrice 0x0000...
+//! ```
+
+use examples::decode::decode;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [kind, hex] = args.as_slice() else {
+        eprintln!("Usage: decode <call|event|storage:<Pallet>:<Entry>> <hex>");
+        return ExitCode::FAILURE;
+    };
+
+    match decode(kind, hex) {
+        Ok(output) => {
+            println!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
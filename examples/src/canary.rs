@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod test {
+    use cess_rust_sdk::gateway::canary::{self, CanaryOptions, CanaryPhase};
+    use cess_rust_sdk::health::CheckStatus;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const MNEMONIC: &str =
+        "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice";
+
+    /// Answers every request with `response` after `delay`, returns the gateway url.
+    async fn stub_gateway(response: &'static [u8], delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = socket.read(&mut buf).await;
+                    tokio::time::sleep(delay).await;
+                    let _ = socket.write_all(response).await;
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_failed_upload_ends_the_canary() {
+        let url = stub_gateway(
+            b"HTTP/1.1 403 Forbidden\r\nContent-Length: 18\r\n\r\nsignature mismatch",
+            Duration::ZERO,
+        )
+        .await;
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook_seen = seen.clone();
+        let options = CanaryOptions::default()
+            .on_phase(move |result| hook_seen.lock().unwrap().push(result.phase));
+
+        let report = canary::run(&url, MNEMONIC, "canary", options)
+            .await
+            .unwrap();
+        assert!(!report.succeeded());
+        assert_eq!(report.failed_phase, Some(CanaryPhase::Upload));
+        assert!(report.fid.is_none());
+        assert_eq!(report.phases.len(), 1);
+        let detail = report.phases[0].detail.as_deref().unwrap();
+        assert!(detail.contains("authentication failed"));
+        assert_eq!(*seen.lock().unwrap(), vec![CanaryPhase::Upload]);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["failed_phase"], "upload");
+        assert!(json.get("fid").is_none());
+        assert!(report.file_name.starts_with(canary::CANARY_PREFIX));
+    }
+
+    #[tokio::test]
+    async fn test_slow_upload_times_out() {
+        let url = stub_gateway(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n",
+            Duration::from_secs(5),
+        )
+        .await;
+        let options = CanaryOptions {
+            upload_timeout: Duration::from_millis(200),
+            ..CanaryOptions::default()
+        };
+
+        let report = canary::run(&url, MNEMONIC, "canary", options)
+            .await
+            .unwrap();
+        assert_eq!(report.failed_phase, Some(CanaryPhase::Upload));
+        assert_eq!(report.phases[0].status, CheckStatus::Fail);
+        assert!(report.phases[0]
+            .detail
+            .as_deref()
+            .unwrap()
+            .starts_with("Timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_mnemonic_fails_to_start() {
+        let result = canary::run(
+            "http://127.0.0.1:1",
+            "not a mnemonic",
+            "canary",
+            CanaryOptions::default(),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(cess_rust_sdk::core::Error::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,32 @@
+use cess_rust_sdk::chain::debug::{fetch_dynamic, list_entries, BlockRef};
+use cess_rust_sdk::chain::storage_handler::query::StorageQuery;
+
+pub async fn get_unit_price_dynamic() -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let value = fetch_dynamic("StorageHandler", "UnitPrice", &[], BlockRef::Latest).await?;
+    Ok((!value.is_null()).then(|| value.to_string().trim_matches('"').to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dotenv::dotenv;
+
+    #[tokio::test]
+    async fn test_dynamic_unit_price_matches_typed_query() {
+        dotenv().ok();
+
+        let dynamic = get_unit_price_dynamic().await.unwrap();
+        let typed = StorageQuery::unit_price(None).await.unwrap();
+        assert_eq!(dynamic, typed.map(|price| price.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_list_entries() {
+        dotenv().ok();
+
+        let entries = list_entries("StorageHandler").await.unwrap();
+        let unit_price = entries.iter().find(|e| e.name == "UnitPrice").unwrap();
+        assert_eq!(unit_price.key_type, None);
+        assert_eq!(unit_price.value_type, "u128");
+    }
+}
@@ -0,0 +1,117 @@
+use cess_rust_sdk::chain::debug::{decode_call_hex, decode_event_hex, decode_storage_value};
+
+/// Pretty prints pasted hex. `kind` is one of `call`, `event` or `storage:<Pallet>:<Entry>`.
+pub fn decode(kind: &str, hex: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let value = match kind.split(':').collect::<Vec<_>>().as_slice() {
+        ["call"] => decode_call_hex(hex)?,
+        ["event"] => decode_event_hex(hex)?,
+        ["storage", pallet, entry] => decode_storage_value(pallet, entry, hex)?,
+        _ => return Err(format!("Unknown kind '{}'", kind).into()),
+    };
+
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cess_rust_sdk::chain::debug::bundled_metadata;
+    use cess_rust_sdk::polkadot;
+    use cess_rust_sdk::subxt::ext::codec::Encode;
+    use cess_rust_sdk::subxt::tx::Payload;
+    use cess_rust_sdk::subxt::utils::{AccountId32, MultiAddress};
+
+    const ALICE: [u8; 32] = [
+        0xd4, 0x35, 0x93, 0xc7, 0x15, 0xfd, 0xd3, 0x1c, 0x61, 0x14, 0x1a, 0xbd, 0x04, 0xa9, 0x9f,
+        0xd6, 0x82, 0x2c, 0x85, 0x58, 0x85, 0x4c, 0xcd, 0xe3, 0x9a, 0x56, 0x84, 0xe7, 0xa5, 0x6d,
+        0xa2, 0x7d,
+    ];
+
+    #[test]
+    fn test_decode_call() {
+        let metadata = bundled_metadata().unwrap();
+        let tx = polkadot::tx()
+            .balances()
+            .transfer_allow_death(MultiAddress::Id(AccountId32(ALICE)), 1_000);
+        let call_data = tx.encode_call_data(&metadata).unwrap();
+
+        let value = decode_call_hex(&hex::encode(call_data)).unwrap();
+        let call = &value["Balances"]["transfer_allow_death"];
+        assert_eq!(call["dest"]["Id"], format!("0x{}", hex::encode(ALICE)));
+        assert_eq!(call["value"], 1_000);
+    }
+
+    #[test]
+    fn test_decode_event() {
+        let event = polkadot::Event::Balances(
+            polkadot::runtime_types::pallet_balances::pallet::Event::Transfer {
+                from: AccountId32(ALICE),
+                to: AccountId32(ALICE),
+                amount: 42,
+            },
+        );
+
+        let value = decode_event_hex(&format!("0x{}", hex::encode(event.encode()))).unwrap();
+        assert_eq!(value["Balances"]["Transfer"]["amount"], 42);
+    }
+
+    #[test]
+    fn test_decode_storage_value() {
+        let unit_price = 30_000_000_000_000_000_000u128;
+        let output = decode(
+            "storage:StorageHandler:UnitPrice",
+            &hex::encode(unit_price.encode()),
+        )
+        .unwrap();
+        assert_eq!(output, "\"30000000000000000000\"");
+    }
+
+    #[test]
+    fn test_decode_rejects_trailing_bytes() {
+        assert!(decode_storage_value("StorageHandler", "UnitPrice", &"00".repeat(17)).is_err());
+    }
+
+    #[test]
+    fn test_file_metadata_view() {
+        use cess_rust_sdk::chain::file_bank::types::{FileMetadata, FileStatus};
+        use cess_rust_sdk::polkadot::runtime_types::bounded_collections::bounded_vec::BoundedVec;
+        use cess_rust_sdk::polkadot::runtime_types::pallet_file_bank::types::{
+            FileInfo, FileState, UserBrief,
+        };
+
+        let file = FileInfo {
+            segment_list: BoundedVec(vec![]),
+            owner: BoundedVec(vec![UserBrief {
+                user: AccountId32(ALICE),
+                file_name: BoundedVec(b"file.txt".to_vec()),
+                bucket_name: BoundedVec(b"bucket".to_vec()),
+                territory_name: BoundedVec(b"hello".to_vec()),
+            }]),
+            file_size: 1024,
+            completion: 7,
+            stat: FileState::Active,
+        };
+
+        let mut view = FileMetadata::from(file);
+        assert_eq!(view.status, FileStatus::Active);
+        assert_eq!(view.owners[0].file_name, "file.txt");
+        assert!(view.owners[0].account.starts_with("c"));
+
+        let book = cess_rust_sdk::utils::account::AddressBook::from_json(
+            r#"{"Alice": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"}"#,
+        )
+        .unwrap();
+        view.annotate(&book);
+        assert_eq!(view.owners[0].label.as_deref(), Some("Alice"));
+    }
+
+    #[test]
+    fn test_file_status_recovery() {
+        use cess_rust_sdk::chain::file_bank::types::FileStatus;
+        use cess_rust_sdk::polkadot::runtime_types::pallet_file_bank::types::FileState;
+
+        let status = FileStatus::from(FileState::Recovery);
+        assert_eq!(status, FileStatus::Recovery);
+        assert_eq!(status.to_string(), "recovering");
+    }
+}
@@ -1,4 +1,15 @@
-use cess_rust_sdk::gateway::file::{download, upload};
+use cess_rust_sdk::chain::file_bank::query::StorageQuery;
+use cess_rust_sdk::chain::file_bank::transaction::DeclarationSpec;
+use cess_rust_sdk::gateway::file::{download, download_with_progress, upload};
+use cess_rust_sdk::gateway::progress::{Progress, ProgressEvent};
+use cess_rust_sdk::polkadot::runtime_types::{
+    bounded_collections::bounded_vec::BoundedVec,
+    pallet_file_bank::types::{SegmentList, UserBrief},
+};
+use cess_rust_sdk::subxt::utils::AccountId32;
+use cess_rust_sdk::utils::{cost::required_territory_space, hash_from_string};
+use std::str::FromStr;
+use std::sync::Arc;
 
 async fn upload_file() {
     let gateway = "https://deoss-sgp.cess.network";
@@ -28,9 +39,71 @@ async fn download_file() {
     }
 }
 
+async fn download_file_with_progress() {
+    let gateway = "https://deoss-sgp.cess.network";
+    let mnemonic = "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice";
+    let fid = "48609e0f30979f40f838deeed66da835086f787fe6dae2f8dbe364afd28793b6";
+    let progress: Progress = Arc::new(|event: ProgressEvent| match event.percent() {
+        Some(percent) => println!("{:.1}%", percent),
+        None => println!("{} bytes", event.transferred),
+    });
+    let response =
+        download_with_progress(gateway, fid, mnemonic, "download.txt", None, Some(progress)).await;
+    match response {
+        Ok(s) => println!("{:?}", s),
+        Err(e) => {
+            println!("{:?}", e);
+            assert!(false);
+        }
+    }
+}
+
+fn declaration(bucket_name: &str) -> DeclarationSpec {
+    let fid = "48609e0f30979f40f838deeed66da835086f787fe6dae2f8dbe364afd28793b6";
+    let hash = hash_from_string(fid).unwrap();
+    DeclarationSpec {
+        file_hash: fid.to_string(),
+        segment_list: vec![SegmentList {
+            hash: hash.clone(),
+            fragment_list: BoundedVec(vec![hash; 6]),
+        }],
+        user_brief: UserBrief {
+            user: AccountId32::from_str("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY")
+                .unwrap(),
+            file_name: BoundedVec(b"file.txt".to_vec()),
+            bucket_name: BoundedVec(bucket_name.as_bytes().to_vec()),
+            territory_name: BoundedVec(b"hello".to_vec()),
+        },
+        file_size: 1024,
+    }
+}
+
+/// Walks every file held by `account`, one page at a time.
+pub async fn list_account_files(account: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    let mut count = 0;
+    let mut next_key = None;
+    loop {
+        let page = StorageQuery::files_by_account(account, 20, next_key.as_deref(), None).await?;
+        for file in &page.items {
+            println!("{} {} {} bytes", file.fid, file.file_name, file.file_size);
+        }
+        count += page.items.len();
+        match page.next_key {
+            Some(key) => next_key = Some(key),
+            None => return Ok(count),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use cess_rust_sdk::chain::file_bank::query::{FileDetail, Page};
+    use cess_rust_sdk::chain::file_bank::transaction::StorageTransaction;
+    use cess_rust_sdk::chain::Call;
+    use cess_rust_sdk::core::Error;
+    use cess_rust_sdk::polkadot;
+    use cess_rust_sdk::subxt::error::DispatchError;
 
     #[tokio::test]
     async fn test_upload_file() {
@@ -41,4 +114,177 @@ mod test {
     async fn test_download_file() {
         download_file().await;
     }
+
+    #[tokio::test]
+    async fn test_download_file_with_progress() {
+        download_file_with_progress().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_account_files() {
+        list_account_files("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_deleted_continuation_file_resumes_in_place() {
+        let account = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        let first = StorageQuery::files_by_account(account, 1, None, None)
+            .await
+            .unwrap();
+        // Names a file that is not in the list, as after its deletion.
+        let stale = format!("0:{}", "0".repeat(64));
+        let resumed = StorageQuery::files_by_account(account, 1, Some(&stale), None)
+            .await
+            .unwrap();
+        let fids = |page: &Page<FileDetail>| -> Vec<String> {
+            page.items.iter().map(|file| file.fid.clone()).collect()
+        };
+        assert_eq!(fids(&first), fids(&resumed));
+    }
+
+    #[tokio::test]
+    async fn test_space_efficiency() {
+        use cess_rust_sdk::chain::file_bank::reporting::space_efficiency;
+
+        let report = space_efficiency("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY")
+            .await
+            .unwrap();
+        assert_eq!(
+            report.physical_size,
+            report.logical_size + report.padding + report.replication_overhead
+        );
+        assert!(report.top_padding.len() <= report.file_count);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_missing_restoral_order() {
+        let completed = StorageQuery::wait_for_restoral_completion(
+            &"0".repeat(64),
+            std::time::Duration::from_secs(30),
+        )
+        .await
+        .unwrap();
+        assert!(completed);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_delete_missing_file() {
+        let transaction = StorageTransaction::new(
+            "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice",
+        );
+        let file_hash = hash_from_string(&"0".repeat(64)).unwrap();
+        let tx = polkadot::tx()
+            .file_bank()
+            .delete_file(transaction.account_id(), file_hash);
+
+        let result = transaction.dry_run(&tx).await.unwrap();
+        assert!(result.partial_fee > 0);
+        match result.dispatch_result {
+            Err(DispatchError::Module(e)) => {
+                assert_eq!(e.details().unwrap().pallet.name(), "FileBank")
+            }
+            other => panic!("Expected a FileBank error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_missing_file_names_pallet_error() {
+        let transaction = StorageTransaction::new(
+            "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice",
+        );
+
+        let err = transaction
+            .delete_own_file(&"0".repeat(64))
+            .await
+            .unwrap_err();
+        match err.downcast_ref::<Error>() {
+            Some(Error::Pallet { pallet, error, .. }) => {
+                assert_eq!(pallet, "FileBank");
+                assert_eq!(error, "FileNonExistent");
+            }
+            other => panic!("Expected a FileBank error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_delete_names_failing_file() {
+        let transaction = StorageTransaction::new(
+            "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice",
+        );
+        let deletions = vec![(
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            vec!["0".repeat(64)],
+        )];
+
+        let err = transaction.batch_delete_files(deletions).await.unwrap_err();
+        match err.downcast_ref::<Error>() {
+            Some(Error::BatchItemFailed { index, error }) => {
+                assert_eq!(*index, 0);
+                assert!(error.contains("FileNonExistent"));
+            }
+            other => panic!("Expected a failed batch item, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_delete_files_limit() {
+        let transaction = StorageTransaction::new(
+            "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice",
+        )
+        .with_max_batch_deletions(1);
+        let deletions = vec![(
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            vec!["0".repeat(64), "1".repeat(64)],
+        )];
+
+        let err = transaction.batch_delete_files(deletions).await.unwrap_err();
+        assert!(err.to_string().contains("exceed the batch limit"));
+    }
+
+    #[tokio::test]
+    async fn test_update_missing_file_metadata() {
+        let transaction = StorageTransaction::new(
+            "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice",
+        );
+
+        let err = transaction
+            .update_file_metadata(&"0".repeat(64), "renamed.txt", "hello", false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn test_preflight_names_missing_territory() {
+        use cess_rust_sdk::gateway::file::preflight;
+
+        let alice = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        match preflight(1024, alice, "no-such-territory").await {
+            Err(Error::TerritoryNotFound { name }) => assert_eq!(name, "no-such-territory"),
+            other => panic!("Expected TerritoryNotFound, got {:?}", other),
+        }
+        assert!(matches!(
+            preflight(u64::MAX, alice, "no-such-territory").await,
+            Err(Error::FileTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn test_declaration_validation() {
+        assert!(declaration("hello").validate().is_ok());
+        assert!(declaration("hi").validate().is_err());
+    }
+
+    #[test]
+    fn test_required_territory_space() {
+        let fragment = 16 * 1024 * 1024;
+        assert_eq!(required_territory_space(0), 0);
+        assert_eq!(required_territory_space(1), 6 * fragment);
+        assert_eq!(
+            required_territory_space(64 * 1024 * 1024 + 1),
+            12 * fragment
+        );
+    }
 }
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod test {
+    use cess_rust_sdk::gateway::error::{GatewayError, GatewayErrorBody};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_constructed_gateway_error_bodies() {
+        // Constructed in the `{"code","msg","data"}` shape of DeOSS, none of them was recorded
+        // from a gateway. Bodies recorded from the public gateways belong in a separate test.
+        let cases = [
+            (
+                403,
+                r#"{"code":403,"msg":"territory expired","data":null}"#,
+                GatewayError::TerritoryExpired,
+            ),
+            (
+                400,
+                r#"{"code":400,"msg":"space not enough","data":null}"#,
+                GatewayError::InsufficientTerritorySpace,
+            ),
+            (
+                403,
+                r#"{"code":403,"msg":"signature verification failed","data":null}"#,
+                GatewayError::AuthFailed {
+                    reason: "signature verification failed".to_string(),
+                },
+            ),
+            (404, "file not found", GatewayError::FidNotFound),
+            (
+                429,
+                "Too Many Requests",
+                GatewayError::RateLimited {
+                    retry_after: Some(Duration::from_secs(30)),
+                },
+            ),
+            (
+                500,
+                r#"{"code":500,"msg":"InternalError","data":null}"#,
+                GatewayError::Api {
+                    status: 500,
+                    body: r#"{"code":500,"msg":"InternalError","data":null}"#.to_string(),
+                },
+            ),
+        ];
+
+        for (status, body, expected) in cases {
+            let parsed = GatewayErrorBody::parse(status, body, Some(Duration::from_secs(30)));
+            assert_eq!(parsed, expected, "body: {}", body);
+        }
+    }
+}
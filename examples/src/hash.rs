@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod test {
+    use cess_rust_sdk::core::Error;
+    use cess_rust_sdk::polkadot::runtime_types::cp_cess_common::Hash;
+    use cess_rust_sdk::utils::{hash_from_string, hash_to_string};
+
+    const HASH: &str = "4f1c3f0b6e8ac1fc1c1b5b3d1b0a3dcda2e8a1d7b1e3b7f14b0c8b2f6d0e5a1c";
+
+    fn length_error(input: &str) -> Option<(usize, usize)> {
+        match hash_from_string(input) {
+            Err(Error::InvalidHashLength { expected, got }) => Some((expected, got)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_rejects_wrong_lengths() {
+        assert_eq!(length_error(""), Some((64, 0)));
+        assert_eq!(length_error(&HASH[..63]), Some((64, 63)));
+        assert_eq!(length_error(&format!("{}0", HASH)), Some((64, 65)));
+        assert_eq!(length_error(&format!("0x{}", &HASH[..63])), Some((64, 63)));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let prefixed = hash_from_string(&format!("0x{}", HASH)).unwrap();
+        assert_eq!(hash_to_string(&prefixed), HASH);
+
+        let upper = Hash::try_from(HASH.to_uppercase().as_str()).unwrap();
+        assert_eq!(hash_to_string(&upper), HASH);
+    }
+
+    #[test]
+    fn test_rejects_non_hex() {
+        let input = format!("{}g", &HASH[..63]);
+        assert!(hash_from_string(&input).is_err());
+    }
+}
@@ -1,4 +1,18 @@
+mod account;
+mod activity;
 mod balances;
+mod canary;
+mod client;
+mod debug;
+pub mod decode;
 mod files;
+mod gateway_error;
+mod hash;
+mod http;
+mod nonce;
 mod object;
+mod rng;
+mod sanity;
+mod sminer;
 mod storage_handler;
+mod tee_worker;
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod test {
+    use cess_rust_sdk::chain::balances::transaction::StorageTransaction;
+    use cess_rust_sdk::chain::nonce::NonceManager;
+    use cess_rust_sdk::chain::{Call, TxOptions};
+    use cess_rust_sdk::core::Error;
+    use cess_rust_sdk::polkadot;
+    use cess_rust_sdk::subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
+    use cess_rust_sdk::subxt::tx::PairSigner;
+    use cess_rust_sdk::subxt::utils::MultiAddress;
+    use cess_rust_sdk::utils::account::IntoAccount;
+    use std::collections::HashSet;
+
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+    const MNEMONIC: &str =
+        "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice";
+
+    /// Makes sure Alice has sent at least one transaction, so nonce 0 is stale.
+    async fn use_first_nonce() {
+        StorageTransaction::new(MNEMONIC)
+            .transfer_keep_alive(BOB, 1_000_000_000_000_000_000)
+            .await
+            .unwrap();
+    }
+
+    fn transfer() -> impl cess_rust_sdk::subxt::tx::Payload + Sync {
+        let bob = BOB.into_account().unwrap();
+        polkadot::tx()
+            .balances()
+            .transfer_keep_alive(MultiAddress::Id(bob), 1_000_000_000_000_000_000)
+    }
+
+    #[tokio::test]
+    async fn test_nonces_are_counted_per_account() {
+        let alice = ALICE.into_account().unwrap();
+        let bob = BOB.into_account().unwrap();
+        let nonces = NonceManager::new();
+        nonces.set(&alice, 7);
+        nonces.set(&bob, 0);
+
+        assert_eq!(nonces.next(&alice).await.unwrap(), 7);
+        assert_eq!(nonces.next(&alice).await.unwrap(), 8);
+        assert_eq!(nonces.next(&bob).await.unwrap(), 0);
+
+        // Clones share the cache.
+        assert_eq!(nonces.clone().next(&alice).await.unwrap(), 9);
+
+        nonces.set(&alice, 3);
+        assert_eq!(nonces.next(&alice).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reservations_are_unique() {
+        let alice = ALICE.into_account().unwrap();
+        let nonces = NonceManager::new();
+        nonces.set(&alice, 100);
+
+        let tasks: Vec<_> = (0..50)
+            .map(|_| {
+                let (nonces, alice) = (nonces.clone(), alice.clone());
+                tokio::spawn(async move { nonces.next(&alice).await.unwrap() })
+            })
+            .collect();
+        let mut reserved = HashSet::new();
+        for task in tasks {
+            assert!(reserved.insert(task.await.unwrap()));
+        }
+        assert_eq!(reserved, (100..150).collect());
+    }
+
+    #[tokio::test]
+    async fn test_stale_nonce_returns_pool_error() {
+        use_first_nonce().await;
+        let signer = PairSigner::new(PairS::from_string(MNEMONIC, None).unwrap());
+        let options = TxOptions {
+            nonce: Some(0),
+            ..TxOptions::default()
+        };
+
+        let err =
+            StorageTransaction::sign_and_submit_tx_with_options(&transfer(), &signer, &options)
+                .await
+                .unwrap_err();
+        assert!(matches!(err.downcast_ref::<Error>(), Some(Error::Rpc(_))));
+        let message = err.to_string().to_lowercase();
+        assert!(message.contains("outdated") || message.contains("stale"));
+    }
+
+    #[tokio::test]
+    async fn test_stale_cached_nonce_is_refetched() {
+        use_first_nonce().await;
+        let alice = ALICE.into_account().unwrap();
+        let signer = PairSigner::new(PairS::from_string(MNEMONIC, None).unwrap());
+        let nonces = NonceManager::new();
+        nonces.set(&alice, 0);
+
+        StorageTransaction::sign_and_submit_with_nonce_manager(
+            &transfer(),
+            &signer,
+            &nonces,
+            &TxOptions::default(),
+        )
+        .await
+        .unwrap();
+        // The retry fetched the nonce from the node and counted on from it.
+        assert!(nonces.next(&alice).await.unwrap() > 1);
+    }
+}
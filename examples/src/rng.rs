@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod test {
+    use cess_rust_sdk::core::rng::SeededRng;
+    use cess_rust_sdk::utils::str::get_random_code;
+
+    #[test]
+    fn test_seeded_message_codes() {
+        let first = {
+            let _rng = SeededRng::install(7);
+            (get_random_code(16).unwrap(), get_random_code(16).unwrap())
+        };
+        let _rng = SeededRng::install(7);
+        assert_eq!(get_random_code(16).unwrap(), first.0);
+        assert_eq!(get_random_code(16).unwrap(), first.1);
+        assert_ne!(first.0, first.1);
+    }
+
+    #[test]
+    fn test_guard_restores_previous_source() {
+        let outer = SeededRng::install(1);
+        let expected = get_random_code(16).unwrap();
+        drop(outer);
+
+        let _outer = SeededRng::install(1);
+        {
+            let _inner = SeededRng::install(2);
+            get_random_code(16).unwrap();
+        }
+        assert_eq!(get_random_code(16).unwrap(), expected);
+    }
+}
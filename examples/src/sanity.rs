@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod test {
+    use cess_rust_sdk::chain::sanity::Sanity;
+    use cess_rust_sdk::core::Error;
+    use cess_rust_sdk::polkadot::runtime_types::pallet_storage_handler::types::{
+        ConsignmentInfo, TerritoryInfo, TerritoryState,
+    };
+    use cess_rust_sdk::subxt::utils::{AccountId32, H256};
+
+    fn territory(total_space: u128, used_space: u128) -> TerritoryInfo {
+        TerritoryInfo {
+            token: H256::zero(),
+            total_space,
+            used_space,
+            locked_space: 0,
+            remaining_space: total_space - used_space.min(total_space),
+            start: 100,
+            deadline: 100 + 14400 * 30,
+            state: TerritoryState::Active,
+        }
+    }
+
+    fn consignment(price: u128) -> ConsignmentInfo {
+        ConsignmentInfo {
+            user: AccountId32([1u8; 32]),
+            price,
+            buyers: None,
+            exec: None,
+            locked: false,
+        }
+    }
+
+    fn violated_field(result: Result<(), Error>) -> Option<&'static str> {
+        match result {
+            Err(Error::Insane { field, .. }) => Some(field),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_territory_invariants() {
+        assert!(territory(1024, 512).check().is_ok());
+        assert_eq!(
+            violated_field(territory(1024, 2048).check()),
+            Some("used_space")
+        );
+
+        let mut reversed = territory(1024, 0);
+        reversed.deadline = reversed.start - 1;
+        assert_eq!(violated_field(reversed.check()), Some("deadline"));
+    }
+
+    #[test]
+    fn test_consignment_invariants() {
+        assert!(consignment(1).check().is_ok());
+        assert_eq!(violated_field(consignment(0).check()), Some("price"));
+    }
+}
@@ -0,0 +1,24 @@
+use cess_rust_sdk::chain::sminer::query::StorageQuery;
+
+pub async fn count_miners() -> Result<usize, Box<dyn std::error::Error>> {
+    let miners = StorageQuery::miner_items_iter(None).await?;
+
+    Ok(miners.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dotenv::dotenv;
+
+    #[tokio::test]
+    async fn test_miner_items_iter() {
+        dotenv().ok();
+
+        let listed = count_miners().await.unwrap();
+        let all = StorageQuery::all_miner(None).await.unwrap();
+        if let Some(all) = all {
+            assert!(listed >= all.0.len());
+        }
+    }
+}
@@ -1,13 +1,74 @@
-use cess_rust_sdk::chain::storage_handler::query::StorageQuery;
+use cess_rust_sdk::chain::storage_handler::watch::TerritoryWatcher;
+use cess_rust_sdk::chain::storage_handler::{
+    orders::{territory_price, OrderId, PaymentOrder},
+    query::{StorageQuery, TerritoryOp},
+    transaction::StorageTransaction,
+};
+use cess_rust_sdk::polkadot::runtime_types::pallet_storage_handler::types::{
+    OrderType, TerritoryState,
+};
+use futures::StreamExt;
+use std::str::FromStr;
+use std::time::Duration;
+
+const MNEMONIC: &str =
+    "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice";
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
 
 pub async fn get_unit_price() -> Result<Option<u128>, Box<dyn std::error::Error>> {
     Ok(StorageQuery::unit_price(None).await.unwrap())
 }
 
+pub async fn create_shared_order() -> Result<String, Box<dyn std::error::Error>> {
+    let storage = StorageTransaction::new(MNEMONIC);
+    let (_, order_id) = storage
+        .create_order(
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            "hello",
+            OrderType::Buy,
+            1,
+            30,
+            100,
+        )
+        .await?;
+    let info = StorageQuery::pay_order(&order_id, None)
+        .await?
+        .ok_or("Order not found")?;
+
+    Ok(PaymentOrder::new(order_id, &info).to_string())
+}
+
+pub async fn exec_shared_order(shared: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let order = PaymentOrder::from_str(shared)?;
+    let storage = StorageTransaction::new(MNEMONIC);
+    let (tx_hash, _) = storage.exec_order(&order.order_id).await?;
+
+    Ok(tx_hash)
+}
+
+/// Prints territory expiry alerts for `account` for up to `duration`.
+pub async fn print_territory_alerts(account: &str, duration: Duration) {
+    let alerts = TerritoryWatcher::new(account)
+        .thresholds(vec![Duration::from_secs(30 * 24 * 60 * 60)])
+        .watch()
+        .for_each(|alert| async move {
+            match alert {
+                Ok(alert) => println!(
+                    "Territory '{}' expires in {} blocks ({:?})",
+                    alert.name, alert.blocks_remaining, alert.estimated_expiry
+                ),
+                Err(e) => println!("{:?}", e),
+            }
+        });
+    let _ = tokio::time::timeout(duration, alerts).await;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cess_rust_sdk::chain::storage_handler::history::{territory_usage_series, UsagePoint};
     use dotenv::dotenv;
+    use std::time::{SystemTime, UNIX_EPOCH};
 
     #[tokio::test]
     async fn test_get_unit_price() {
@@ -27,4 +88,158 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_territory_price_matches_mint() {
+        dotenv().ok();
+
+        let price = get_unit_price().await.unwrap().unwrap();
+        let name = format!(
+            "priced-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis()
+        );
+        let estimate = StorageQuery::estimate_territory_cost(2, 30, TerritoryOp::Mint)
+            .await
+            .unwrap();
+        let storage = StorageTransaction::new(MNEMONIC);
+        let (_, minted) = storage.mint_territory(2, &name, 30).await.unwrap();
+        assert_eq!(minted.spend, territory_price(price, 2, 30));
+        assert_eq!(minted.spend, estimate);
+    }
+
+    #[test]
+    fn test_month_starts() {
+        use cess_rust_sdk::chain::storage_handler::history::month_starts;
+
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+        // 2023-11-15 to 2024-03-02, across a year end and a leap February.
+        assert_eq!(
+            month_starts(at(1_700_006_400), at(1_709_337_600)),
+            vec![
+                at(1_701_388_800),
+                at(1_704_067_200),
+                at(1_706_745_600),
+                at(1_709_251_200),
+            ]
+        );
+        // 2000-02-15 to 2000-03-01, a leap century, the bounds included.
+        assert_eq!(
+            month_starts(at(950_572_800), at(951_868_800)),
+            vec![at(951_868_800)]
+        );
+        assert_eq!(month_starts(at(0), at(0)), vec![at(0)]);
+        assert!(month_starts(at(1_709_337_600), at(1_709_337_600)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_territory_usage_series() {
+        dotenv().ok();
+
+        let blocks = [1, u64::MAX];
+        let series = territory_usage_series(
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            "hello",
+            &blocks,
+        )
+        .await
+        .unwrap();
+        assert_eq!(series.len(), 2);
+        assert!(matches!(series[1], UsagePoint::Unavailable { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_order_id_round_trip() {
+        dotenv().ok();
+
+        let shared = create_shared_order().await.unwrap();
+        let order = PaymentOrder::from_str(&shared).unwrap();
+        assert_eq!(order.to_string(), shared);
+        assert_eq!(
+            OrderId::from_str(&order.order_id.to_string()).unwrap(),
+            order.order_id
+        );
+        assert_eq!(order.territory_name, "hello");
+        assert!(StorageQuery::pay_order(&order.order_id, None)
+            .await
+            .unwrap()
+            .is_some());
+
+        let result = exec_shared_order(&shared).await;
+        match result {
+            Ok(tx_hash) => println!("{}", tx_hash),
+            Err(e) => {
+                println!("{:?}", e);
+                assert!(false);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_iteration_ends_when_client_replaced() {
+        use cess_rust_sdk::chain::oss::query::StorageQuery as OssQuery;
+        use cess_rust_sdk::core::Error;
+        use cess_rust_sdk::init_api_with_force;
+        use futures::StreamExt;
+
+        dotenv().ok();
+
+        let mut stream = OssQuery.oss_list(None).await.unwrap();
+        assert!(stream.next().await.unwrap().is_ok());
+
+        init_api_with_force(true, None).await.unwrap();
+
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(matches!(Error::from(err), Error::ClientReplaced));
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rename_consigned_territory_fails() {
+        use cess_rust_sdk::core::Error;
+
+        dotenv().ok();
+
+        let storage = StorageTransaction::new(MNEMONIC);
+        // Kept between runs, and left on consignment by a run that failed halfway.
+        let existing = StorageQuery::territory(ALICE, "consigned", None)
+            .await
+            .unwrap();
+        match existing.map(|territory| territory.state) {
+            None => {
+                storage.mint_territory(1, "consigned", 30).await.unwrap();
+            }
+            Some(TerritoryState::OnConsignment) => {
+                storage.cancel_consignment("consigned").await.unwrap();
+            }
+            Some(_) => {}
+        }
+        storage
+            .territory_consignment("consigned", 1_000_000_000_000_000_000)
+            .await
+            .unwrap();
+
+        let err = storage
+            .territory_rename("consigned", "renamed")
+            .await
+            .unwrap_err();
+        storage.cancel_consignment("consigned").await.unwrap();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::TerritoryOnConsignment)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_territory_alerts() {
+        dotenv().ok();
+
+        print_territory_alerts(
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+            Duration::from_secs(30),
+        )
+        .await;
+    }
 }
@@ -0,0 +1,27 @@
+use cess_rust_sdk::chain::tee_worker::query::StorageQuery;
+
+pub async fn list_tee_workers() -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let workers = StorageQuery::tee_worker_list(None).await?;
+
+    Ok(workers
+        .into_iter()
+        .map(|(pubkey, endpoint)| (hex::encode(pubkey), endpoint))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dotenv::dotenv;
+
+    #[tokio::test]
+    async fn test_tee_worker_list() {
+        dotenv().ok();
+
+        let workers = list_tee_workers().await.unwrap();
+        for (pubkey, endpoint) in &workers {
+            assert_eq!(pubkey.len(), 64);
+            println!("{} {}", pubkey, endpoint);
+        }
+    }
+}
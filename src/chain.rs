@@ -1,26 +1,276 @@
+pub mod activity;
 pub mod audit;
 pub mod balances;
+pub mod batch;
+pub mod blocks;
+pub mod debug;
+pub mod ecdsa_signer;
+pub mod events;
 pub mod file_bank;
+pub mod nonce;
 pub mod oss;
+pub mod sanity;
+pub mod signer;
+pub mod sminer;
+pub mod staking;
 pub mod storage_handler;
+pub mod tee_worker;
+pub mod tracker;
 
-use crate::core::Error;
-use crate::{init_api, StorageAddress, Yes, H256};
+use crate::chain::batch::ApplyOutcome;
+use crate::chain::nonce::{is_nonce_error, NonceManager};
+use crate::client::CessConfig;
+use crate::core::{Error, CLIENT_REPLACED};
+use crate::polkadot::{
+    runtime_types::{frame_support::dispatch::DispatchClass, sp_weights::weight_v2::Weight},
+    system::events::ExtrinsicFailed,
+};
+use crate::{
+    client, client_generation, client_replacements, init_api, init_api_for, init_api_with_force,
+    StorageAddress, Yes, H256,
+};
 use async_trait::async_trait;
+use futures::{future, stream, StreamExt};
+use once_cell::sync::Lazy;
 use std::marker::Sync;
+use std::sync::{Arc, RwLock};
 use subxt::backend::StreamOfResults;
-use subxt::ext::sp_core::sr25519::Pair;
-use subxt::storage::StorageKeyValuePair;
+use subxt::config::{substrate::BlakeTwo256, DefaultExtrinsicParamsBuilder, Hasher};
+use subxt::error::{DispatchError, RpcError};
+use subxt::ext::codec::{Decode, Encode};
+use subxt::ext::sp_core::{hashing::twox_128, sr25519::Pair};
+use subxt::storage::{Storage, StorageKeyValuePair};
 use subxt::{
     blocks::ExtrinsicEvents,
-    tx::{PairSigner, Payload, Signer as SignerT},
-    Config, PolkadotConfig,
+    tx::{PairSigner, Payload, Signer as SignerT, SubmittableExtrinsic},
+    utils::{AccountId32, MultiAddress, MultiSignature},
+    Config, OnlineClient, PolkadotConfig,
 };
+use tokio::sync::watch;
+use tracker::{TrackedTx, TxTracker};
+
+type SubmitHook = Arc<dyn Fn(H256) + Send + Sync>;
+
+static SUBMIT_HOOK: Lazy<RwLock<Option<SubmitHook>>> = Lazy::new(|| RwLock::new(None));
+
+/// Calls `hook` with the hash of every extrinsic the SDK signs, right before it is submitted,
+/// e.g. to record it as an attribute of the current tracing span.
+pub fn set_submit_hook(hook: impl Fn(H256) + Send + Sync + 'static) {
+    *SUBMIT_HOOK.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(hook));
+}
+
+pub fn clear_submit_hook() {
+    *SUBMIT_HOOK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+fn run_submit_hook(tx_hash: H256) {
+    let hook = SUBMIT_HOOK
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    if let Some(hook) = hook {
+        hook(tx_hash);
+    }
+}
+
+/// Signs `tx` with the signed extensions of `options`, for a mortality counted from `latest`.
+///
+/// Every extrinsic the SDK submits is signed here, so a [`TxOptions::bound_genesis`] other than
+/// the network of `api` fails with [`Error::WrongNetwork`] before anything is signed, and the
+/// submit hook sees every hash.
+async fn sign_for_submission<C, Call, Signer>(
+    api: &OnlineClient<C>,
+    tx: &Call,
+    from: &Signer,
+    options: &TxOptions,
+    latest: &C::Header,
+) -> Result<SubmittableExtrinsic<C, OnlineClient<C>>, Error>
+where
+    C: CessConfig,
+    Call: Payload,
+    Signer: SignerT<C>,
+{
+    if let Some(expected) = options.bound_genesis {
+        // Cached by the client, no RPC call.
+        let connected = api.genesis_hash();
+        if connected != expected {
+            return Err(Error::WrongNetwork {
+                expected,
+                connected,
+            });
+        }
+    }
+
+    let mut params = DefaultExtrinsicParamsBuilder::<C>::new();
+    if let Some(tip) = options.tip {
+        params = params.tip(tip);
+    }
+    if let Some(nonce) = options.nonce {
+        params = params.nonce(nonce);
+    }
+    if let Some(period) = options.mortality {
+        params = params.mortal(latest, period);
+    }
+
+    let extrinsic = api.tx().create_signed(tx, from, params.build()).await?;
+    run_submit_hook(extrinsic.hash());
+    Ok(extrinsic)
+}
+
+/// `twox128(pallet) ++ twox128(entry)`, the key prefix shared by every value of a storage entry.
+pub fn storage_prefix(pallet: &str, entry: &str) -> [u8; 32] {
+    let mut prefix = [0u8; 32];
+    prefix[..16].copy_from_slice(&twox_128(pallet.as_bytes()));
+    prefix[16..].copy_from_slice(&twox_128(entry.as_bytes()));
+    prefix
+}
+
+/// Storage at `block_hash`, or at the latest block. Every storage read of the SDK goes through
+/// here, so historical queries behave the same as current ones.
+pub(crate) async fn storage_at(
+    block_hash: Option<H256>,
+) -> Result<Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>, Error> {
+    storage_at_for(block_hash).await
+}
+
+/// [`storage_at`] through the client [`init_api_for`] resolves to.
+pub(crate) async fn storage_at_for<C: CessConfig>(
+    block_hash: Option<H256>,
+) -> Result<Storage<C, OnlineClient<C>>, Error> {
+    let api = init_api_for::<C>().await?;
+    match block_hash {
+        Some(block_hash) => Ok(api.storage().at(block_hash)),
+        None => Ok(api.storage().at_latest().await?),
+    }
+}
+
+/// `Timestamp::Now` of block `block_hash`, in milliseconds since the epoch.
+pub(crate) async fn block_timestamp(block_hash: H256) -> Result<Option<u64>, Error> {
+    let now = crate::polkadot::storage().timestamp().now();
+    Ok(storage_at(Some(block_hash)).await?.fetch(&now).await?)
+}
+
+/// Overrides for the signed extensions of a transaction, unset fields keep the defaults.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxOptions {
+    pub tip: Option<u128>,
+    /// Explicit nonce, for submitting several transactions from one account in parallel.
+    pub nonce: Option<u64>,
+    /// Number of blocks the transaction stays valid for, immortal when unset.
+    pub mortality: Option<u64>,
+    /// Genesis hash of the network the signer is meant for. Signing against any other network
+    /// fails with [`Error::WrongNetwork`]. Unset, transactions go to whatever is connected.
+    pub bound_genesis: Option<H256>,
+}
+
+impl TxOptions {
+    /// Binds transactions to the network with genesis hash `genesis`.
+    pub fn bound_to(mut self, genesis: H256) -> Self {
+        self.bound_genesis = Some(genesis);
+        self
+    }
+
+    /// Binds transactions to the network the SDK is connected to right now, so a later switch
+    /// of RPC endpoint to another network cannot redirect them.
+    pub async fn bound_to_current_network(self) -> Result<Self, Error> {
+        let api = init_api().await?;
+        Ok(self.bound_to(api.genesis_hash()))
+    }
+
+    /// Removes the network binding.
+    pub fn allow_any_network(mut self) -> Self {
+        self.bound_genesis = None;
+        self
+    }
+}
+
+/// Outcome of applying a transaction to the latest block without submitting it, see
+/// [`Call::dry_run`].
+#[derive(Debug)]
+pub struct DryRunResult {
+    /// Fee the transaction would pay, excluding any tip.
+    pub partial_fee: u128,
+    pub weight: Weight,
+    /// The error the call would fail with, e.g. a module error of the pallet.
+    pub dispatch_result: Result<(), DispatchError>,
+}
+
+/// Tips for submissions racing a deadline block, escalating as it nears.
+///
+/// Tips are in the smallest unit, see [`crate::utils::format_cess`]. By default they start at
+/// 0.0001 CESS and never exceed 0.1 CESS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TipSchedule {
+    /// `(remaining_blocks, tip)` pairs: the tip of the pair with the smallest block count that is
+    /// still at least the blocks remaining applies, no tip when more blocks remain than any count.
+    pub steps: Vec<(u32, u128)>,
+    /// Finalized blocks to wait for inclusion before resubmitting with a higher tip.
+    pub resubmit_after: u32,
+    /// Highest tip ever offered, by the steps or by doubling on resubmission.
+    pub max_tip: u128,
+}
 
+impl Default for TipSchedule {
+    fn default() -> Self {
+        Self {
+            steps: vec![
+                (100, 100_000_000),
+                (50, 1_000_000_000),
+                (20, 10_000_000_000),
+            ],
+            resubmit_after: 3,
+            max_tip: 100_000_000_000,
+        }
+    }
+}
+
+impl TipSchedule {
+    /// Caps every tip at `max_tip`.
+    pub fn with_max_tip(mut self, max_tip: u128) -> Self {
+        self.max_tip = max_tip;
+        self
+    }
+
+    pub fn tip_for(&self, remaining_blocks: u32) -> u128 {
+        self.steps
+            .iter()
+            .filter(|(blocks, _)| remaining_blocks <= *blocks)
+            .min_by_key(|(blocks, _)| *blocks)
+            .map_or(0, |(_, tip)| *tip)
+            .min(self.max_tip)
+    }
+
+    /// Tip of the resubmission after one tipped `previous`, or `None` once the cap is reached.
+    pub fn bump(&self, previous: u128, remaining_blocks: u32) -> Option<u128> {
+        let tip = previous
+            .saturating_mul(2)
+            .max(self.tip_for(remaining_blocks))
+            .max(1)
+            .min(self.max_tip);
+        (tip > previous).then_some(tip)
+    }
+}
+
+/// Outcome of [`Call::sign_and_submit_with_deadline`].
+pub struct DeadlineSubmission<C: Config = PolkadotConfig> {
+    pub events: ExtrinsicEvents<C>,
+    pub block_hash: H256,
+    pub block_number: u64,
+    /// Tip of the included extrinsic, the only one paid.
+    pub tip: u128,
+    /// How many times the extrinsic was replaced with a higher tip.
+    pub bumps: u32,
+}
+
+/// How many finalized blocks are searched for an extrinsic after a reconnect.
+const MAX_RECOVERY_BLOCKS: usize = 256;
+
+/// Base of the [`Query`] and [`Call`] traits. Their config defaults to `PolkadotConfig`, other
+/// [`CessConfig`]s resolve their client with [`init_api_for`].
 #[async_trait]
-pub trait Chain {
+pub trait Chain<C: CessConfig = PolkadotConfig> {
     async fn get_latest_block() -> Result<u64, Error> {
-        let api = init_api()
+        let api = init_api_for::<C>()
             .await
             .map_err(|_| Error::Custom("All connections failed.".into()))?;
 
@@ -30,7 +280,7 @@ pub trait Chain {
 }
 
 #[async_trait]
-pub trait Query: Chain {
+pub trait Query<C: CessConfig = PolkadotConfig>: Chain<C> {
     type Api;
 
     fn get_api() -> Self::Api;
@@ -55,22 +305,41 @@ pub trait Query: Chain {
     where
         Address: StorageAddress<IsFetchable = Yes> + Sync + 'address,
     {
-        let api = init_api()
-            .await
-            .map_err(|_| Error::Custom("All connections failed.".into()))?;
-        if let Some(block_hash) = block_hash {
-            match api.storage().at(block_hash).fetch(query).await {
-                Ok(value) => Ok(value),
-                Err(_) => Err("Failed to retrieve data from storage".into()),
-            }
-        } else {
-            match api.storage().at_latest().await {
-                Ok(mid_result) => match mid_result.fetch(query).await {
-                    Ok(value) => Ok(value),
-                    Err(_) => Err("Failed to retrieve data from storage".into()),
-                },
-                Err(_) => Err("Failed to retrieve data from storage".into()),
-            }
+        Ok(storage_at_for::<C>(block_hash).await?.fetch(query).await?)
+    }
+
+    /// Encodes the full key of `address`, see [`storage_prefix`].
+    async fn storage_key<Address>(address: &Address) -> Result<Vec<u8>, Error>
+    where
+        Address: StorageAddress + Sync,
+    {
+        let api = init_api_for::<C>().await?;
+        let mut key = storage_prefix(address.pallet_name(), address.entry_name()).to_vec();
+        address.append_entry_bytes(&api.metadata(), &mut key)?;
+        Ok(key)
+    }
+
+    /// Fetches the value under an already encoded storage key, skipping address encoding.
+    ///
+    /// Meant for hot loops that build keys from [`storage_prefix`] and hashed map keys.
+    async fn execute_query_raw(
+        key: &[u8],
+        block_hash: Option<H256>,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        Ok(storage_at_for::<C>(block_hash)
+            .await?
+            .fetch_raw(key.to_vec())
+            .await?)
+    }
+
+    /// Like [`Query::execute_query_raw`], decoding the value as `T`.
+    async fn execute_query_raw_as<T: Decode>(
+        key: &[u8],
+        block_hash: Option<H256>,
+    ) -> Result<Option<T>, Error> {
+        match Self::execute_query_raw(key, block_hash).await? {
+            Some(bytes) => T::decode(&mut &bytes[..]).map(Some).map_err(Error::from),
+            None => Ok(None),
         }
     }
 
@@ -96,35 +365,65 @@ pub trait Query: Chain {
         Address: StorageAddress<IsIterable = Yes> + 'static + Send,
         Address::Keys: 'static + Sized,
     {
-        let api = init_api()
-            .await
-            .map_err(|_| Error::Custom("All connections failed.".into()))?;
-        if let Some(block_hash) = block_hash {
-            match api.storage().at(block_hash).iter(query).await {
-                Ok(value) => Ok(value),
-                Err(_) => Err("Failed to retrieve data from storage".into()),
-            }
-        } else {
-            match api.storage().at_latest().await {
-                Ok(mid_result) => match mid_result.iter(query).await {
-                    Ok(value) => Ok(value),
-                    Err(_) => Err("Failed to retrieve data from storage".into()),
-                },
-                Err(_) => Err("Failed to retrieve data from storage".into()),
+        let generation = client::scoped_client_for::<C>()
+            .is_none()
+            .then(client_generation);
+        let stream = storage_at_for::<C>(block_hash).await?.iter(query).await?;
+
+        Ok(match generation {
+            Some(generation) => end_on_client_replaced(stream, generation),
+            None => stream,
+        })
+    }
+}
+
+/// Ends `stream` with an error converting to [`Error::ClientReplaced`] once the global client
+/// changes, also while it waits for the next item.
+fn end_on_client_replaced<T: Send + 'static>(
+    stream: StreamOfResults<T>,
+    generation: u64,
+) -> StreamOfResults<T> {
+    let state = (stream, client_replacements(), false);
+    let guarded = stream::unfold(state, move |(mut stream, mut replaced, ended)| async move {
+        if ended {
+            return None;
+        }
+        tokio::select! {
+            biased;
+            _ = replaced_since(&mut replaced, generation) => {
+                let err = subxt::Error::Other(CLIENT_REPLACED.to_string());
+                Some((Err(err), (stream, replaced, true)))
             }
+            item = stream.next() => item.map(|item| (item, (stream, replaced, false))),
+        }
+    });
+    StreamOfResults::new(Box::pin(guarded))
+}
+
+/// Resolves once the global client is no longer the one of `generation`.
+async fn replaced_since(replaced: &mut watch::Receiver<u64>, generation: u64) {
+    loop {
+        let current = *replaced.borrow_and_update();
+        if current != generation {
+            return;
+        }
+        if replaced.changed().await.is_err() {
+            return future::pending().await;
         }
     }
 }
 
+/// Submits transactions over `C`. The signers passed to the `sign_and_submit` helpers only need
+/// to sign for `C`, e.g. an [`ecdsa_signer::EcdsaSigner`] or a [`signer::DynSigner`].
 #[async_trait]
-pub trait Call: Chain {
+pub trait Call<C: CessConfig = PolkadotConfig>: Chain<C> {
     type Api;
 
     fn get_api() -> Self::Api;
-    fn get_pair_signer(&self) -> PairSigner<PolkadotConfig, Pair>;
+    fn get_pair_signer(&self) -> PairSigner<C, Pair>;
 
     fn find_first<E: subxt::events::StaticEvent>(
-        event: ExtrinsicEvents<PolkadotConfig>,
+        event: ExtrinsicEvents<C>,
     ) -> Result<(String, E), Box<dyn std::error::Error>> {
         let hash = event.extrinsic_hash();
         match event.find_first::<E>() {
@@ -139,23 +438,421 @@ pub trait Call: Chain {
         }
     }
 
-    async fn sign_and_submit_tx_then_watch_default<Call, Signer, T>(
+    /// Signs `tx` and applies it to the latest block without submitting it.
+    async fn dry_run<Call>(&self, tx: &Call) -> Result<DryRunResult, Error>
+    where
+        Call: Payload + Sync,
+        Self: Sync,
+        PairSigner<C, Pair>: SignerT<C>,
+    {
+        let api = init_api_for::<C>().await?;
+        let from = self.get_pair_signer();
+        let params = DefaultExtrinsicParamsBuilder::<C>::new().build();
+        let extrinsic = api.tx().create_signed(tx, &from, params).await?;
+        let info = query_info(&api, extrinsic.encoded()).await?;
+
+        let outcome: ApplyOutcome = api
+            .runtime_api()
+            .at_latest()
+            .await?
+            .call_raw("BlockBuilder_apply_extrinsic", Some(extrinsic.encoded()))
+            .await?;
+        let dispatch_result = match outcome {
+            ApplyOutcome::Success => Ok(()),
+            ApplyOutcome::DispatchFailed(bytes) => {
+                Err(DispatchError::decode_from(bytes, api.metadata())?)
+            }
+            ApplyOutcome::Invalid => {
+                return Err(
+                    "Transaction is invalid, e.g. a stale nonce or no funds for the fee".into(),
+                )
+            }
+        };
+
+        Ok(DryRunResult {
+            partial_fee: info.partial_fee,
+            weight: info.weight,
+            dispatch_result,
+        })
+    }
+
+    /// Fee `tx` signed by this instance would pay, excluding any tip.
+    async fn estimate_partial_fee<Call>(&self, tx: &Call) -> Result<u128, Error>
+    where
+        Call: Payload + Sync,
+        Self: Sync,
+        PairSigner<C, Pair>: SignerT<C>,
+    {
+        let api = init_api_for::<C>().await?;
+        let params = DefaultExtrinsicParamsBuilder::<C>::new().build();
+        let extrinsic = api
+            .tx()
+            .create_signed(tx, &self.get_pair_signer(), params)
+            .await?;
+        Ok(query_info(&api, extrinsic.encoded()).await?.partial_fee)
+    }
+
+    /// Submits `tx` with a tip from `schedule` for the blocks left before `deadline`, and
+    /// replaces it under the same nonce with a higher tip whenever it is not finalized within
+    /// `schedule.resubmit_after` blocks.
+    ///
+    /// Every finalized block is searched for all versions submitted so far before bumping, and
+    /// sharing a nonce means at most one of them can ever be included. Tips never exceed
+    /// `schedule.max_tip`, once it is reached the last version is left to be included.
+    ///
+    /// The nonce is `options.nonce` if set, otherwise reserved from `nonces`. The tip of
+    /// `options` is replaced, its other fields apply.
+    async fn sign_and_submit_with_deadline<Call, Signer>(
+        tx: &Call,
+        from: &Signer,
+        deadline: u32,
+        schedule: &TipSchedule,
+        nonces: &NonceManager,
+        options: &TxOptions,
+    ) -> Result<DeadlineSubmission<C>, Box<dyn std::error::Error>>
+    where
+        Call: Payload + Sync,
+        Signer: SignerT<C> + Sync,
+        C: Config<AccountId = AccountId32>,
+    {
+        let account = from.account_id();
+        let api = init_api_for::<C>().await?;
+        let nonce = match options.nonce {
+            Some(nonce) => nonce,
+            None => nonces.next_for::<C>(&account).await?,
+        };
+        let mut blocks = api.blocks().subscribe_finalized().await?;
+        let latest = api.blocks().at_latest().await?;
+        let current: u64 = latest.number().into();
+
+        let submit = |tip: u128| {
+            let api = api.clone();
+            let header = latest.header().clone();
+            let options = TxOptions {
+                tip: Some(tip),
+                nonce: Some(nonce),
+                ..*options
+            };
+            async move {
+                let extrinsic = sign_for_submission(&api, tx, from, &options, &header).await?;
+                Ok::<_, Error>(extrinsic.submit().await?)
+            }
+        };
+
+        let mut tip = schedule.tip_for(deadline.saturating_sub(current as u32));
+        let first = match submit(tip).await {
+            Ok(hash) => hash,
+            Err(e) => {
+                if is_nonce_error(&e.to_string()) {
+                    nonces.invalidate(&account);
+                }
+                return Err(e.into());
+            }
+        };
+        let mut submitted = vec![(first, tip)];
+        let mut submitted_at = current;
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let number: u64 = block.number().into();
+            for extrinsic in block.extrinsics().await?.iter() {
+                let extrinsic = extrinsic?;
+                let hash = BlakeTwo256::hash(extrinsic.bytes());
+                if let Some((_, tip)) = submitted.iter().find(|(h, _)| *h == hash) {
+                    return Ok(DeadlineSubmission {
+                        events: ensure_success(extrinsic.events().await?, &api)?,
+                        block_hash: block.hash(),
+                        block_number: number,
+                        tip: *tip,
+                        bumps: submitted.len() as u32 - 1,
+                    });
+                }
+            }
+
+            if number >= deadline as u64 {
+                return Err(format!("Deadline block {} passed before inclusion", deadline).into());
+            }
+            if number.saturating_sub(submitted_at) >= schedule.resubmit_after as u64 {
+                let remaining = deadline.saturating_sub(number as u32);
+                if let Some(bumped) = schedule.bump(tip, remaining) {
+                    tip = bumped;
+                    // A stale nonce means a previous version is already in a block, keep watching.
+                    if let Ok(hash) = submit(tip).await {
+                        submitted.push((hash, tip));
+                    }
+                }
+                submitted_at = number;
+            }
+        }
+
+        Err("Finalized block subscription ended".into())
+    }
+
+    /// Submits `tx` with a nonce reserved from `nonces`, so it does not collide with other
+    /// transactions of `from` still in the pool.
+    ///
+    /// A failure caused by the nonce itself, e.g. `Priority is too low`, drops the cached nonce
+    /// of `from` and is retried once with a nonce fetched from the node. Other failures keep the
+    /// cache, the nonce was used by an included extrinsic or is still pending. The nonce of
+    /// `options` is replaced, its other fields apply.
+    async fn sign_and_submit_with_nonce_manager<Call, Signer>(
         tx: &Call,
         from: &Signer,
-    ) -> Result<ExtrinsicEvents<PolkadotConfig>, Box<dyn std::error::Error>>
+        nonces: &NonceManager,
+        options: &TxOptions,
+    ) -> Result<ExtrinsicEvents<C>, Box<dyn std::error::Error>>
     where
         Call: Payload + Sync,
-        Signer: SignerT<T> + subxt::tx::Signer<subxt::PolkadotConfig> + Sync,
-        T: Config,
+        Signer: SignerT<C> + Sync,
+        C: Config<AccountId = AccountId32>,
     {
-        let api = init_api().await?;
+        let account = from.account_id();
+        let mut retried = false;
+        loop {
+            let options = TxOptions {
+                nonce: Some(nonces.next_for::<C>(&account).await?),
+                ..*options
+            };
+            match Self::sign_and_submit_tx_with_options(tx, from, &options).await {
+                Ok(events) => return Ok(events),
+                Err(e) => {
+                    if !is_nonce_error(&e.to_string()) {
+                        return Err(e);
+                    }
+                    nonces.invalidate(&account);
+                    if retried {
+                        return Err(e);
+                    }
+                    retried = true;
+                }
+            }
+        }
+    }
+
+    async fn sign_and_submit_tx_then_watch_default<Call, Signer>(
+        tx: &Call,
+        from: &Signer,
+    ) -> Result<ExtrinsicEvents<C>, Box<dyn std::error::Error>>
+    where
+        Call: Payload + Sync,
+        Signer: SignerT<C> + Sync,
+    {
+        Self::sign_and_submit_tx_with_options(tx, from, &TxOptions::default()).await
+    }
 
-        match api.tx().sign_and_submit_then_watch_default(tx, from).await {
-            Ok(result) => match result.wait_for_finalized_success().await {
-                Ok(r) => Ok(r),
-                Err(e) => Err(format!("{}", e).into()),
-            },
+    async fn sign_and_submit_tx_with_options<Call, Signer>(
+        tx: &Call,
+        from: &Signer,
+        options: &TxOptions,
+    ) -> Result<ExtrinsicEvents<C>, Box<dyn std::error::Error>>
+    where
+        Call: Payload + Sync,
+        Signer: SignerT<C> + Sync,
+    {
+        let api = init_api_for::<C>().await?;
+        let latest = api.blocks().at_latest().await?;
+        let extrinsic = sign_for_submission(&api, tx, from, options, latest.header()).await?;
+        let tx_hash = extrinsic.hash();
+        let submitted_after = latest.hash();
+
+        // A dropped connection leaves the outcome unknown, anything else is final.
+        let outcome = match extrinsic.submit_and_watch().await {
+            Ok(progress) => progress.wait_for_finalized_success().await,
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(events) => Ok(events),
+            Err(e) if is_disconnect(&e) => Ok(recover_after_disconnect(
+                &api,
+                extrinsic.encoded().to_vec(),
+                tx_hash,
+                submitted_after,
+            )
+            .await?),
+            // Rejected by the pool, e.g. a stale nonce or no funds for the fee: never submitted.
+            Err(e @ subxt::Error::Rpc(_)) => Err(Error::from(e).into()),
+            Err(subxt::Error::Runtime(e)) => Err(Error::from(e).into()),
             Err(e) => Err(format!("{}", e).into()),
         }
     }
+
+    /// Submits `tx` without waiting for it. The returned [`TrackedTx`] holds the extrinsic hash
+    /// and a future resolving once `tracker` sees it included or dropped.
+    async fn sign_and_submit_tracked<Call, Signer>(
+        tx: &Call,
+        from: &Signer,
+        options: &TxOptions,
+        tracker: TxTracker,
+    ) -> Result<TrackedTx, Box<dyn std::error::Error>>
+    where
+        Call: Payload + Sync,
+        Signer: SignerT<C> + Sync,
+    {
+        let api = init_api_for::<C>().await?;
+        let latest = api.blocks().at_latest().await?;
+        let submitted_at_block: u64 = latest.number().into();
+        let extrinsic = sign_for_submission(&api, tx, from, options, latest.header()).await?;
+        let tx_hash = extrinsic.hash();
+        extrinsic.submit().await.map_err(|e| format!("{}", e))?;
+
+        Ok(TrackedTx {
+            tx_hash,
+            status: Box::pin(
+                async move { tracker.track_on(&api, tx_hash, submitted_at_block).await },
+            ),
+        })
+    }
+}
+
+/// Fee `tx` would pay when signed by `signer_account`, excluding any tip, in the smallest unit.
+/// See [`crate::utils::format_cess`] for display.
+///
+/// The extrinsic is built with a placeholder signature, so no key is needed.
+pub async fn estimate_fee<C: Payload + Sync>(
+    tx: &C,
+    signer_account: &AccountId32,
+) -> Result<u128, Error> {
+    let api = init_api().await?;
+    api.tx().validate(tx)?;
+    let params = DefaultExtrinsicParamsBuilder::<PolkadotConfig>::new().build();
+    let extrinsic = api
+        .tx()
+        .create_partial_signed(tx, signer_account, params)
+        .await?
+        .sign_with_address_and_signature(
+            &MultiAddress::Id(signer_account.clone()),
+            &MultiSignature::Sr25519([0; 64]),
+        );
+
+    Ok(query_info(&api, extrinsic.encoded()).await?.partial_fee)
+}
+
+/// `TransactionPaymentApi_query_info` result.
+#[derive(Decode)]
+#[codec(crate = subxt::ext::codec)]
+pub(crate) struct DispatchInfo {
+    pub weight: Weight,
+    _class: DispatchClass,
+    pub partial_fee: u128,
+}
+
+/// Weight and partial fee of a signed extrinsic.
+pub(crate) async fn query_info<C: Config>(
+    api: &OnlineClient<C>,
+    encoded: &[u8],
+) -> Result<DispatchInfo, Error> {
+    let mut params = encoded.to_vec();
+    (encoded.len() as u32).encode_to(&mut params);
+    Ok(api
+        .runtime_api()
+        .at_latest()
+        .await?
+        .call_raw("TransactionPaymentApi_query_info", Some(&params))
+        .await?)
+}
+
+/// Whether `e` means the connection dropped, leaving the outcome of a submission unknown.
+/// Rejections by the transaction pool arrive as RPC errors too, but over a live connection.
+fn is_disconnect(e: &subxt::Error) -> bool {
+    match e {
+        subxt::Error::Rpc(
+            RpcError::DisconnectedWillReconnect(_) | RpcError::SubscriptionDropped,
+        ) => true,
+        subxt::Error::Rpc(RpcError::ClientError(e)) => {
+            let message = e.to_string().to_lowercase();
+            [
+                "restart",
+                "connection closed",
+                "background task",
+                "disconnected",
+            ]
+            .iter()
+            .any(|pattern| message.contains(pattern))
+        }
+        _ => false,
+    }
+}
+
+/// Reconnects and settles an extrinsic whose watch on `api` was interrupted.
+///
+/// Finalized blocks since submission are searched first. If the extrinsic is not there, the
+/// identical bytes are submitted again, which the pool deduplicates and a used nonce rejects, so
+/// it can never be applied twice.
+///
+/// Only the global client is replaced. A [`client::CessClient::scope`] keeps its own client,
+/// whose RPC connection reconnects by itself.
+async fn recover_after_disconnect<C: CessConfig>(
+    api: &OnlineClient<C>,
+    encoded: Vec<u8>,
+    tx_hash: H256,
+    submitted_after: H256,
+) -> Result<ExtrinsicEvents<C>, Error> {
+    let api = match client::scoped_client_for::<C>() {
+        Some(_) => api.clone(),
+        None => {
+            init_api_with_force(true, None)
+                .await
+                .map_err(|_| Error::SubmittedButUnconfirmed(tx_hash))?;
+            init_api_for::<C>()
+                .await
+                .map_err(|_| Error::SubmittedButUnconfirmed(tx_hash))?
+        }
+    };
+
+    let found = find_finalized_extrinsic(&api, tx_hash, submitted_after)
+        .await
+        .map_err(|_| Error::SubmittedButUnconfirmed(tx_hash))?;
+    if let Some(events) = found {
+        return ensure_success(events, &api);
+    }
+
+    let outcome = match SubmittableExtrinsic::from_bytes(api.clone(), encoded)
+        .submit_and_watch()
+        .await
+    {
+        Ok(progress) => progress.wait_for_finalized_success().await,
+        Err(e) => Err(e),
+    };
+    match outcome {
+        Ok(events) => Ok(events),
+        Err(subxt::Error::Runtime(e)) => Err(e.into()),
+        // Rejected as already known or stale: it may have landed while we were reconnecting.
+        Err(_) => match find_finalized_extrinsic(&api, tx_hash, submitted_after).await {
+            Ok(Some(events)) => ensure_success(events, &api),
+            _ => Err(Error::SubmittedButUnconfirmed(tx_hash)),
+        },
+    }
+}
+
+async fn find_finalized_extrinsic<C: CessConfig>(
+    api: &OnlineClient<C>,
+    tx_hash: H256,
+    submitted_after: H256,
+) -> Result<Option<ExtrinsicEvents<C>>, Error> {
+    let mut block = api.blocks().at_latest().await?;
+    for _ in 0..MAX_RECOVERY_BLOCKS {
+        if block.hash() == submitted_after {
+            break;
+        }
+        if let Some(events) = tracker::find_extrinsic(&block, tx_hash).await? {
+            return Ok(Some(events));
+        }
+        block = api.blocks().at(block.header().parent_hash).await?;
+    }
+    Ok(None)
+}
+
+fn ensure_success<C: Config>(
+    events: ExtrinsicEvents<C>,
+    api: &OnlineClient<C>,
+) -> Result<ExtrinsicEvents<C>, Error> {
+    match events.find_first::<ExtrinsicFailed>()? {
+        Some(failed) => {
+            let bytes = failed.dispatch_error.encode();
+            match DispatchError::decode_from(bytes, api.metadata()) {
+                Ok(e) => Err(e.into()),
+                Err(_) => Err(format!("Extrinsic failed: {:?}", failed.dispatch_error).into()),
+            }
+        }
+        None => Ok(events),
+    }
 }
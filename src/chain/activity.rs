@@ -0,0 +1,390 @@
+//! Activity feed of an account: the extrinsics it signed and the events that mention it.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! use cess_rust_sdk::chain::activity::{account_activity, ActivityFilter};
+//!
+//! let account = "cXh5StobuVP4B7mGH9xn8dSsDtXks4qLAou8ZdkZ6DbB6zzxe";
+//! let mut from = 1_000;
+//! loop {
+//!     let page = account_activity(account, from, 2_000, &ActivityFilter::default()).await?;
+//!     for item in &page.items {
+//!         println!("#{} {}::{}", item.block_number, item.pallet, item.name);
+//!     }
+//!     match page.cursor {
+//!         Some(next) => from = next,
+//!         None => break,
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::chain::block_timestamp;
+use crate::chain::debug::bundled_metadata;
+use crate::core::Error;
+use crate::polkadot::system::events::ExtrinsicFailed;
+use crate::utils::get_block_hash_by_number;
+use crate::{init_api, H256};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+use std::collections::HashSet;
+use std::str::FromStr;
+use subxt::config::{substrate::BlakeTwo256, Hasher};
+use subxt::events::Phase;
+use subxt::ext::scale_value::{self, Composite, Primitive, Value, ValueDef};
+use subxt::utils::AccountId32;
+
+/// Blocks scanned per [`account_activity`] call.
+pub const ACTIVITY_PAGE_BLOCKS: u64 = 256;
+/// Most blocks fetched at once.
+const MAX_CONCURRENT_BLOCKS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActivityCategory {
+    Transfer,
+    Territory,
+    File,
+    Authorization,
+    Reward,
+    Staking,
+    Other,
+}
+
+impl ActivityCategory {
+    fn of(pallet: &str, name: &str) -> Self {
+        let rewarded = ["Reward", "Receive", "Payout"]
+            .iter()
+            .any(|word| name.contains(word) || name.contains(&word.to_lowercase()));
+        match pallet {
+            "Balances" => ActivityCategory::Transfer,
+            "StorageHandler" => ActivityCategory::Territory,
+            "FileBank" => ActivityCategory::File,
+            "Oss" => ActivityCategory::Authorization,
+            "Sminer" | "Staking" if rewarded => ActivityCategory::Reward,
+            "Sminer" | "Staking" => ActivityCategory::Staking,
+            _ => ActivityCategory::Other,
+        }
+    }
+}
+
+/// Categories to keep, all of them when empty.
+#[derive(Debug, Clone, Default)]
+pub struct ActivityFilter {
+    pub categories: Vec<ActivityCategory>,
+}
+
+impl ActivityFilter {
+    fn accepts(&self, category: ActivityCategory) -> bool {
+        self.categories.is_empty() || self.categories.contains(&category)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ActivityItem {
+    pub block_number: u64,
+    pub block_hash: H256,
+    /// Milliseconds since the epoch, from `Timestamp::Now`.
+    pub block_time: Option<u64>,
+    /// Hash of the extrinsic, `None` for events outside of one.
+    pub tx_hash: Option<String>,
+    pub category: ActivityCategory,
+    pub pallet: String,
+    /// Call name when the account signed the extrinsic, event name otherwise.
+    pub name: String,
+    pub signed: bool,
+    /// Whether the extrinsic the item belongs to succeeded, true for items outside of one.
+    /// Calls of a failed extrinsic had no effect beyond the fee.
+    pub success: bool,
+    /// Other accounts referenced by the call or event.
+    pub counterparties: Vec<AccountId32>,
+    pub amounts: Vec<u128>,
+    pub fids: Vec<String>,
+    pub territories: Vec<String>,
+}
+
+/// One page of activity, with the block to resume from while blocks are left in the range.
+#[derive(Debug, Clone)]
+pub struct ActivityPage {
+    pub items: Vec<ActivityItem>,
+    pub cursor: Option<u64>,
+}
+
+/// Scans up to [`ACTIVITY_PAGE_BLOCKS`] blocks of `from_block..=to_block` for activity of
+/// `account`.
+///
+/// Extrinsics signed by the account are reported once, as their call. Events are reported when
+/// one of their fields is the account and they were not emitted by such an extrinsic, e.g.
+/// incoming transfers or rewards.
+pub async fn account_activity(
+    account: &str,
+    from_block: u64,
+    to_block: u64,
+    filters: &ActivityFilter,
+) -> Result<ActivityPage, Error> {
+    let account =
+        AccountId32::from_str(account).map_err(|e| Error::Custom(format!("{}: {}", account, e)))?;
+    if from_block > to_block {
+        return Ok(ActivityPage {
+            items: Vec::new(),
+            cursor: None,
+        });
+    }
+
+    let last = to_block.min(from_block.saturating_add(ACTIVITY_PAGE_BLOCKS - 1));
+    let blocks: Vec<Vec<ActivityItem>> = stream::iter(from_block..=last)
+        .map(|number| block_activity(&account, number, filters))
+        .buffered(MAX_CONCURRENT_BLOCKS)
+        .try_collect()
+        .await?;
+
+    Ok(ActivityPage {
+        items: blocks.into_iter().flatten().collect(),
+        cursor: (last < to_block).then_some(last + 1),
+    })
+}
+
+/// Like [`account_activity`], streaming every item of `from_block..=to_block` page by page.
+pub fn account_activity_stream(
+    account: &str,
+    from_block: u64,
+    to_block: u64,
+    filters: ActivityFilter,
+) -> impl Stream<Item = Result<ActivityItem, Error>> {
+    let account = account.to_string();
+    stream::try_unfold(Some(from_block), move |cursor| {
+        let account = account.clone();
+        let filters = filters.clone();
+        async move {
+            let from = match cursor {
+                Some(from) => from,
+                None => return Ok(None),
+            };
+            let page = account_activity(&account, from, to_block, &filters).await?;
+            let items = stream::iter(page.items.into_iter().map(Ok));
+            Ok::<_, Error>(Some((items, page.cursor)))
+        }
+    })
+    .try_flatten()
+}
+
+async fn block_activity(
+    account: &AccountId32,
+    number: u64,
+    filters: &ActivityFilter,
+) -> Result<Vec<ActivityItem>, Error> {
+    let api = init_api().await?;
+    let hash = get_block_hash_by_number(number).await?;
+    let block = api.blocks().at(hash).await?;
+    let extrinsics = block.extrinsics().await?;
+    let events = block.events().await?;
+    let block_time = block_timestamp(hash).await?;
+    let metadata = api.metadata();
+    let types = metadata.types();
+
+    let item = |pallet: &str,
+                name: &str,
+                tx_hash: Option<String>,
+                signed: bool,
+                success: bool,
+                found: References| {
+        let category = ActivityCategory::of(pallet, name);
+        filters.accepts(category).then(|| ActivityItem {
+            block_number: number,
+            block_hash: hash,
+            block_time,
+            tx_hash,
+            category,
+            pallet: pallet.to_string(),
+            name: name.to_string(),
+            signed,
+            success,
+            counterparties: found
+                .accounts
+                .into_iter()
+                .filter(|other| other != account)
+                .collect(),
+            amounts: found.amounts,
+            fids: found.fids,
+            territories: found.territories,
+        })
+    };
+
+    let mut failed = HashSet::new();
+    for event in events.iter() {
+        let event = event?;
+        if let (Phase::ApplyExtrinsic(index), Some(_)) =
+            (event.phase(), event.as_event::<ExtrinsicFailed>()?)
+        {
+            failed.insert(index as usize);
+        }
+    }
+
+    let mut items = Vec::new();
+    let mut tx_hashes = Vec::new();
+    let mut signed_by_account = Vec::new();
+    for (index, ext) in extrinsics.iter().enumerate() {
+        let ext = ext?;
+        let tx_hash = format!("0x{}", hex::encode(BlakeTwo256::hash(ext.bytes()).0));
+        tx_hashes.push(tx_hash.clone());
+        // A `MultiAddress::Id` signer encodes as its variant index followed by the account.
+        let signed = matches!(
+            ext.address_bytes(),
+            Some([0, rest @ ..]) if rest == account.0.as_slice()
+        );
+        signed_by_account.push(signed);
+        if signed {
+            let mut found = References::default();
+            walk_composite(types, None, &ext.field_values()?, &mut found);
+            items.extend(item(
+                ext.pallet_name()?,
+                ext.variant_name()?,
+                Some(tx_hash),
+                true,
+                !failed.contains(&index),
+                found,
+            ));
+        }
+    }
+
+    for event in events.iter() {
+        let event = event?;
+        let index = match event.phase() {
+            Phase::ApplyExtrinsic(index) => Some(index as usize),
+            _ => None,
+        };
+        if index.map_or(false, |index| signed_by_account.get(index) == Some(&true)) {
+            continue;
+        }
+        let mut found = References::default();
+        walk_composite(types, None, &event.field_values()?, &mut found);
+        if !found.accounts.contains(account) {
+            continue;
+        }
+        let tx_hash = index.and_then(|index| tx_hashes.get(index).cloned());
+        items.extend(item(
+            event.pallet_name(),
+            event.variant_name(),
+            tx_hash,
+            false,
+            index.map_or(true, |index| !failed.contains(&index)),
+            found,
+        ));
+    }
+
+    Ok(items)
+}
+
+/// Values of interest found in decoded call or event fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct References {
+    pub accounts: Vec<AccountId32>,
+    /// `u128` values, balances in most calls and events.
+    pub amounts: Vec<u128>,
+    pub fids: Vec<String>,
+    /// Byte strings of fields whose name mentions a territory.
+    pub territories: Vec<String>,
+}
+
+/// What [`account_activity`] picks out of SCALE encoded call data (pallet index, call index and
+/// arguments), decoded with the metadata the SDK bundles.
+pub fn call_references(call_data: &[u8]) -> Result<References, Error> {
+    let metadata = bundled_metadata()?;
+    let types = metadata.types();
+    let cursor = &mut &call_data[..];
+    let value =
+        scale_value::scale::decode_as_type(cursor, metadata.outer_enums().call_enum_ty(), types)
+            .map_err(|e| Error::Custom(format!("Failed to decode call: {}", e)))?;
+    if !cursor.is_empty() {
+        return Err(format!("{} trailing bytes left after decoding", cursor.len()).into());
+    }
+
+    let mut found = References::default();
+    walk(types, None, &value, &mut found);
+    Ok(found)
+}
+
+fn walk(types: &PortableRegistry, field: Option<&str>, value: &Value<u32>, found: &mut References) {
+    let ty = types.resolve(value.context);
+    let path: &[String] = ty.map_or(&[], |ty| ty.path.segments.as_slice());
+    match path {
+        [.., last] if last == "AccountId32" => {
+            if let Some(Ok(account)) = value_bytes(types, value).map(<[u8; 32]>::try_from) {
+                found.accounts.push(AccountId32(account));
+            }
+            return;
+        }
+        [first, .., last] if first == "cp_cess_common" && last == "Hash" => {
+            if let Some(Ok(fid)) = value_bytes(types, value).map(String::from_utf8) {
+                found.fids.push(fid);
+            }
+            return;
+        }
+        _ => {}
+    }
+    if field.map_or(false, |field| field.contains("territory")) {
+        if let Some(Ok(name)) = value_bytes(types, value).map(String::from_utf8) {
+            found.territories.push(name);
+            return;
+        }
+    }
+
+    match &value.value {
+        ValueDef::Primitive(Primitive::U128(amount))
+            if is_primitive(types, value.context, TypeDefPrimitive::U128) =>
+        {
+            found.amounts.push(*amount)
+        }
+        ValueDef::Composite(composite) => walk_composite(types, field, composite, found),
+        ValueDef::Variant(variant) => walk_composite(types, field, &variant.values, found),
+        _ => {}
+    }
+}
+
+fn walk_composite(
+    types: &PortableRegistry,
+    field: Option<&str>,
+    composite: &Composite<u32>,
+    found: &mut References,
+) {
+    match composite {
+        Composite::Named(fields) => {
+            for (name, value) in fields {
+                walk(types, Some(name), value, found);
+            }
+        }
+        Composite::Unnamed(values) => {
+            for value in values {
+                walk(types, field, value, found);
+            }
+        }
+    }
+}
+
+/// Whether `id` is `primitive`, or a compact encoding of it.
+fn is_primitive(types: &PortableRegistry, id: u32, primitive: TypeDefPrimitive) -> bool {
+    match types.resolve(id).map(|ty| &ty.type_def) {
+        Some(TypeDef::Primitive(p)) => *p == primitive,
+        Some(TypeDef::Compact(compact)) => is_primitive(types, compact.type_param.id, primitive),
+        _ => false,
+    }
+}
+
+/// The bytes of a byte array or sequence, possibly wrapped in newtypes.
+fn value_bytes(types: &PortableRegistry, value: &Value<u32>) -> Option<Vec<u8>> {
+    match &value.value {
+        ValueDef::Primitive(Primitive::U128(n))
+            if is_primitive(types, value.context, TypeDefPrimitive::U8) =>
+        {
+            Some(vec![*n as u8])
+        }
+        ValueDef::Composite(composite) => {
+            let mut bytes = Vec::new();
+            for value in composite.values() {
+                bytes.extend(value_bytes(types, value)?);
+            }
+            Some(bytes)
+        }
+        _ => None,
+    }
+}
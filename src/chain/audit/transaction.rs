@@ -1,4 +1,4 @@
-use crate::chain::{Call, Chain};
+use crate::chain::{Call, Chain, TxOptions};
 use crate::core::ApiProvider;
 use crate::impl_api_provider;
 use crate::polkadot::audit::calls::types::submit_verify_idle_result::Accumulator;
@@ -25,6 +25,7 @@ impl_api_provider!(
 pub type TxHash = String;
 pub struct StorageTransaction {
     pair: PairS,
+    pub(crate) options: TxOptions,
 }
 
 impl Chain for StorageTransaction {}
@@ -44,7 +45,16 @@ impl Call for StorageTransaction {
 impl StorageTransaction {
     pub fn new(mnemonic: &str) -> Self {
         let pair = PairS::from_string(mnemonic, None).unwrap();
-        Self { pair }
+        Self {
+            pair,
+            options: TxOptions::default(),
+        }
+    }
+
+    /// Applies `options` to every transaction submitted through this instance.
+    pub fn with_options(mut self, options: TxOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub async fn submit_idle_proof(
@@ -55,7 +65,7 @@ impl StorageTransaction {
 
         let tx = api.submit_idle_proof(idle_prove);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<SubmitIdleProof>(event)
     }
@@ -68,7 +78,7 @@ impl StorageTransaction {
 
         let tx = api.submit_service_proof(service_prove);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<SubmitServiceProof>(event)
     }
@@ -96,7 +106,7 @@ impl StorageTransaction {
             tee_puk,
         );
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<SubmitIdleVerifyResult>(event)
     }
@@ -117,7 +127,7 @@ impl StorageTransaction {
             tee_puk,
         );
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<SubmitServiceVerifyResult>(event)
     }
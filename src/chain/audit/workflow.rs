@@ -0,0 +1,268 @@
+use super::query::StorageQuery;
+use super::transaction::{StorageTransaction, TxHash};
+use crate::chain::batch::BatchTransaction;
+use crate::chain::nonce::NonceManager;
+use crate::chain::{Call, Chain, DeadlineSubmission, TipSchedule};
+use crate::constants::BLOCK_INTERVAL;
+use crate::core::Error;
+use crate::polkadot::{
+    self,
+    audit::events::{SubmitIdleProof, SubmitServiceProof},
+    runtime_types::bounded_collections::bounded_vec::BoundedVec,
+};
+use std::time::Duration;
+use subxt::events::StaticEvent;
+
+/// Upper bound of `submit_idle_proof` payloads (`IdleTotalHashLength`).
+pub const MAX_IDLE_PROOF_LENGTH: usize = 256;
+/// Upper bound of `submit_service_proof` payloads (`SigmaMax`).
+pub const MAX_SERVICE_PROOF_LENGTH: usize = 2048;
+
+/// The active challenge of a miner, plus the proofs computed for it.
+#[derive(Debug, Clone)]
+pub struct ChallengeSession {
+    pub miner: String,
+    /// Block the challenge was issued at.
+    pub start: u32,
+    /// Last block an idle proof is accepted.
+    pub idle_deadline: u32,
+    /// Last block a service proof is accepted.
+    pub service_deadline: u32,
+    pub verify_deadline: u32,
+    pub idle_space: u128,
+    pub service_space: u128,
+    /// Idle space challenge parameters.
+    pub space_param: [u64; 8],
+    /// Service challenge indices and the random values paired with them.
+    pub random_index_list: Vec<u32>,
+    pub random_list: Vec<[u8; 20]>,
+    pub idle_submitted: bool,
+    pub service_submitted: bool,
+    /// Block the session was loaded at.
+    pub loaded_at: u32,
+    idle_proof: Option<Vec<u8>>,
+    service_proof: Option<Vec<u8>>,
+}
+
+/// Results of [`ChallengeSession::submit_all_with_deadline`], `None` for proofs that were not
+/// submitted.
+#[derive(Debug)]
+pub struct DeadlineReceipts {
+    pub idle: Option<ProofInclusion<SubmitIdleProof>>,
+    pub service: Option<ProofInclusion<SubmitServiceProof>>,
+}
+
+#[derive(Debug)]
+pub struct ProofInclusion<E> {
+    pub tx_hash: TxHash,
+    pub event: E,
+    pub block_number: u64,
+    /// Tip paid for the included submission.
+    pub tip: u128,
+    /// Times the proof was resubmitted with a higher tip.
+    pub bumps: u32,
+}
+
+/// Results of [`ChallengeSession::submit_all`], `None` for proofs that were not submitted.
+#[derive(Debug)]
+pub struct ChallengeReceipts {
+    pub idle: Option<(TxHash, SubmitIdleProof)>,
+    pub service: Option<(TxHash, SubmitServiceProof)>,
+}
+
+impl ChallengeSession {
+    /// Loads the current challenge of `miner`, `Ok(None)` when no challenge is active.
+    pub async fn load(miner: &str) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let info = match StorageQuery::challenge_snapshot(miner, None).await? {
+            Some(info) => info,
+            None => return Ok(None),
+        };
+        let loaded_at = StorageQuery::get_latest_block().await? as u32;
+
+        let element = info.challenge_element;
+        let snapshot = info.miner_snapshot;
+        let prove = info.prove_info;
+        Ok(Some(Self {
+            miner: miner.to_string(),
+            start: element.start,
+            idle_deadline: element.idle_slip,
+            service_deadline: element.service_slip,
+            verify_deadline: element.verify_slip,
+            idle_space: snapshot.idle_space,
+            service_space: snapshot.service_space,
+            space_param: element.space_param,
+            random_index_list: element.service_param.random_index_list.0,
+            random_list: element.service_param.random_list.0,
+            idle_submitted: prove.idle_prove.is_some(),
+            service_submitted: prove.service_prove.is_some(),
+            loaded_at,
+            idle_proof: None,
+            service_proof: None,
+        }))
+    }
+
+    /// Blocks left, as of loading, before the earliest deadline of a proof still to be submitted.
+    pub fn remaining_blocks(&self) -> u32 {
+        let mut deadlines = Vec::new();
+        if !self.idle_submitted {
+            deadlines.push(self.idle_deadline);
+        }
+        if !self.service_submitted {
+            deadlines.push(self.service_deadline);
+        }
+        deadlines
+            .into_iter()
+            .min()
+            .map_or(0, |deadline| deadline.saturating_sub(self.loaded_at))
+    }
+
+    pub fn remaining_time(&self) -> Duration {
+        BLOCK_INTERVAL * self.remaining_blocks()
+    }
+
+    pub fn attach_idle_proof(&mut self, proof: Vec<u8>) -> Result<(), Error> {
+        if self.idle_submitted {
+            return Err("Idle proof already submitted for this challenge".into());
+        }
+        check_proof("Idle", &proof, MAX_IDLE_PROOF_LENGTH)?;
+        self.idle_proof = Some(proof);
+        Ok(())
+    }
+
+    pub fn attach_service_proof(&mut self, proof: Vec<u8>) -> Result<(), Error> {
+        if self.service_submitted {
+            return Err("Service proof already submitted for this challenge".into());
+        }
+        check_proof("Service", &proof, MAX_SERVICE_PROOF_LENGTH)?;
+        self.service_proof = Some(proof);
+        Ok(())
+    }
+
+    /// Submits the attached proofs one after another.
+    pub async fn submit_all(
+        &self,
+        signer: &StorageTransaction,
+    ) -> Result<ChallengeReceipts, Box<dyn std::error::Error>> {
+        let idle = match &self.idle_proof {
+            Some(proof) => Some(signer.submit_idle_proof(BoundedVec(proof.clone())).await?),
+            None => None,
+        };
+        let service = match &self.service_proof {
+            Some(proof) => Some(
+                signer
+                    .submit_service_proof(BoundedVec(proof.clone()))
+                    .await?,
+            ),
+            None => None,
+        };
+
+        Ok(ChallengeReceipts { idle, service })
+    }
+
+    /// Submits the attached proofs one after another, tipping according to `schedule` for the
+    /// blocks left before `deadline_block` and bumping the tip while they are not included.
+    /// Nonces come from `nonces`, shared with other submissions of the signer.
+    pub async fn submit_all_with_deadline(
+        &self,
+        signer: &StorageTransaction,
+        deadline_block: u32,
+        schedule: &TipSchedule,
+        nonces: &NonceManager,
+    ) -> Result<DeadlineReceipts, Box<dyn std::error::Error>> {
+        let tx = polkadot::tx().audit();
+        let from = signer.get_pair_signer();
+        let idle = match &self.idle_proof {
+            Some(proof) => {
+                let call = tx.submit_idle_proof(BoundedVec(proof.clone()));
+                let submission = StorageTransaction::sign_and_submit_with_deadline(
+                    &call,
+                    &from,
+                    deadline_block,
+                    schedule,
+                    nonces,
+                    &signer.options,
+                )
+                .await?;
+                Some(inclusion(submission)?)
+            }
+            None => None,
+        };
+        let service = match &self.service_proof {
+            Some(proof) => {
+                let call = tx.submit_service_proof(BoundedVec(proof.clone()));
+                let submission = StorageTransaction::sign_and_submit_with_deadline(
+                    &call,
+                    &from,
+                    deadline_block,
+                    schedule,
+                    nonces,
+                    &signer.options,
+                )
+                .await?;
+                Some(inclusion(submission)?)
+            }
+            None => None,
+        };
+
+        Ok(DeadlineReceipts { idle, service })
+    }
+
+    /// Submits the attached proofs in one `utility.batch_all` extrinsic.
+    pub async fn submit_all_batched(
+        &self,
+        mnemonic: &str,
+    ) -> Result<ChallengeReceipts, Box<dyn std::error::Error>> {
+        let tx = polkadot::tx().audit();
+        let mut batch = BatchTransaction::new(mnemonic);
+        if let Some(proof) = &self.idle_proof {
+            batch = batch.add(&tx.submit_idle_proof(BoundedVec(proof.clone())))?;
+        }
+        if let Some(proof) = &self.service_proof {
+            batch = batch.add(&tx.submit_service_proof(BoundedVec(proof.clone())))?;
+        }
+        if batch.is_empty() {
+            return Err("Error: No proof attached".into());
+        }
+
+        let (tx_hash, per_call) = batch.submit_all().await?;
+        let mut receipts = ChallengeReceipts {
+            idle: None,
+            service: None,
+        };
+        for event in per_call.into_iter().flatten() {
+            if let Some(e) = event.as_event::<SubmitIdleProof>()? {
+                receipts.idle = Some((tx_hash.clone(), e));
+            } else if let Some(e) = event.as_event::<SubmitServiceProof>()? {
+                receipts.service = Some((tx_hash.clone(), e));
+            }
+        }
+
+        Ok(receipts)
+    }
+}
+
+fn inclusion<E: StaticEvent>(
+    submission: DeadlineSubmission,
+) -> Result<ProofInclusion<E>, Box<dyn std::error::Error>> {
+    let (tx_hash, event) = StorageTransaction::find_first::<E>(submission.events)?;
+    Ok(ProofInclusion {
+        tx_hash,
+        event,
+        block_number: submission.block_number,
+        tip: submission.tip,
+        bumps: submission.bumps,
+    })
+}
+
+fn check_proof(kind: &str, proof: &[u8], max: usize) -> Result<(), Error> {
+    if proof.is_empty() || proof.len() > max {
+        return Err(format!(
+            "{} proof must be between 1 and {} bytes, got {}",
+            kind,
+            max,
+            proof.len()
+        )
+        .into());
+    }
+    Ok(())
+}
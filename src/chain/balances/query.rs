@@ -0,0 +1,72 @@
+use crate::chain::{Chain, Query};
+use crate::core::ApiProvider;
+use crate::polkadot::{
+    self,
+    runtime_types::{frame_system::AccountInfo, pallet_balances::types::AccountData},
+    system::storage::StorageApi,
+};
+use crate::utils::account::IntoAccount;
+use crate::{impl_api_provider, init_api, H256};
+
+// Balances are kept in `System::Account`.
+// impl ApiProvider for StorageApiProvider
+impl_api_provider!(StorageApiProvider, StorageApi, polkadot::storage().system());
+
+/// Amounts are in the smallest unit, see [`crate::utils::format_cess`] for display.
+pub struct StorageQuery;
+
+impl Chain for StorageQuery {}
+
+impl Query for StorageQuery {
+    type Api = StorageApi;
+
+    fn get_api() -> Self::Api {
+        crate::core::get_api::<StorageApiProvider>()
+    }
+}
+
+impl StorageQuery {
+    pub async fn account_info(
+        account: impl IntoAccount,
+        block_hash: Option<H256>,
+    ) -> Result<Option<AccountInfo<u32, AccountData<u128>>>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let account = account.into_account()?;
+        let query = api.account(account);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    /// Free balance, 0 for accounts that do not exist.
+    pub async fn free_balance(
+        account: impl IntoAccount,
+        block_hash: Option<H256>,
+    ) -> Result<u128, Box<dyn std::error::Error>> {
+        let info = Self::account_info(account, block_hash).await?;
+        Ok(info.map_or(0, |info| info.data.free))
+    }
+
+    pub async fn reserved_balance(
+        account: impl IntoAccount,
+        block_hash: Option<H256>,
+    ) -> Result<u128, Box<dyn std::error::Error>> {
+        let info = Self::account_info(account, block_hash).await?;
+        Ok(info.map_or(0, |info| info.data.reserved))
+    }
+
+    pub async fn total_issuance(
+        block_hash: Option<H256>,
+    ) -> Result<u128, Box<dyn std::error::Error>> {
+        let query = polkadot::storage().balances().total_issuance();
+        Ok(Self::execute_query(&query, block_hash)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Minimum balance an account must keep to stay alive.
+    pub async fn existential_deposit() -> Result<u128, Box<dyn std::error::Error>> {
+        let api = init_api().await?;
+        let address = polkadot::constants().balances().existential_deposit();
+        Ok(api.constants().at(&address)?)
+    }
+}
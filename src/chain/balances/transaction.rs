@@ -1,12 +1,11 @@
-use crate::chain::{Call, Chain};
-use crate::core::ApiProvider;
+use crate::chain::{Call, Chain, TxOptions};
+use crate::core::{ApiProvider, Error};
 use crate::impl_api_provider;
 use crate::polkadot::balances::events::Transfer;
 use crate::polkadot::{self, balances::calls::TransactionApi};
+use crate::utils::account::IntoAccount;
 // use crate::utils::hash_from_string;
-use std::str::FromStr;
 use subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
-use subxt::ext::subxt_core::utils::AccountId32;
 use subxt::tx::PairSigner;
 use subxt::PolkadotConfig;
 
@@ -20,6 +19,7 @@ impl_api_provider!(
 pub type TxHash = String;
 pub struct StorageTransaction {
     pair: PairS,
+    options: TxOptions,
 }
 
 impl Chain for StorageTransaction {}
@@ -39,19 +39,65 @@ impl Call for StorageTransaction {
 impl StorageTransaction {
     pub fn new(mnemonic: &str) -> Self {
         let pair = PairS::from_string(mnemonic, None).unwrap();
-        Self { pair }
+        Self {
+            pair,
+            options: TxOptions::default(),
+        }
+    }
+
+    /// Applies `options` to every transaction submitted through this instance.
+    pub fn with_options(mut self, options: TxOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub async fn transfer(
         &self,
-        account: &str,
+        account: impl IntoAccount,
+        amount: u128,
+    ) -> Result<(TxHash, Transfer), Box<dyn std::error::Error>> {
+        self.transfer_allow_death(account, amount).await
+    }
+
+    /// Fee [`Self::transfer`] would pay, excluding `amount` itself.
+    pub async fn estimate_transfer_fee(
+        &self,
+        account: impl IntoAccount,
+        amount: u128,
+    ) -> Result<u128, Error> {
+        let api = Self::get_api();
+        let account = account.into_account()?;
+        let tx = api.transfer_allow_death(subxt::utils::MultiAddress::Id(account), amount);
+
+        self.estimate_partial_fee(&tx).await
+    }
+
+    /// Transfers `amount`, failing if the sender would drop below the existential deposit.
+    pub async fn transfer_keep_alive(
+        &self,
+        account: impl IntoAccount,
+        amount: u128,
+    ) -> Result<(TxHash, Transfer), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let account = account.into_account()?;
+        let tx = api.transfer_keep_alive(subxt::utils::MultiAddress::Id(account), amount);
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        Self::find_first::<Transfer>(event)
+    }
+
+    /// Transfers `amount`, reaping the sender if its balance falls below the existential deposit.
+    pub async fn transfer_allow_death(
+        &self,
+        account: impl IntoAccount,
         amount: u128,
     ) -> Result<(TxHash, Transfer), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let tx = api.transfer_allow_death(subxt::utils::MultiAddress::Id(account), amount);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<Transfer>(event)
     }
@@ -0,0 +1,217 @@
+use crate::chain::debug::bundled_metadata;
+use crate::chain::{Call, Chain, TxOptions};
+use crate::core::{ApiProvider, Error};
+use crate::polkadot::{
+    self, runtime_types::cess_node_runtime::RuntimeCall, utility::calls::TransactionApi,
+};
+use crate::{impl_api_provider, init_api};
+use subxt::events::EventDetails;
+use subxt::ext::codec::{self, Decode, Input};
+use subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
+use subxt::tx::{PairSigner, Payload};
+use subxt::PolkadotConfig;
+
+// impl ApiProvider for TransactionApiProvider
+impl_api_provider!(
+    TransactionApiProvider,
+    TransactionApi,
+    polkadot::tx().utility()
+);
+
+pub type TxHash = String;
+
+/// Submits several calls from one signer as a single `utility.batch_all` extrinsic.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// use cess_rust_sdk::chain::batch::BatchTransaction;
+/// use cess_rust_sdk::polkadot::{self, runtime_types::bounded_collections::bounded_vec::BoundedVec};
+///
+/// let mnemonic = "bottom drive obey lake curtain smoke basket hold race lonely fit walk//Alice";
+/// let territory = || BoundedVec(b"hello".to_vec());
+/// let (tx_hash, events) = BatchTransaction::new(mnemonic)
+///     .add(&polkadot::tx().storage_handler().renewal_territory(territory(), 30))?
+///     .add(&polkadot::tx().storage_handler().expanding_territory(territory(), 1))?
+///     .submit_all()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BatchTransaction {
+    pair: PairS,
+    options: TxOptions,
+    calls: Vec<RuntimeCall>,
+}
+
+impl Chain for BatchTransaction {}
+
+impl Call for BatchTransaction {
+    type Api = TransactionApi;
+
+    fn get_api() -> Self::Api {
+        crate::core::get_api::<TransactionApiProvider>()
+    }
+
+    fn get_pair_signer(&self) -> PairSigner<PolkadotConfig, PairS> {
+        PairSigner::new(self.pair.clone())
+    }
+}
+
+impl BatchTransaction {
+    pub fn new(mnemonic: &str) -> Self {
+        let pair = PairS::from_string(mnemonic, None).unwrap();
+        Self::from_pair(pair)
+    }
+
+    /// A batch signed by the signer of another transaction type.
+    pub(crate) fn from_pair(pair: PairS) -> Self {
+        Self {
+            pair,
+            options: TxOptions::default(),
+            calls: Vec::new(),
+        }
+    }
+
+    /// Applies `options` to the batch extrinsic.
+    pub fn with_options(mut self, options: TxOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Appends any call payload, e.g. one built with `polkadot::tx()`.
+    pub fn add(mut self, call: &impl Payload) -> Result<Self, Error> {
+        let metadata = bundled_metadata()?;
+        let call_data = call.encode_call_data(&metadata)?;
+        let call = RuntimeCall::decode(&mut &call_data[..])?;
+        self.calls.push(call);
+        Ok(self)
+    }
+
+    /// Appends a call already in its runtime form, skipping the encoding round trip of `add`.
+    pub(crate) fn add_call(mut self, call: RuntimeCall) -> Self {
+        self.calls.push(call);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Submits all calls atomically and returns the events emitted by each call, in call order.
+    ///
+    /// If one call fails nothing is applied, and the error is [`Error::BatchItemFailed`] when the
+    /// failing call can be identified.
+    pub async fn submit_all(
+        &self,
+    ) -> Result<(TxHash, Vec<Vec<EventDetails<PolkadotConfig>>>), Box<dyn std::error::Error>> {
+        if self.calls.is_empty() {
+            return Err("Error: Empty batch".into());
+        }
+
+        let api = Self::get_api();
+        let tx = api.batch_all(self.calls.clone());
+        let from = self.get_pair_signer();
+        let result = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await;
+        // SDK errors (e.g. an unconfirmed submission) say nothing about the calls themselves,
+        // dispatch errors come from one of them.
+        let result = match result {
+            Ok(events) => Ok(events),
+            Err(e) => match e.downcast_ref::<Error>() {
+                Some(
+                    Error::Pallet { .. }
+                    | Error::TerritoryFrozen { .. }
+                    | Error::TerritoryOnConsignment
+                    | Error::ConsignmentLocked
+                    | Error::Subxt(subxt::Error::Runtime(_)),
+                )
+                | None => Err(e.to_string()),
+                Some(_) => return Err(e),
+            },
+        };
+        let events = match result {
+            Ok(events) => events,
+            Err(message) => {
+                return Err(match self.locate_failure().await {
+                    Some(index) => Error::BatchItemFailed {
+                        index,
+                        error: message,
+                    }
+                    .into(),
+                    None => message.into(),
+                })
+            }
+        };
+
+        let hash = format!("0x{}", hex::encode(events.extrinsic_hash().0));
+        // batch_all emits `ItemCompleted` after the events of each call.
+        let mut per_call = vec![Vec::new()];
+        for event in events.iter() {
+            let event = event?;
+            if event.pallet_name() == "Utility" && event.variant_name() == "ItemCompleted" {
+                per_call.push(Vec::new());
+            } else if event.pallet_name() == "Utility" && event.variant_name() == "BatchCompleted" {
+                continue;
+            } else if let Some(current) = per_call.last_mut() {
+                current.push(event);
+            }
+        }
+        per_call.truncate(self.calls.len());
+
+        Ok((hash, per_call))
+    }
+
+    /// Applies growing prefixes of the batch against the latest state to find the failing call.
+    async fn locate_failure(&self) -> Option<usize> {
+        let api = init_api().await.ok()?;
+        let from = self.get_pair_signer();
+        for index in 0..self.calls.len() {
+            let tx = Self::get_api().batch_all(self.calls[..=index].to_vec());
+            let extrinsic = api
+                .tx()
+                .create_signed(&tx, &from, Default::default())
+                .await
+                .ok()?;
+            let outcome: ApplyOutcome = api
+                .runtime_api()
+                .at_latest()
+                .await
+                .ok()?
+                .call_raw("BlockBuilder_apply_extrinsic", Some(extrinsic.encoded()))
+                .await
+                .ok()?;
+            match outcome {
+                ApplyOutcome::DispatchFailed(_) => return Some(index),
+                ApplyOutcome::Invalid => return None,
+                ApplyOutcome::Success => {}
+            }
+        }
+        None
+    }
+}
+
+/// Outcome of `BlockBuilder_apply_extrinsic`, which encodes
+/// `Result<Result<(), DispatchError>, TransactionValidityError>`.
+pub(crate) enum ApplyOutcome {
+    Success,
+    /// The encoded `DispatchError`.
+    DispatchFailed(Vec<u8>),
+    Invalid,
+}
+
+impl Decode for ApplyOutcome {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, codec::Error> {
+        if input.read_byte()? != 0 {
+            return Ok(Self::Invalid);
+        }
+        if input.read_byte()? == 0 {
+            return Ok(Self::Success);
+        }
+        let mut bytes = vec![0; input.remaining_len()?.unwrap_or(0)];
+        input.read(&mut bytes)?;
+        Ok(Self::DispatchFailed(bytes))
+    }
+}
@@ -0,0 +1,176 @@
+use crate::chain::block_timestamp;
+use crate::constants::BLOCK_INTERVAL;
+use crate::core::Error;
+use crate::init_api;
+use crate::utils::get_block_hash_by_number;
+use futures::StreamExt;
+use log::warn;
+use std::time::{Duration, Instant, SystemTime};
+use subxt::backend::StreamOfResults;
+use subxt::blocks::Block;
+use subxt::{OnlineClient, PolkadotConfig};
+
+type FinalizedBlock = Block<PolkadotConfig, OnlineClient<PolkadotConfig>>;
+
+/// Interval between checks of the latest block while no subscription is available.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Paces polling on finalized block arrival rather than fixed sleeps.
+///
+/// Follows finalized heads through a subscription, and polls the latest block instead when the
+/// subscription cannot be established or ends.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), cess_rust_sdk::core::Error> {
+/// use cess_rust_sdk::chain::blocks::BlockClock;
+///
+/// let mut clock = BlockClock::new().await;
+/// let number = clock.wait_blocks(3).await?;
+/// println!("#{}, ~{:?} per block", number, clock.average_block_time());
+/// # Ok(())
+/// # }
+/// ```
+pub struct BlockClock {
+    blocks: Option<StreamOfResults<FinalizedBlock>>,
+    last: Option<(u64, Instant)>,
+    average: Duration,
+}
+
+impl BlockClock {
+    pub async fn new() -> Self {
+        let mut clock = Self {
+            blocks: None,
+            last: None,
+            average: BLOCK_INTERVAL,
+        };
+        clock.subscribe().await;
+        clock
+    }
+
+    /// Waits for the next finalized block and returns its number.
+    pub async fn wait_next_block(&mut self) -> Result<u64, Error> {
+        loop {
+            if self.blocks.is_none() {
+                self.subscribe().await;
+            }
+
+            let next = match self.blocks.as_mut() {
+                Some(blocks) => blocks.next().await,
+                None => return self.poll_next_block().await,
+            };
+            match next {
+                Some(Ok(block)) => {
+                    let number: u64 = block.number().into();
+                    if !matches!(self.last, Some((last, _)) if number <= last) {
+                        self.observe(number);
+                        return Ok(number);
+                    }
+                }
+                Some(Err(e)) => {
+                    warn!(target: "SDK", "Finalized block subscription failed: {}", e);
+                    self.blocks = None;
+                    return self.poll_next_block().await;
+                }
+                None => {
+                    self.blocks = None;
+                    return self.poll_next_block().await;
+                }
+            }
+        }
+    }
+
+    /// Waits until `n` more blocks are finalized and returns the number of the last one.
+    pub async fn wait_blocks(&mut self, n: u32) -> Result<u64, Error> {
+        let mut number = match self.last {
+            Some((number, _)) => number,
+            None => latest_finalized().await?,
+        };
+        let target = number + n as u64;
+        while number < target {
+            number = self.wait_next_block().await?;
+        }
+        Ok(number)
+    }
+
+    /// Moving average of the observed block time, [`BLOCK_INTERVAL`] until blocks were seen.
+    pub fn average_block_time(&self) -> Duration {
+        self.average
+    }
+
+    /// Number of the first block produced at or after `time`, found by bisecting on the
+    /// `Timestamp::Now` of past blocks. Times after the latest block give the numbers blocks are
+    /// expected to have, counting [`Self::average_block_time`] per block.
+    pub async fn estimate_block_at(&self, time: SystemTime) -> Result<u64, Error> {
+        let api = init_api().await?;
+        let latest = api.blocks().at_latest().await?;
+        let number: u64 = latest.number().into();
+        let latest_ms = block_timestamp(latest.hash())
+            .await?
+            .ok_or_else(|| Error::Custom(format!("Block #{} has no timestamp", number)))?;
+
+        let time_ms = time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        if time_ms > latest_ms {
+            let block_ms = (self.average.as_millis() as u64).max(1);
+            return Ok(number + (time_ms - latest_ms).div_ceil(block_ms));
+        }
+
+        // The block at `high` is never older than `time`.
+        let (mut low, mut high) = (0, number);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if timestamp_of(mid).await? >= time_ms {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Ok(high)
+    }
+
+    async fn subscribe(&mut self) {
+        let blocks = match init_api().await {
+            Ok(api) => api.blocks().subscribe_finalized().await.ok(),
+            Err(_) => None,
+        };
+        self.blocks = blocks;
+    }
+
+    async fn poll_next_block(&mut self) -> Result<u64, Error> {
+        let start = match self.last {
+            Some((number, _)) => number,
+            None => latest_finalized().await?,
+        };
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let number = latest_finalized().await?;
+            if number > start {
+                self.observe(number);
+                return Ok(number);
+            }
+        }
+    }
+
+    fn observe(&mut self, number: u64) {
+        let now = Instant::now();
+        if let Some((last, at)) = self.last {
+            let blocks = (number - last) as u32;
+            let sample = now.duration_since(at) / blocks;
+            self.average = (self.average * 7 + sample) / 8;
+        }
+        self.last = Some((number, now));
+    }
+}
+
+async fn latest_finalized() -> Result<u64, Error> {
+    let api = init_api().await?;
+    Ok(api.blocks().at_latest().await?.number().into())
+}
+
+/// `Timestamp::Now` of block `number`, 0 for the genesis block which has none.
+async fn timestamp_of(number: u64) -> Result<u64, Error> {
+    let hash = get_block_hash_by_number(number).await?;
+    Ok(block_timestamp(hash).await?.unwrap_or(0))
+}
@@ -0,0 +1,330 @@
+use crate::chain::storage_at;
+use crate::core::Error;
+use crate::utils::{get_block_hash_by_number, hash_from_string};
+use crate::{init_api, H256};
+use scale_info::{form::PortableForm, PortableRegistry, Type, TypeDef, TypeDefPrimitive};
+use serde::Serialize;
+use serde_json::{Map, Number, Value as JsonValue};
+use std::str::FromStr;
+use subxt::dynamic::{self, Value};
+use subxt::ext::codec::Decode;
+use subxt::ext::scale_value::{self, Composite, Primitive, ValueDef};
+use subxt::metadata::types::StorageEntryType;
+use subxt::utils::AccountId32;
+use subxt::Metadata;
+
+static BUNDLED_METADATA: &[u8] = include_bytes!("../../metadata/metadata.scale");
+
+/// A single storage key part for dynamic queries.
+pub enum DynamicKey {
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Bool(bool),
+    /// SS58 encoded account.
+    Account(String),
+    /// 64 character file/fragment hash (`cp_cess_common::Hash`).
+    FileHash(String),
+    /// Hex encoded H256, with or without `0x` prefix.
+    H256(String),
+    /// UTF-8 name stored as `BoundedVec<u8>` (bucket, territory, ...).
+    Name(String),
+    Bytes(Vec<u8>),
+}
+
+impl TryFrom<&DynamicKey> for Value {
+    type Error = Error;
+
+    fn try_from(key: &DynamicKey) -> Result<Self, Self::Error> {
+        let value = match key {
+            DynamicKey::U32(v) => Value::u128(*v as u128),
+            DynamicKey::U64(v) => Value::u128(*v as u128),
+            DynamicKey::U128(v) => Value::u128(*v),
+            DynamicKey::Bool(v) => Value::bool(*v),
+            DynamicKey::Account(v) => {
+                let account = AccountId32::from_str(v).map_err(|e| Error::Custom(e.to_string()))?;
+                Value::from_bytes(account.0)
+            }
+            DynamicKey::FileHash(v) => {
+                let hash = hash_from_string(v)?;
+                Value::from_bytes(hash.0)
+            }
+            DynamicKey::H256(v) => {
+                let hash = H256::from_str(v).map_err(|e| Error::Custom(e.to_string()))?;
+                Value::from_bytes(hash.0)
+            }
+            DynamicKey::Name(v) => Value::from_bytes(v.as_bytes()),
+            DynamicKey::Bytes(v) => Value::from_bytes(v),
+        };
+        Ok(value)
+    }
+}
+
+/// Block a dynamic query reads storage at.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum BlockRef {
+    #[default]
+    Latest,
+    Hash(H256),
+    Number(u64),
+}
+
+impl BlockRef {
+    async fn hash(self) -> Result<Option<H256>, Error> {
+        match self {
+            Self::Latest => Ok(None),
+            Self::Hash(hash) => Ok(Some(hash)),
+            Self::Number(n) => get_block_hash_by_number(n).await.map(Some),
+        }
+    }
+}
+
+impl From<H256> for BlockRef {
+    fn from(hash: H256) -> Self {
+        Self::Hash(hash)
+    }
+}
+
+impl From<Option<H256>> for BlockRef {
+    fn from(hash: Option<H256>) -> Self {
+        hash.map_or(Self::Latest, Self::Hash)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageEntryInfo {
+    pub name: String,
+    pub key_type: Option<String>,
+    pub value_type: String,
+    pub docs: String,
+}
+
+/// Fetches any storage entry by pallet and entry name and renders it as JSON, `null` when no
+/// value is stored.
+///
+/// Complements the typed queries for entries the SDK has not wrapped yet.
+pub async fn fetch_dynamic(
+    pallet: &str,
+    entry: &str,
+    keys: &[DynamicKey],
+    block: BlockRef,
+) -> Result<JsonValue, Error> {
+    let api = init_api().await?;
+    let keys = keys
+        .iter()
+        .map(Value::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    let address = dynamic::storage(pallet, entry, keys);
+
+    let block_hash = block.hash().await?;
+
+    match storage_at(block_hash).await?.fetch(&address).await? {
+        Some(thunk) => {
+            let value = thunk.to_value()?;
+            Ok(value_to_json(&value, api.metadata().types()))
+        }
+        None => Ok(JsonValue::Null),
+    }
+}
+
+/// Lists the storage items of a pallet with their key and value type names.
+pub async fn list_entries(pallet: &str) -> Result<Vec<StorageEntryInfo>, Error> {
+    let api = init_api().await?;
+    let metadata = api.metadata();
+    let types = metadata.types();
+
+    let pallet_metadata = metadata
+        .pallet_by_name(pallet)
+        .ok_or_else(|| Error::Custom(format!("Pallet '{}' not found in metadata", pallet)))?;
+    let storage = match pallet_metadata.storage() {
+        Some(storage) => storage,
+        None => return Ok(Vec::new()),
+    };
+
+    let entries = storage
+        .entries()
+        .iter()
+        .map(|entry| {
+            let (key_type, value_type) = match entry.entry_type() {
+                StorageEntryType::Plain(value_ty) => (None, type_name(types, *value_ty)),
+                StorageEntryType::Map {
+                    key_ty, value_ty, ..
+                } => (Some(type_name(types, *key_ty)), type_name(types, *value_ty)),
+            };
+            StorageEntryInfo {
+                name: entry.name().to_string(),
+                key_type,
+                value_type,
+                docs: entry.docs().join("\n"),
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// Metadata the SDK was generated from, usable without a connection.
+pub fn bundled_metadata() -> Result<Metadata, Error> {
+    Metadata::decode(&mut &BUNDLED_METADATA[..]).map_err(Error::from)
+}
+
+/// Decodes hex encoded call data (pallet index, call index and arguments).
+pub fn decode_call_hex(hex_str: &str) -> Result<JsonValue, Error> {
+    let metadata = bundled_metadata()?;
+    let type_id = metadata.outer_enums().call_enum_ty();
+    decode_hex_as_type(hex_str, type_id, &metadata)
+}
+
+/// Decodes hex encoded event bytes (pallet index, event index and fields).
+pub fn decode_event_hex(hex_str: &str) -> Result<JsonValue, Error> {
+    let metadata = bundled_metadata()?;
+    let type_id = metadata.outer_enums().event_enum_ty();
+    decode_hex_as_type(hex_str, type_id, &metadata)
+}
+
+/// Decodes a hex encoded storage value of `pallet`/`entry`.
+pub fn decode_storage_value(pallet: &str, entry: &str, hex_str: &str) -> Result<JsonValue, Error> {
+    let metadata = bundled_metadata()?;
+    let type_id = metadata
+        .pallet_by_name(pallet)
+        .and_then(|p| p.storage())
+        .and_then(|s| s.entry_by_name(entry))
+        .map(|e| e.entry_type().value_ty())
+        .ok_or_else(|| Error::Custom(format!("Storage entry {}::{} not found", pallet, entry)))?;
+    decode_hex_as_type(hex_str, type_id, &metadata)
+}
+
+fn decode_hex_as_type(
+    hex_str: &str,
+    type_id: u32,
+    metadata: &Metadata,
+) -> Result<JsonValue, Error> {
+    let hex_str = hex_str.trim();
+    let bytes = hex::decode(hex_str.strip_prefix("0x").unwrap_or(hex_str))
+        .map_err(|e| Error::InvalidInput(format!("Invalid hex: {}", e)))?;
+
+    let cursor = &mut &bytes[..];
+    let value = scale_value::scale::decode_as_type(cursor, type_id, metadata.types())
+        .map_err(|e| Error::Custom(format!("Failed to decode: {}", e)))?;
+    if !cursor.is_empty() {
+        return Err(format!("{} trailing bytes left after decoding", cursor.len()).into());
+    }
+
+    Ok(value_to_json(&value, metadata.types()))
+}
+
+pub(crate) fn type_name(types: &PortableRegistry, id: u32) -> String {
+    let ty = match types.resolve(id) {
+        Some(ty) => ty,
+        None => return format!("<unknown type {}>", id),
+    };
+
+    let params: Vec<String> = ty
+        .type_params
+        .iter()
+        .filter_map(|param| param.ty.map(|ty| type_name(types, ty.id)))
+        .collect();
+
+    if let Some(ident) = ty.path.ident() {
+        return if params.is_empty() {
+            ident
+        } else {
+            format!("{}<{}>", ident, params.join(", "))
+        };
+    }
+
+    match &ty.type_def {
+        TypeDef::Primitive(primitive) => format!("{:?}", primitive).to_lowercase(),
+        TypeDef::Sequence(seq) => format!("Vec<{}>", type_name(types, seq.type_param.id)),
+        TypeDef::Array(arr) => format!("[{}; {}]", type_name(types, arr.type_param.id), arr.len),
+        TypeDef::Tuple(tuple) => format!(
+            "({})",
+            tuple
+                .fields
+                .iter()
+                .map(|field| type_name(types, field.id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TypeDef::Compact(compact) => {
+            format!("Compact<{}>", type_name(types, compact.type_param.id))
+        }
+        TypeDef::BitSequence(_) => "BitSequence".to_string(),
+        _ => format!("<type {}>", id),
+    }
+}
+
+pub(crate) fn value_to_json(value: &Value<u32>, types: &PortableRegistry) -> JsonValue {
+    match &value.value {
+        ValueDef::Composite(composite) => composite_to_json(composite, types),
+        ValueDef::Variant(variant) => {
+            if variant.values.is_empty() {
+                return JsonValue::String(variant.name.clone());
+            }
+            let mut map = Map::new();
+            map.insert(
+                variant.name.clone(),
+                composite_to_json(&variant.values, types),
+            );
+            JsonValue::Object(map)
+        }
+        ValueDef::BitSequence(bits) => JsonValue::Array(bits.iter().map(JsonValue::Bool).collect()),
+        ValueDef::Primitive(primitive) => primitive_to_json(primitive),
+    }
+}
+
+fn composite_to_json(composite: &Composite<u32>, types: &PortableRegistry) -> JsonValue {
+    match composite {
+        Composite::Named(fields) => JsonValue::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), value_to_json(value, types)))
+                .collect(),
+        ),
+        Composite::Unnamed(values) => {
+            // Byte sequences and arrays are far easier to read as hex.
+            if !values.is_empty() && values.iter().all(|v| is_u8(types.resolve(v.context))) {
+                let bytes: Vec<u8> = values
+                    .iter()
+                    .filter_map(|v| match v.value {
+                        ValueDef::Primitive(Primitive::U128(n)) => Some(n as u8),
+                        _ => None,
+                    })
+                    .collect();
+                return JsonValue::String(format!("0x{}", hex::encode(bytes)));
+            }
+            // Newtype wrappers (BoundedVec, Hash, ...) are flattened.
+            if values.len() == 1 {
+                return value_to_json(&values[0], types);
+            }
+            JsonValue::Array(values.iter().map(|v| value_to_json(v, types)).collect())
+        }
+    }
+}
+
+fn is_u8(ty: Option<&Type<PortableForm>>) -> bool {
+    matches!(
+        ty.map(|ty| &ty.type_def),
+        Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+    )
+}
+
+fn primitive_to_json(primitive: &Primitive) -> JsonValue {
+    match primitive {
+        Primitive::Bool(v) => JsonValue::Bool(*v),
+        Primitive::Char(v) => JsonValue::String(v.to_string()),
+        Primitive::String(v) => JsonValue::String(v.clone()),
+        // JSON numbers lose precision above u64, so large balances are strings.
+        Primitive::U128(v) => match u64::try_from(*v) {
+            Ok(v) => JsonValue::Number(Number::from(v)),
+            Err(_) => JsonValue::String(v.to_string()),
+        },
+        Primitive::I128(v) => match i64::try_from(*v) {
+            Ok(v) => JsonValue::Number(Number::from(v)),
+            Err(_) => JsonValue::String(v.to_string()),
+        },
+        Primitive::U256(v) | Primitive::I256(v) => {
+            JsonValue::String(format!("0x{}", hex::encode(v)))
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! secp256k1 keys, as used by Ethereum wallets.
+//!
+//! The same key signs extrinsics, through the [`Signer`] implementation, and Ethereum style
+//! messages such as the payload of `oss::transaction::StorageTransaction::evm_proxy_authorzie`,
+//! through [`EcdsaSigner::sign_eth_message`]. The two hash the payload differently: the runtime
+//! verifies ECDSA extrinsic signatures over its blake2-256 hash, Ethereum over its keccak-256
+//! hash.
+
+use crate::core::Error;
+use k256::ecdsa::SigningKey;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::SecretKey;
+use subxt::ext::sp_core::hashing::{blake2_256, keccak_256};
+use subxt::tx::Signer;
+use subxt::utils::{AccountId32, MultiAddress, MultiSignature};
+use subxt::Config;
+
+pub struct EcdsaSigner {
+    secret: SecretKey,
+    account_id: AccountId32,
+}
+
+impl EcdsaSigner {
+    pub fn new(secret: SecretKey) -> Self {
+        // Accounts of ECDSA keys are the blake2-256 hash of the compressed public key.
+        let public = secret.public_key().to_encoded_point(true);
+        let account_id = AccountId32(blake2_256(public.as_bytes()));
+        Self { secret, account_id }
+    }
+
+    /// The 33 byte compressed public key.
+    pub fn public_key(&self) -> [u8; 33] {
+        let mut key = [0u8; 33];
+        key.copy_from_slice(self.secret.public_key().to_encoded_point(true).as_bytes());
+        key
+    }
+
+    /// The 20 byte Ethereum address of the key.
+    pub fn eth_address(&self) -> [u8; 20] {
+        let public = self.secret.public_key().to_encoded_point(false);
+        let hash = keccak_256(&public.as_bytes()[1..]);
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&hash[12..]);
+        address
+    }
+
+    /// Signs the keccak-256 hash of `message`, returning `r || s || v`.
+    pub fn sign_eth_message(&self, message: &[u8]) -> [u8; 65] {
+        self.sign_prehashed(&keccak_256(message))
+    }
+
+    fn sign_prehashed(&self, digest: &[u8; 32]) -> [u8; 65] {
+        let key = SigningKey::from(&self.secret);
+        // A 32 byte digest is always accepted.
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(digest)
+            .expect("32 byte prehash");
+
+        let mut out = [0u8; 65];
+        out[..64].copy_from_slice(&signature.to_bytes());
+        out[64] = recovery_id.to_byte();
+        out
+    }
+}
+
+/// Signs for any config with the account, address and signature types of `PolkadotConfig`.
+impl<C> Signer<C> for EcdsaSigner
+where
+    C: Config<
+        AccountId = AccountId32,
+        Address = MultiAddress<AccountId32, ()>,
+        Signature = MultiSignature,
+    >,
+{
+    fn account_id(&self) -> AccountId32 {
+        self.account_id.clone()
+    }
+
+    fn address(&self) -> MultiAddress<AccountId32, ()> {
+        MultiAddress::Id(self.account_id.clone())
+    }
+
+    fn sign(&self, signer_payload: &[u8]) -> MultiSignature {
+        MultiSignature::Ecdsa(self.sign_prehashed(&blake2_256(signer_payload)))
+    }
+}
+
+pub fn ecdsa_signer_from_hex(private_key_hex: &str) -> Result<EcdsaSigner, Error> {
+    let private_key_hex = private_key_hex.trim();
+    let bytes = hex::decode(
+        private_key_hex
+            .strip_prefix("0x")
+            .unwrap_or(private_key_hex),
+    )
+    .map_err(|e| Error::InvalidInput(format!("Invalid private key hex: {}", e)))?;
+    let secret = SecretKey::from_slice(&bytes)
+        .map_err(|_| Error::InvalidInput("Invalid secp256k1 private key".into()))?;
+
+    Ok(EcdsaSigner::new(secret))
+}
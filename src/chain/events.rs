@@ -0,0 +1,176 @@
+use crate::core::Error;
+use crate::{check_api_health, client, init_api, H256};
+use futures::stream::{self, Stream, StreamExt};
+use log::warn;
+use std::collections::VecDeque;
+use std::time::Duration;
+use subxt::backend::StreamOfResults;
+use subxt::blocks::Block;
+use subxt::events::{EventDetails, StaticEvent};
+use subxt::{OnlineClient, PolkadotConfig};
+
+type FinalizedBlock = Block<PolkadotConfig, OnlineClient<PolkadotConfig>>;
+
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Streams every `E` emitted in finalized blocks, with the hash of the block it was found in.
+///
+/// ```no_run
+/// # async fn run() {
+/// use cess_rust_sdk::chain::events::subscribe_events;
+/// use cess_rust_sdk::polkadot::file_bank::events::TransferReport;
+/// use futures::StreamExt;
+///
+/// let mut reports = Box::pin(subscribe_events::<TransferReport>());
+/// while let Some(report) = reports.next().await {
+///     println!("{:?}", report);
+/// }
+/// # }
+/// ```
+pub fn subscribe_events<E>() -> impl Stream<Item = Result<(H256, E), Error>> + Send
+where
+    E: StaticEvent + Send + 'static,
+{
+    subscribe_events_filtered(|_: &E| true)
+}
+
+/// Like [`subscribe_events`], keeping only events `filter` accepts, e.g. `DeleteFile` events
+/// whose `operator` is a given account.
+///
+/// When the connection drops, the client is re-established and the finalized blocks missed in
+/// between are scanned before following new ones, so no event is skipped. Connection failures are
+/// yielded as errors without ending the stream.
+pub fn subscribe_events_filtered<E, F>(
+    filter: F,
+) -> impl Stream<Item = Result<(H256, E), Error>> + Send
+where
+    E: StaticEvent + Send + 'static,
+    F: Fn(&E) -> bool + Send + 'static,
+{
+    subscribe_mapped(move |event| Ok(event.as_event::<E>()?.filter(|event| filter(event))))
+}
+
+/// Streams whatever `map` extracts from the events of finalized blocks, for subscriptions
+/// spanning several event types. Events `map` returns `None` for are skipped.
+pub fn subscribe_mapped<T, F>(map: F) -> impl Stream<Item = Result<(H256, T), Error>> + Send
+where
+    T: Send + 'static,
+    F: Fn(&EventDetails<PolkadotConfig>) -> Result<Option<T>, Error> + Send + 'static,
+{
+    let state = Subscription {
+        map,
+        blocks: None,
+        last_number: None,
+        pending: VecDeque::new(),
+        reconnect_delay: None,
+    };
+
+    stream::unfold(state, |mut state| async move {
+        let item = state.next_event().await;
+        Some((item, state))
+    })
+}
+
+struct Subscription<T, F> {
+    map: F,
+    blocks: Option<StreamOfResults<FinalizedBlock>>,
+    last_number: Option<u64>,
+    pending: VecDeque<(H256, T)>,
+    /// Set after a connection failure, doubled on every further failure.
+    reconnect_delay: Option<Duration>,
+}
+
+impl<T, F> Subscription<T, F>
+where
+    T: Send + 'static,
+    F: Fn(&EventDetails<PolkadotConfig>) -> Result<Option<T>, Error> + Send + 'static,
+{
+    async fn next_event(&mut self) -> Result<(H256, T), Error> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+
+            let blocks = match self.blocks.as_mut() {
+                Some(blocks) => blocks,
+                None => {
+                    self.connect().await?;
+                    continue;
+                }
+            };
+
+            match blocks.next().await {
+                Some(Ok(block)) => {
+                    if let Err(e) = self.collect_up_to(block).await {
+                        self.blocks = None;
+                        return Err(e);
+                    }
+                }
+                Some(Err(e)) => {
+                    warn!(target: "SDK", "Finalized block subscription failed: {}", e);
+                    self.blocks = None;
+                }
+                None => self.blocks = None,
+            }
+        }
+    }
+
+    /// Subscribes through the current client. The global client is only replaced when it no
+    /// longer answers, other tasks using it are left alone otherwise.
+    async fn connect(&mut self) -> Result<(), Error> {
+        match self.reconnect_delay {
+            Some(delay) => {
+                tokio::time::sleep(delay).await;
+                self.reconnect_delay = Some((delay * 2).min(MAX_RECONNECT_DELAY));
+            }
+            None => self.reconnect_delay = Some(Duration::from_secs(1)),
+        }
+
+        let api = init_api().await?;
+        match api.blocks().subscribe_finalized().await {
+            Ok(blocks) => {
+                self.blocks = Some(blocks);
+                Ok(())
+            }
+            Err(e) => {
+                if client::scoped_client().is_none() {
+                    // Retires the global client if it is dead, the next attempt reconnects.
+                    let _ = check_api_health().await;
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Collects events of `block` and of any finalized blocks skipped since the last one seen.
+    async fn collect_up_to(&mut self, block: FinalizedBlock) -> Result<(), Error> {
+        let number: u64 = block.number().into();
+        let mut blocks = vec![block];
+        if let Some(last) = self.last_number {
+            if number <= last {
+                return Ok(());
+            }
+            let api = init_api().await?;
+            while let Some(oldest) = blocks.last() {
+                if u64::from(oldest.number()) <= last + 1 {
+                    break;
+                }
+                let parent = api.blocks().at(oldest.header().parent_hash).await?;
+                blocks.push(parent);
+            }
+        }
+
+        for block in blocks.into_iter().rev() {
+            let events = block.events().await?;
+            for event in events.iter() {
+                if let Some(item) = (self.map)(&event?)? {
+                    self.pending.push_back((block.hash(), item));
+                }
+            }
+            self.last_number = Some(block.number().into());
+        }
+
+        self.reconnect_delay = None;
+        Ok(())
+    }
+}
@@ -1,2 +1,5 @@
+pub mod events;
 pub mod query;
+pub mod reporting;
 pub mod transaction;
+pub mod types;
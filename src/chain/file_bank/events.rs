@@ -0,0 +1,76 @@
+use crate::chain::events::subscribe_mapped;
+use crate::core::Error;
+use crate::init_api;
+use crate::polkadot::file_bank::events::{
+    ClaimRestoralOrder, DeleteFile, GenerateRestoralOrder, TransferReport, UploadDeclaration,
+};
+use futures::{Stream, StreamExt};
+use subxt::events::EventDetails;
+use subxt::utils::AccountId32;
+use subxt::PolkadotConfig;
+
+/// File lifecycle events of the `FileBank` pallet.
+#[derive(Debug)]
+pub enum FileBankEvent {
+    Upload(UploadDeclaration),
+    Delete(DeleteFile),
+    TransferReport(TransferReport),
+    GenerateRestoralOrder(GenerateRestoralOrder),
+    ClaimRestoralOrder(ClaimRestoralOrder),
+}
+
+impl FileBankEvent {
+    /// Accounts the event concerns: operator and owner, reporting account or miner.
+    pub fn accounts(&self) -> Vec<&AccountId32> {
+        match self {
+            FileBankEvent::Upload(e) => vec![&e.operator, &e.owner],
+            FileBankEvent::Delete(e) => vec![&e.operator, &e.owner],
+            FileBankEvent::TransferReport(e) => vec![&e.acc],
+            FileBankEvent::GenerateRestoralOrder(e) => vec![&e.miner],
+            FileBankEvent::ClaimRestoralOrder(e) => vec![&e.miner],
+        }
+    }
+
+    fn from_details(event: &EventDetails<PolkadotConfig>) -> Result<Option<Self>, Error> {
+        if let Some(e) = event.as_event::<UploadDeclaration>()? {
+            return Ok(Some(FileBankEvent::Upload(e)));
+        }
+        if let Some(e) = event.as_event::<DeleteFile>()? {
+            return Ok(Some(FileBankEvent::Delete(e)));
+        }
+        if let Some(e) = event.as_event::<TransferReport>()? {
+            return Ok(Some(FileBankEvent::TransferReport(e)));
+        }
+        if let Some(e) = event.as_event::<GenerateRestoralOrder>()? {
+            return Ok(Some(FileBankEvent::GenerateRestoralOrder(e)));
+        }
+        if let Some(e) = event.as_event::<ClaimRestoralOrder>()? {
+            return Ok(Some(FileBankEvent::ClaimRestoralOrder(e)));
+        }
+        Ok(None)
+    }
+}
+
+/// Streams file events from finalized blocks, only those concerning `account` when given.
+///
+/// Fails right away when no connection can be made; later connection failures are yielded by
+/// the stream, which keeps following the chain, see [`subscribe_mapped`].
+pub async fn subscribe_file_events(
+    account: Option<AccountId32>,
+) -> Result<impl Stream<Item = Result<FileBankEvent, Error>>, Error> {
+    init_api().await?;
+
+    let events = subscribe_mapped(move |details| {
+        if details.pallet_name() != "FileBank" {
+            return Ok(None);
+        }
+        Ok(
+            FileBankEvent::from_details(details)?.filter(|event| match &account {
+                Some(account) => event.accounts().contains(&account),
+                None => true,
+            }),
+        )
+    });
+
+    Ok(events.map(|item| item.map(|(_, event)| event)))
+}
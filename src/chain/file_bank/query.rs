@@ -1,19 +1,27 @@
+use super::types::{FileMetadata, StorageOrder};
+use crate::chain::blocks::BlockClock;
+use crate::chain::sanity::checked;
 use crate::chain::{Chain, Query};
-use crate::core::ApiProvider;
+use crate::constants::{DATA_SHARDS, FRAEMENT_SIZE, PAR_SHARDS};
+use crate::core::{ApiProvider, Error};
 use crate::polkadot::{
     self,
     file_bank::storage::StorageApi,
     runtime_types::{
         bounded_collections::bounded_vec::BoundedVec,
         pallet_file_bank::types::{
-            BucketInfo, DealInfo, FileInfo, RestoralOrderInfo, UserFileSliceInfo,
+            BucketInfo, DealInfo, FileInfo, FileState, RestoralOrderInfo, UserFileSliceInfo,
         },
     },
 };
-use crate::utils::hash_from_string;
+use crate::utils::{hash_from_string, hash_to_string};
 use crate::{impl_api_provider, H256};
+use futures::{stream, StreamExt, TryStreamExt};
 use std::str::FromStr;
-use subxt::utils::AccountId32;
+use std::time::{Duration, Instant};
+use subxt::backend::StreamOfResults;
+use subxt::storage::{address::StaticAddress, StorageKeyValuePair};
+use subxt::utils::{AccountId32, Yes};
 
 // impl ApiProvider for StorageApiProvider
 impl_api_provider!(
@@ -22,6 +30,52 @@ impl_api_provider!(
     polkadot::storage().file_bank()
 );
 
+/// What deleting a file would do for a given owner, see [`StorageQuery::delete_preview`].
+#[derive(Debug, Clone)]
+pub struct DeletePreview {
+    pub file_hash: String,
+    /// Territory the caller declared the file under.
+    pub territory_name: String,
+    /// When false, deleting only drops the caller's ownership and the data stays on the network.
+    pub sole_owner: bool,
+    pub other_owners: Vec<AccountId32>,
+    /// Fragment hash and open restoral order for fragments currently being restored.
+    pub restoral_orders: Vec<(String, RestoralOrderInfo)>,
+    /// Space returned to the caller's territory, in bytes.
+    pub freed_space: u128,
+}
+
+/// Most storage lookups [`StorageQuery::files_by_account`] and [`StorageQuery::delete_preview`]
+/// run at once.
+const MAX_CONCURRENT_FILE_QUERIES: usize = 8;
+
+/// Most blocks between two polls of [`StorageQuery::wait_for_restoral_completion`].
+const MAX_RESTORAL_POLL_BLOCKS: u32 = 10;
+
+/// A file held by an account, see [`StorageQuery::files_by_account`].
+#[derive(Debug, Clone)]
+pub struct FileDetail {
+    pub fid: String,
+    /// Name the account declared the file with.
+    pub file_name: String,
+    pub territory: String,
+    pub file_size: u128,
+    /// Segments the file is split into, each stored as data and parity fragments.
+    pub segment_count: usize,
+    pub state: FileState,
+    pub completion_block: u32,
+}
+
+/// A page of results, with the key to pass back for the next page while there is one.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_key: Option<String>,
+}
+
+/// Storage address iterating the whole `File` map.
+pub type FileIter = StaticAddress<(), FileInfo, (), (), Yes>;
+
 pub struct StorageQuery;
 
 impl Chain for StorageQuery {}
@@ -46,6 +100,14 @@ impl StorageQuery {
         Self::execute_query(&query, block_hash).await
     }
 
+    /// [`Self::deal_map`], decoded into a [`StorageOrder`].
+    pub async fn deal_map_decoded(
+        hash: &str,
+        block_hash: Option<H256>,
+    ) -> Result<Option<StorageOrder>, Box<dyn std::error::Error>> {
+        Ok(Self::deal_map(hash, block_hash).await?.map(Into::into))
+    }
+
     pub async fn file(
         hash: &str,
         block_hash: Option<H256>,
@@ -54,7 +116,56 @@ impl StorageQuery {
         let hash = hash_from_string(hash)?;
         let query = api.file(hash);
 
-        Self::execute_query(&query, block_hash).await
+        Ok(checked(Self::execute_query(&query, block_hash).await?)?)
+    }
+
+    /// [`Self::file`], decoded into a [`FileMetadata`].
+    pub async fn file_decoded(
+        hash: &str,
+        block_hash: Option<H256>,
+    ) -> Result<Option<FileMetadata>, Box<dyn std::error::Error>> {
+        Ok(Self::file(hash, block_hash).await?.map(Into::into))
+    }
+
+    /// Lazily streams every file stored on chain.
+    pub async fn all_files(
+        block_hash: Option<H256>,
+    ) -> Result<StreamOfResults<StorageKeyValuePair<FileIter>>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.file_iter();
+
+        Self::execute_iter(query, block_hash).await
+    }
+
+    /// Collects [`StorageQuery::all_files`]. Iterates the whole `File` map, prefer the stream
+    /// on large chains.
+    pub async fn all_files_collected(
+        block_hash: Option<H256>,
+    ) -> Result<Vec<FileInfo>, Box<dyn std::error::Error>> {
+        let mut stream = Self::all_files(block_hash).await?;
+        let mut files = Vec::new();
+        while let Some(result) = stream.next().await {
+            files.push(result?.value);
+        }
+
+        Ok(files)
+    }
+
+    /// Encoded `File` key of `hash`, to be reused with [`StorageQuery::file_by_key`].
+    pub async fn file_key(hash: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let hash = hash_from_string(hash)?;
+
+        Ok(Self::storage_key(&api.file(hash)).await?)
+    }
+
+    pub async fn file_by_key(
+        key: &[u8],
+        block_hash: Option<H256>,
+    ) -> Result<Option<FileInfo>, Box<dyn std::error::Error>> {
+        Ok(checked(
+            Self::execute_query_raw_as::<FileInfo>(key, block_hash).await?,
+        )?)
     }
 
     pub async fn user_hold_file_list(
@@ -68,6 +179,111 @@ impl StorageQuery {
         Self::execute_query(&query, block_hash).await
     }
 
+    /// One page of the files held by `account`, and whether further pages exist.
+    ///
+    /// The list is a single storage value, so it is still fetched whole; paging bounds what
+    /// callers hold and pass on.
+    pub async fn user_hold_file_list_paged(
+        account: &str,
+        page: u32,
+        page_size: u32,
+        block_hash: Option<H256>,
+    ) -> Result<(Vec<UserFileSliceInfo>, bool), Box<dyn std::error::Error>> {
+        if page_size == 0 {
+            return Err("Error: page_size must be greater than 0".into());
+        }
+
+        let files = match Self::user_hold_file_list(account, block_hash).await? {
+            Some(files) => files.0,
+            None => return Ok((Vec::new(), false)),
+        };
+        let start = (page as usize).saturating_mul(page_size as usize);
+        let end = start.saturating_add(page_size as usize);
+        let has_more = files.len() > end;
+        let page = files
+            .into_iter()
+            .skip(start)
+            .take(page_size as usize)
+            .collect();
+
+        Ok((page, has_more))
+    }
+
+    /// Up to `page_size` files held by `account` with their details, starting at the `next_key`
+    /// of a previous page or at the first file.
+    ///
+    /// A key names the next file and its position in the list, so a page still starts where the
+    /// previous one ended when that file was deleted in between.
+    pub async fn files_by_account(
+        account: &str,
+        page_size: u32,
+        start_key: Option<&str>,
+        block_hash: Option<H256>,
+    ) -> Result<Page<FileDetail>, Box<dyn std::error::Error>> {
+        if page_size == 0 {
+            return Err("Error: page_size must be greater than 0".into());
+        }
+
+        let owner = AccountId32::from_str(account)?;
+        let slices = match Self::user_hold_file_list(account, block_hash).await? {
+            Some(slices) => slices.0,
+            None => Vec::new(),
+        };
+        let start = match start_key {
+            Some(key) => {
+                let (position, fid) = key
+                    .split_once(':')
+                    .and_then(|(position, fid)| Some((position.parse::<usize>().ok()?, fid)))
+                    .ok_or_else(|| format!("Error: invalid continuation key {}", key))?;
+                slices
+                    .iter()
+                    .position(|slice| hash_to_string(&slice.file_hash) == fid)
+                    .unwrap_or_else(|| position.min(slices.len()))
+            }
+            None => 0,
+        };
+        let end = slices.len().min(start.saturating_add(page_size as usize));
+        let next_key = slices
+            .get(end)
+            .map(|slice| format!("{}:{}", end, hash_to_string(&slice.file_hash)));
+
+        let items: Vec<Option<FileDetail>> = stream::iter(slices[start..end].iter())
+            .map(|slice| {
+                let owner = owner.clone();
+                async move {
+                    let fid = hash_to_string(&slice.file_hash);
+                    let file = match Self::file(&fid, block_hash).await? {
+                        Some(file) => file,
+                        None => return Ok(None),
+                    };
+                    let file_name = file
+                        .owner
+                        .0
+                        .iter()
+                        .find(|brief| brief.user == owner)
+                        .map(|brief| String::from_utf8_lossy(&brief.file_name.0).into_owned())
+                        .unwrap_or_default();
+                    Ok::<_, Box<dyn std::error::Error>>(Some(FileDetail {
+                        fid,
+                        file_name,
+                        territory: String::from_utf8_lossy(&slice.territory_name.0).into_owned(),
+                        file_size: file.file_size,
+                        segment_count: file.segment_list.0.len(),
+                        state: file.stat,
+                        completion_block: file.completion,
+                    }))
+                }
+            })
+            .buffered(MAX_CONCURRENT_FILE_QUERIES)
+            .try_collect()
+            .await?;
+
+        Ok(Page {
+            items: items.into_iter().flatten().collect(),
+            next_key,
+        })
+    }
+
     pub async fn bucket(
         account: &str,
         bucket_name: &str,
@@ -103,6 +319,39 @@ impl StorageQuery {
         Self::execute_query(&query, block_hash).await
     }
 
+    /// Polls the restoral order of `fragment_hash` until it is gone, which happens once a miner
+    /// completes it, after the next finalized block at first and twice as many blocks after every
+    /// poll, see [`BlockClock`].
+    ///
+    /// Returns `Ok(false)` when the order still exists after `timeout`. An order that never
+    /// existed counts as completed.
+    pub async fn wait_for_restoral_completion(
+        fragment_hash: &str,
+        timeout: Duration,
+    ) -> Result<bool, Error> {
+        let deadline = Instant::now() + timeout;
+        let mut clock = BlockClock::new().await;
+        let mut blocks = 1;
+        loop {
+            let order = Self::restoral_order(fragment_hash, None)
+                .await
+                .map_err(|e| Error::Custom(e.to_string()))?;
+            if order.is_none() {
+                return Ok(true);
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(false);
+            }
+            // Polls a last time once `timeout` passed.
+            if let Ok(waited) = tokio::time::timeout(remaining, clock.wait_blocks(blocks)).await {
+                waited?;
+            }
+            blocks = (blocks * 2).min(MAX_RESTORAL_POLL_BLOCKS);
+        }
+    }
+
     pub async fn clear_user_list(
         block_hash: Option<H256>,
     ) -> Result<Option<BoundedVec<(AccountId32, BoundedVec<u8>)>>, Box<dyn std::error::Error>> {
@@ -122,4 +371,59 @@ impl StorageQuery {
 
         Self::execute_query(&query, block_hash).await
     }
+
+    /// Reports what `delete_file(account, file_hash)` would do without submitting anything.
+    pub async fn delete_preview(
+        file_hash: &str,
+        account: &str,
+        block_hash: Option<H256>,
+    ) -> Result<DeletePreview, Box<dyn std::error::Error>> {
+        let account = AccountId32::from_str(account)?;
+        let file = match Self::file(file_hash, block_hash).await? {
+            Some(file) => file,
+            None => return Err(format!("File {} not found", file_hash).into()),
+        };
+
+        let territory_name = match file.owner.0.iter().find(|brief| brief.user == account) {
+            Some(brief) => String::from_utf8_lossy(&brief.territory_name.0).to_string(),
+            None => return Err(format!("{} is not an owner of file {}", account, file_hash).into()),
+        };
+        let other_owners: Vec<AccountId32> = file
+            .owner
+            .0
+            .iter()
+            .map(|brief| brief.user.clone())
+            .filter(|user| *user != account)
+            .collect();
+
+        let fragment_hashes: Vec<String> = file
+            .segment_list
+            .0
+            .iter()
+            .flat_map(|segment| segment.fragment_list.0.iter())
+            .map(|fragment| hash_to_string(&fragment.hash))
+            .collect();
+        let restoral_orders: Vec<(String, RestoralOrderInfo)> = stream::iter(fragment_hashes)
+            .map(|hash| async move {
+                let order = Self::restoral_order(&hash, block_hash).await?;
+                Ok::<_, Box<dyn std::error::Error>>(order.map(|order| (hash, order)))
+            })
+            .buffer_unordered(MAX_CONCURRENT_FILE_QUERIES)
+            .try_filter_map(|order| async move { Ok(order) })
+            .try_collect()
+            .await?;
+
+        let segment_count = file.segment_list.0.len() as u128;
+        let freed_space =
+            segment_count * FRAEMENT_SIZE as u128 * (DATA_SHARDS + PAR_SHARDS) as u128;
+
+        Ok(DeletePreview {
+            file_hash: file_hash.to_string(),
+            territory_name,
+            sole_owner: other_owners.is_empty(),
+            other_owners,
+            restoral_orders,
+            freed_space,
+        })
+    }
 }
@@ -0,0 +1,96 @@
+//! Why the files of an account take more territory space than their size.
+//!
+//! Files are split into segments of [`SEGMENT_SIZE`], the last one padded, and every segment is
+//! stored as [`DATA_SHARDS`] + [`PAR_SHARDS`] fragments of [`FRAEMENT_SIZE`].
+
+use super::query::StorageQuery;
+use crate::chain::storage_handler::query::StorageQuery as TerritoryQuery;
+use crate::constants::{DATA_SHARDS, FRAEMENT_SIZE, PAR_SHARDS, SEGMENT_SIZE};
+use crate::core::Error;
+use serde::Serialize;
+
+/// Files with the most padding listed in [`EfficiencyReport::top_padding`].
+const TOP_OFFENDERS: usize = 10;
+
+/// Space used by the files of an account, in bytes.
+#[derive(Debug, Clone, Serialize)]
+pub struct EfficiencyReport {
+    pub account: String,
+    pub file_count: usize,
+    /// Sum of the file sizes.
+    pub logical_size: u128,
+    /// Space the fragments of the files take.
+    pub physical_size: u128,
+    /// Used space of all territories of the account.
+    pub territory_used_space: u128,
+    /// Space added by rounding every file up to whole segments.
+    pub padding: u128,
+    /// Space added by storing every segment as data and parity fragments.
+    pub replication_overhead: u128,
+    /// Used territory space the files do not explain, negative when they take more.
+    pub unexplained_drift: i128,
+    /// Files wasting the most padding, largest first. Packing them together saves the most.
+    pub top_padding: Vec<FileWaste>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileWaste {
+    pub fid: String,
+    pub file_name: String,
+    pub file_size: u128,
+    pub padding: u128,
+}
+
+/// Compares the sizes of the files held by `account` with the territory space they use.
+pub async fn space_efficiency(account: &str) -> Result<EfficiencyReport, Error> {
+    // Every file in one page.
+    let files = StorageQuery::files_by_account(account, u32::MAX, None, None)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?
+        .items;
+    let mut files: Vec<(FileWaste, u128)> = files
+        .into_iter()
+        .map(|file| {
+            let segments = file.segment_count as u128;
+            let waste = FileWaste {
+                fid: file.fid,
+                file_name: file.file_name,
+                file_size: file.file_size,
+                padding: (segments * SEGMENT_SIZE as u128).saturating_sub(file.file_size),
+            };
+            (waste, segments)
+        })
+        .collect();
+
+    let territory_used_space = TerritoryQuery::territories_by_account(account, None)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?
+        .unwrap_or_default()
+        .iter()
+        .map(|territory| territory.used_space)
+        .sum::<u128>();
+
+    let segments: u128 = files.iter().map(|(_, segments)| segments).sum();
+    let logical_size: u128 = files.iter().map(|(file, _)| file.file_size).sum();
+    let padding: u128 = files.iter().map(|(file, _)| file.padding).sum();
+    let segmented_size = segments * SEGMENT_SIZE as u128;
+    let physical_size = segments * FRAEMENT_SIZE as u128 * (DATA_SHARDS + PAR_SHARDS) as u128;
+
+    files.sort_by(|(a, _), (b, _)| b.padding.cmp(&a.padding));
+    let file_count = files.len();
+    Ok(EfficiencyReport {
+        account: account.to_string(),
+        file_count,
+        logical_size,
+        physical_size,
+        territory_used_space,
+        padding,
+        replication_overhead: physical_size.saturating_sub(segmented_size),
+        unexplained_drift: territory_used_space as i128 - physical_size as i128,
+        top_padding: files
+            .into_iter()
+            .take(TOP_OFFENDERS)
+            .map(|(file, _)| file)
+            .collect(),
+    })
+}
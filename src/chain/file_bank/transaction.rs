@@ -1,6 +1,13 @@
-use crate::chain::{Call, Chain};
-use crate::core::ApiProvider;
+use crate::chain::batch::BatchTransaction;
+use crate::chain::file_bank::query::StorageQuery;
+use crate::chain::{query_info, Call, Chain, TxOptions};
+use crate::constants::{
+    MAX_BATCH_DELETIONS, MAX_BUCKET_NAME_LENGHT, MAX_FILE_NAME_LENGTH, MAX_FRAGMENT_COUNT,
+    MAX_SEGMENT_COUNT, MAX_TERRITORY_NAME_LENGTH, MIN_BUCKET_NAME_LENGTH,
+};
+use crate::core::{ApiProvider, Error};
 use crate::impl_api_provider;
+use crate::init_api;
 use crate::polkadot::{
     self,
     file_bank::calls::{
@@ -13,13 +20,14 @@ use crate::polkadot::{
         TerritoryFileDelivery, TransferReport, UploadDeclaration,
     },
     runtime_types::bounded_collections::bounded_vec::BoundedVec,
+    runtime_types::cess_node_runtime::RuntimeCall,
+    runtime_types::pallet_file_bank::pallet::Call as FileBankCall,
     runtime_types::pallet_file_bank::types::{DigestInfo, SegmentList, TagSigInfo, UserBrief},
 };
-use crate::utils::hash_from_string;
-use std::str::FromStr;
+use crate::utils::{account::IntoAccount, hash_from_string};
 use subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
-use subxt::ext::subxt_core::utils::AccountId32;
 use subxt::tx::PairSigner;
+use subxt::utils::AccountId32;
 use subxt::PolkadotConfig;
 
 // impl ApiProvider for TransactionApiProvider
@@ -30,8 +38,91 @@ impl_api_provider!(
 );
 
 pub type TxHash = String;
+
+/// Arguments of a single `upload_declaration`, used by [`StorageTransaction::upload_declarations`].
+#[derive(Debug, Clone)]
+pub struct DeclarationSpec {
+    pub file_hash: String,
+    pub segment_list: Vec<SegmentList>,
+    pub user_brief: UserBrief,
+    pub file_size: u128,
+}
+
+impl DeclarationSpec {
+    /// Checks the bounds the pallet enforces, so one bad item does not fail the whole batch.
+    pub fn validate(&self) -> Result<(), Error> {
+        hash_from_string(&self.file_hash)?;
+
+        if self.segment_list.is_empty() || self.segment_list.len() > MAX_SEGMENT_COUNT {
+            return Err(format!(
+                "Segment count must be between 1 and {}, got {}",
+                MAX_SEGMENT_COUNT,
+                self.segment_list.len()
+            )
+            .into());
+        }
+        if let Some(segment) = self
+            .segment_list
+            .iter()
+            .find(|s| s.fragment_list.0.is_empty() || s.fragment_list.0.len() > MAX_FRAGMENT_COUNT)
+        {
+            return Err(format!(
+                "Fragment count must be between 1 and {}, got {}",
+                MAX_FRAGMENT_COUNT,
+                segment.fragment_list.0.len()
+            )
+            .into());
+        }
+
+        let brief = &self.user_brief;
+        check_length(
+            "File name",
+            brief.file_name.0.len(),
+            1,
+            MAX_FILE_NAME_LENGTH,
+        )?;
+        check_length(
+            "Bucket name",
+            brief.bucket_name.0.len(),
+            MIN_BUCKET_NAME_LENGTH,
+            MAX_BUCKET_NAME_LENGHT,
+        )?;
+        check_length(
+            "Territory name",
+            brief.territory_name.0.len(),
+            1,
+            MAX_TERRITORY_NAME_LENGTH,
+        )?;
+
+        Ok(())
+    }
+
+    fn into_call(self) -> Result<RuntimeCall, Error> {
+        let file_hash = hash_from_string(&self.file_hash)?;
+        Ok(RuntimeCall::FileBank(FileBankCall::upload_declaration {
+            file_hash,
+            deal_info: BoundedVec(self.segment_list),
+            user_brief: self.user_brief,
+            file_size: self.file_size,
+        }))
+    }
+}
+
+fn check_length(field: &str, len: usize, min: usize, max: usize) -> Result<(), Error> {
+    if len < min || len > max {
+        return Err(format!(
+            "{} length must be between {} and {}, got {}",
+            field, min, max, len
+        )
+        .into());
+    }
+    Ok(())
+}
+
 pub struct StorageTransaction {
     pair: PairS,
+    options: TxOptions,
+    max_batch_deletions: usize,
 }
 
 impl Chain for StorageTransaction {}
@@ -51,7 +142,29 @@ impl Call for StorageTransaction {
 impl StorageTransaction {
     pub fn new(mnemonic: &str) -> Self {
         let pair = PairS::from_string(mnemonic, None).unwrap();
-        Self { pair }
+        Self {
+            pair,
+            options: TxOptions::default(),
+            max_batch_deletions: MAX_BATCH_DELETIONS,
+        }
+    }
+
+    /// Applies `options` to every transaction submitted through this instance.
+    pub fn with_options(mut self, options: TxOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Most files [`Self::batch_delete_files`] deletes in one call, [`MAX_BATCH_DELETIONS`] by
+    /// default.
+    pub fn with_max_batch_deletions(mut self, max: usize) -> Self {
+        self.max_batch_deletions = max;
+        self
+    }
+
+    /// The account transactions are signed with.
+    pub fn account_id(&self) -> AccountId32 {
+        self.get_pair_signer().account_id().clone()
     }
 
     pub async fn upload_declaration(
@@ -65,24 +178,39 @@ impl StorageTransaction {
         let file_hash = hash_from_string(file_hash)?;
         let tx = api.upload_declaration(file_hash, segment_list, user_brief, file_size);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<UploadDeclaration>(event)
     }
 
+    /// Fee [`Self::upload_declaration`] would pay with these arguments.
+    pub async fn estimate_upload_declaration_fee(
+        &self,
+        file_hash: &str,
+        segment_list: BoundedVec<SegmentList>,
+        user_brief: UserBrief,
+        file_size: u128,
+    ) -> Result<u128, Error> {
+        let api = Self::get_api();
+        let file_hash = hash_from_string(file_hash)?;
+        let tx = api.upload_declaration(file_hash, segment_list, user_brief, file_size);
+
+        self.estimate_partial_fee(&tx).await
+    }
+
     pub async fn territory_file_delivery(
         &self,
-        account: &str,
+        account: impl IntoAccount,
         file_hash: &str,
         target_territory: &str,
     ) -> Result<(TxHash, TerritoryFileDelivery), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let file_hash = hash_from_string(file_hash)?;
         let target_territory = target_territory.as_bytes().to_vec();
         let tx = api.territory_file_delivery(account, file_hash, BoundedVec(target_territory));
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<TerritoryFileDelivery>(event)
     }
@@ -96,7 +224,7 @@ impl StorageTransaction {
         let deal_hash = hash_from_string(deal_hash)?;
         let tx = api.transfer_report(index, deal_hash);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<TransferReport>(event)
     }
@@ -104,13 +232,13 @@ impl StorageTransaction {
     pub async fn calculate_report(
         &self,
         tee_sig: &str,
-        account: &str,
+        account: impl IntoAccount,
         digest: BoundedVec<DigestInfo>,
         file_hash: &str,
     ) -> Result<(TxHash, CalculateReport), Box<dyn std::error::Error>> {
         let api = Self::get_api();
         let tee_sig = tee_sig.as_bytes().to_vec();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let file_hash = hash_from_string(file_hash)?;
         let tag_sig_info = TagSigInfo {
             miner: account,
@@ -119,7 +247,7 @@ impl StorageTransaction {
         };
         let tx = api.calculate_report(BoundedVec(tee_sig), tag_sig_info);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<CalculateReport>(event)
     }
@@ -134,26 +262,187 @@ impl StorageTransaction {
         let api = Self::get_api();
         let tx = api.replace_idle_space(idle_sig_info, tee_sig_need_verify, tee_sig, tee_puk);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<ReplaceIdleSpace>(event)
     }
 
     pub async fn delete_file(
         &self,
-        account: &str,
+        account: impl IntoAccount,
         file_hash: &str,
     ) -> Result<(TxHash, DeleteFile), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let file_hash = hash_from_string(file_hash)?;
         let tx = api.delete_file(account, file_hash);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<DeleteFile>(event)
     }
 
+    /// [`Self::delete_file`] for a file owned by the signer.
+    pub async fn delete_own_file(
+        &self,
+        file_hash: &str,
+    ) -> Result<(TxHash, DeleteFile), Box<dyn std::error::Error>> {
+        self.delete_file(self.account_id(), file_hash).await
+    }
+
+    /// Deletes files of several accounts in one `utility.batch_all` extrinsic, all or nothing.
+    ///
+    /// `deletions` pairs an SS58 account with the hashes of its files to delete. Fails before
+    /// submission when there are more hashes than [`Self::with_max_batch_deletions`] allows.
+    /// A deletion the pallet rejects fails the batch with [`Error::BatchItemFailed`], its index
+    /// counting hashes across all accounts.
+    pub async fn batch_delete_files(
+        &self,
+        deletions: Vec<(&str, Vec<String>)>,
+    ) -> Result<(TxHash, Vec<DeleteFile>), Box<dyn std::error::Error>> {
+        let total: usize = deletions.iter().map(|(_, hashes)| hashes.len()).sum();
+        if total == 0 {
+            return Err("Error: No files to delete".into());
+        }
+        if total > self.max_batch_deletions {
+            return Err(format!(
+                "Error: {} deletions exceed the batch limit of {}",
+                total, self.max_batch_deletions
+            )
+            .into());
+        }
+
+        let mut batch = BatchTransaction::from_pair(self.pair.clone()).with_options(self.options);
+        for (account, file_hashes) in deletions {
+            let owner = account.into_account()?;
+            for file_hash in file_hashes {
+                batch = batch.add_call(RuntimeCall::FileBank(FileBankCall::delete_file {
+                    owner: owner.clone(),
+                    file_hash: hash_from_string(&file_hash)?,
+                }));
+            }
+        }
+
+        let (hash, per_call) = batch.submit_all().await?;
+        let deleted = per_call
+            .iter()
+            .flatten()
+            .filter_map(|event| event.as_event::<DeleteFile>().transpose())
+            .collect::<Result<Vec<_>, _>>()?;
+        if deleted.len() != total {
+            return Err("Error: Unable to fetch event".into());
+        }
+
+        Ok((hash, deleted))
+    }
+
+    /// Renames a file of the signer and moves it to `new_bucket`, keeping its territory.
+    ///
+    /// The pallet has no call for this and refuses to declare a file its owner already holds,
+    /// so the file is deleted then declared again under the new name, both in one
+    /// `utility.batch_all` extrinsic so neither step applies alone.
+    ///
+    /// When the signer is the only owner, deleting the file releases its fragments and the
+    /// declaration is treated as a new upload, stored again only once a gateway delivers the
+    /// data. This refuses to go ahead in that case unless `allow_reupload` is set, see
+    /// [`StorageQuery::delete_preview`].
+    pub async fn update_file_metadata(
+        &self,
+        file_hash: &str,
+        new_name: &str,
+        new_bucket: &str,
+        allow_reupload: bool,
+    ) -> Result<(TxHash, UploadDeclaration), Box<dyn std::error::Error>> {
+        let owner = self.account_id();
+        let preview = StorageQuery::delete_preview(file_hash, &owner.to_string(), None).await?;
+        if preview.sole_owner && !allow_reupload {
+            return Err(format!(
+                "Error: Renaming file {} deletes it as its only owner and requires uploading it \
+                 again, pass allow_reupload to proceed",
+                file_hash
+            )
+            .into());
+        }
+        let file = StorageQuery::file(file_hash, None)
+            .await?
+            .ok_or_else(|| format!("Error: File {} not found", file_hash))?;
+        let brief = file
+            .owner
+            .0
+            .iter()
+            .find(|brief| brief.user == owner)
+            .ok_or_else(|| format!("Error: {} is not an owner of file {}", owner, file_hash))?;
+
+        let spec = DeclarationSpec {
+            file_hash: file_hash.to_string(),
+            segment_list: file
+                .segment_list
+                .0
+                .iter()
+                .map(|segment| SegmentList {
+                    hash: segment.hash.clone(),
+                    fragment_list: BoundedVec(
+                        segment
+                            .fragment_list
+                            .0
+                            .iter()
+                            .map(|fragment| fragment.hash.clone())
+                            .collect(),
+                    ),
+                })
+                .collect(),
+            user_brief: UserBrief {
+                user: owner.clone(),
+                file_name: BoundedVec(new_name.as_bytes().to_vec()),
+                bucket_name: BoundedVec(new_bucket.as_bytes().to_vec()),
+                territory_name: BoundedVec(brief.territory_name.0.clone()),
+            },
+            file_size: file.file_size,
+        };
+        spec.validate()?;
+
+        let (hash, per_call) = BatchTransaction::from_pair(self.pair.clone())
+            .with_options(self.options)
+            .add_call(RuntimeCall::FileBank(FileBankCall::delete_file {
+                owner,
+                file_hash: hash_from_string(file_hash)?,
+            }))
+            .add_call(spec.into_call()?)
+            .submit_all()
+            .await?;
+        let declared = per_call
+            .iter()
+            .flatten()
+            .find_map(|event| event.as_event::<UploadDeclaration>().transpose())
+            .ok_or("Error: Unable to fetch event")??;
+
+        Ok((hash, declared))
+    }
+
+    /// Deletes a file after checking [`StorageQuery::delete_preview`].
+    ///
+    /// When the file has other owners only the caller's ownership is dropped, so this refuses to
+    /// go ahead unless `confirm_shared` is set.
+    pub async fn delete_file_checked(
+        &self,
+        account: &str,
+        file_hash: &str,
+        confirm_shared: bool,
+    ) -> Result<(TxHash, DeleteFile), Box<dyn std::error::Error>> {
+        let preview = StorageQuery::delete_preview(file_hash, account, None).await?;
+        if !preview.sole_owner && !confirm_shared {
+            return Err(format!(
+                "File {} is shared with {} other owner(s); deleting only removes your ownership, \
+                 pass confirm_shared to proceed",
+                file_hash,
+                preview.other_owners.len()
+            )
+            .into());
+        }
+
+        self.delete_file(account, file_hash).await
+    }
+
     pub async fn cert_idle_space(
         &self,
         idle_sig_info: IdleSigInfo,
@@ -164,41 +453,57 @@ impl StorageTransaction {
         let api = Self::get_api();
         let tx = api.cert_idle_space(idle_sig_info, tee_sig_need_verify, tee_sig, tee_puk);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<IdleSpaceCert>(event)
     }
 
     pub async fn create_bucket(
         &self,
-        account: &str,
+        account: impl IntoAccount,
         bucket_name: &str,
     ) -> Result<(TxHash, CreateBucket), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let bucket_name = bucket_name.as_bytes().to_vec();
         let tx = api.create_bucket(account, BoundedVec(bucket_name));
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<CreateBucket>(event)
     }
 
+    /// [`Self::create_bucket`] owned by the signer.
+    pub async fn create_own_bucket(
+        &self,
+        bucket_name: &str,
+    ) -> Result<(TxHash, CreateBucket), Box<dyn std::error::Error>> {
+        self.create_bucket(self.account_id(), bucket_name).await
+    }
+
     pub async fn delete_bucket(
         &self,
-        account: &str,
+        account: impl IntoAccount,
         bucket_name: &str,
     ) -> Result<(TxHash, DeleteBucket), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let bucket_name = bucket_name.as_bytes().to_vec();
         let tx = api.delete_bucket(account, BoundedVec(bucket_name));
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<DeleteBucket>(event)
     }
 
+    /// [`Self::delete_bucket`] for a bucket owned by the signer.
+    pub async fn delete_own_bucket(
+        &self,
+        bucket_name: &str,
+    ) -> Result<(TxHash, DeleteBucket), Box<dyn std::error::Error>> {
+        self.delete_bucket(self.account_id(), bucket_name).await
+    }
+
     pub async fn generate_restoral_order(
         &self,
         file_hash: &str,
@@ -209,7 +514,7 @@ impl StorageTransaction {
         let restoral_fragment = hash_from_string(restoral_fragment)?;
         let tx = api.generate_restoral_order(file_hash, restoral_fragment);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<GenerateRestoralOrder>(event)
     }
@@ -222,24 +527,24 @@ impl StorageTransaction {
         let restoral_fragment = hash_from_string(restoral_fragment)?;
         let tx = api.claim_restoral_order(restoral_fragment);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<ClaimRestoralOrder>(event)
     }
 
     pub async fn claim_restoral_noexist_order(
         &self,
-        account: &str,
+        account: impl IntoAccount,
         file_hash: &str,
         restoral_fragment: &str,
     ) -> Result<(TxHash, ClaimRestoralOrder), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let file_hash = hash_from_string(file_hash)?;
         let restoral_fragment = hash_from_string(restoral_fragment)?;
         let tx = api.claim_restoral_noexist_order(account, file_hash, restoral_fragment);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<ClaimRestoralOrder>(event)
     }
@@ -252,8 +557,180 @@ impl StorageTransaction {
         let fragment_hash = hash_from_string(fragment_hash)?;
         let tx = api.restoral_order_complete(fragment_hash);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<RecoveryCompleted>(event)
     }
+
+    /// Declares several files in one `utility.batch_all` extrinsic.
+    ///
+    /// Items failing validation are reported and left out of the batch. When the batch is larger
+    /// than `batched_calls_limit` or its dry-run weight exceeds the per-extrinsic limit, the valid
+    /// items are declared one by one instead. Results keep the order of `batch`, keyed by file hash.
+    pub async fn upload_declarations(
+        &self,
+        batch: Vec<DeclarationSpec>,
+    ) -> Result<Vec<(String, Result<UploadDeclaration, Error>)>, Box<dyn std::error::Error>> {
+        let mut results: Vec<(String, Option<Result<UploadDeclaration, Error>>)> =
+            Vec::with_capacity(batch.len());
+        let mut valid = Vec::new();
+        for (index, spec) in batch.into_iter().enumerate() {
+            match spec.validate() {
+                Ok(()) => {
+                    results.push((spec.file_hash.clone(), None));
+                    valid.push((index, spec));
+                }
+                Err(e) => results.push((spec.file_hash.clone(), Some(Err(e)))),
+            }
+        }
+
+        if !valid.is_empty() {
+            let outcomes = if self.fits_in_batch(&valid).await? {
+                self.submit_declaration_batch(&valid).await?
+            } else {
+                self.submit_declarations_individually(&valid).await
+            };
+            for ((index, _), outcome) in valid.iter().zip(outcomes) {
+                results[*index].1 = Some(outcome);
+            }
+        }
+
+        Ok(results
+            .into_iter()
+            .map(|(fid, outcome)| (fid, outcome.unwrap_or_else(|| Err("Not submitted".into()))))
+            .collect())
+    }
+
+    /// Declares all `files` in one `utility.batch_all` extrinsic, all or nothing.
+    ///
+    /// Fails before submission when a file is invalid or the batch would exceed the call limit
+    /// or maximum extrinsic weight; see [`StorageTransaction::upload_declarations`] for a
+    /// variant that splits such batches instead.
+    pub async fn batch_upload_declaration(
+        &self,
+        files: Vec<(String, BoundedVec<SegmentList>, UserBrief, u128)>,
+    ) -> Result<(TxHash, Vec<UploadDeclaration>), Box<dyn std::error::Error>> {
+        if files.is_empty() {
+            return Err("Error: No files to declare".into());
+        }
+
+        let valid = files
+            .into_iter()
+            .enumerate()
+            .map(
+                |(index, (file_hash, segment_list, user_brief, file_size))| {
+                    let spec = DeclarationSpec {
+                        file_hash,
+                        segment_list: segment_list.0,
+                        user_brief,
+                        file_size,
+                    };
+                    spec.validate().map(|()| (index, spec))
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        if !self.fits_in_batch(&valid).await? {
+            return Err("Error: Batch exceeds the call limit or maximum extrinsic weight".into());
+        }
+
+        let tx = Self::declaration_batch_tx(&valid)?;
+        let from = self.get_pair_signer();
+        let events = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+        let hash = format!("0x{}", hex::encode(events.extrinsic_hash().0));
+        let declared = events
+            .find::<UploadDeclaration>()
+            .collect::<Result<Vec<_>, _>>()?;
+        if declared.len() != valid.len() {
+            return Err("Error: Unable to fetch event".into());
+        }
+
+        Ok((hash, declared))
+    }
+
+    async fn fits_in_batch(
+        &self,
+        valid: &[(usize, DeclarationSpec)],
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let api = init_api().await?;
+        let limit = api
+            .constants()
+            .at(&polkadot::constants().utility().batched_calls_limit())?;
+        if valid.len() > limit as usize {
+            return Ok(false);
+        }
+
+        let block_weights = api
+            .constants()
+            .at(&polkadot::constants().system().block_weights())?;
+        let max_weight = block_weights
+            .per_class
+            .normal
+            .max_extrinsic
+            .unwrap_or(block_weights.max_block);
+
+        let tx = Self::declaration_batch_tx(valid)?;
+        let from = self.get_pair_signer();
+        let extrinsic = api
+            .tx()
+            .create_signed(&tx, &from, Default::default())
+            .await?;
+        let info = query_info(&api, extrinsic.encoded()).await?;
+
+        Ok(info.weight.ref_time <= max_weight.ref_time
+            && info.weight.proof_size <= max_weight.proof_size)
+    }
+
+    async fn submit_declaration_batch(
+        &self,
+        valid: &[(usize, DeclarationSpec)],
+    ) -> Result<Vec<Result<UploadDeclaration, Error>>, Box<dyn std::error::Error>> {
+        let tx = Self::declaration_batch_tx(valid)?;
+        let from = self.get_pair_signer();
+        let events = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        // batch_all is atomic, so a finalized success emits one event per call, in call order.
+        let mut declared = events
+            .find::<UploadDeclaration>()
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter();
+        Ok(valid
+            .iter()
+            .map(|_| {
+                declared
+                    .next()
+                    .ok_or_else(|| "Error: Unable to fetch event".into())
+            })
+            .collect())
+    }
+
+    async fn submit_declarations_individually(
+        &self,
+        valid: &[(usize, DeclarationSpec)],
+    ) -> Vec<Result<UploadDeclaration, Error>> {
+        let mut outcomes = Vec::with_capacity(valid.len());
+        for (_, spec) in valid {
+            let outcome = self
+                .upload_declaration(
+                    &spec.file_hash,
+                    BoundedVec(spec.segment_list.clone()),
+                    spec.user_brief.clone(),
+                    spec.file_size,
+                )
+                .await
+                .map(|(_, event)| event)
+                .map_err(|e| Error::Custom(e.to_string()));
+            outcomes.push(outcome);
+        }
+        outcomes
+    }
+
+    fn declaration_batch_tx(
+        valid: &[(usize, DeclarationSpec)],
+    ) -> Result<impl subxt::tx::Payload + Sync, Box<dyn std::error::Error>> {
+        let calls = valid
+            .iter()
+            .map(|(_, spec)| spec.clone().into_call())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(polkadot::tx().utility().batch_all(calls))
+    }
 }
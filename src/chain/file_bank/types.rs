@@ -0,0 +1,209 @@
+//! Readable views of `FileBank` storage values: hashes as strings, accounts in CESS SS58 format
+//! and names as UTF-8.
+
+use crate::polkadot::runtime_types::{
+    bounded_collections::bounded_vec::BoundedVec,
+    pallet_file_bank::types::{
+        CompleteInfo, DealInfo, FileInfo, FileState, FragmentInfo, SegmentInfo, SegmentList,
+        UserBrief,
+    },
+};
+use crate::utils::account::{get_ss58_address_from_subxt_accountid32, AddressBook, IntoAccount};
+use crate::utils::hash_to_string;
+use serde::Serialize;
+use std::fmt;
+use subxt::utils::AccountId32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FileStatus {
+    Active,
+    Calculate,
+    Missing,
+    /// Fragments of the file are being restored.
+    Recovery,
+}
+
+impl fmt::Display for FileStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            FileStatus::Active => "active",
+            FileStatus::Calculate => "calculating",
+            FileStatus::Missing => "missing",
+            FileStatus::Recovery => "recovering",
+        };
+        f.write_str(status)
+    }
+}
+
+impl FileMetadata {
+    /// Labels owners found in `book`.
+    pub fn annotate(&mut self, book: &AddressBook) {
+        self.owners
+            .iter_mut()
+            .for_each(|owner| owner.annotate(book));
+    }
+}
+
+impl StorageOrder {
+    /// Labels the owner when found in `book`.
+    pub fn annotate(&mut self, book: &AddressBook) {
+        self.owner.annotate(book);
+    }
+}
+
+impl From<FileState> for FileStatus {
+    fn from(state: FileState) -> Self {
+        match state {
+            FileState::Active => FileStatus::Active,
+            FileState::Calculate => FileStatus::Calculate,
+            FileState::Missing => FileStatus::Missing,
+            FileState::Recovery => FileStatus::Recovery,
+        }
+    }
+}
+
+/// A stored file, decoded from [`FileInfo`].
+#[derive(Debug, Clone, Serialize)]
+pub struct FileMetadata {
+    pub segments: Vec<SegmentInfoView>,
+    pub owners: Vec<FileOwner>,
+    pub file_size: u128,
+    pub completion_block: u32,
+    pub status: FileStatus,
+}
+
+/// One owner of a file and the names it declared the file with, decoded from [`UserBrief`].
+#[derive(Debug, Clone, Serialize)]
+pub struct FileOwner {
+    pub account: String,
+    pub file_name: String,
+    pub bucket_name: String,
+    pub territory_name: String,
+    /// Label of the account in an [`AddressBook`], see [`FileMetadata::annotate`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl FileOwner {
+    fn annotate(&mut self, book: &AddressBook) {
+        self.label = self
+            .account
+            .as_str()
+            .into_account()
+            .ok()
+            .and_then(|account| book.label_for(&account).map(str::to_string));
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentInfoView {
+    pub hash: String,
+    pub fragments: Vec<FragmentInfoView>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FragmentInfoView {
+    pub hash: String,
+    pub avail: bool,
+    pub tag: Option<u32>,
+    pub miner: String,
+}
+
+/// A pending upload declaration, decoded from [`DealInfo`].
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageOrder {
+    pub file_size: u128,
+    pub segments: Vec<SegmentListView>,
+    pub owner: FileOwner,
+    /// Fragment indices already stored, with the miner storing them.
+    pub completed: Vec<(u8, String)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SegmentListView {
+    pub hash: String,
+    pub fragments: Vec<String>,
+}
+
+fn ss58(account: AccountId32) -> String {
+    let fallback = account.to_string();
+    get_ss58_address_from_subxt_accountid32(account).unwrap_or(fallback)
+}
+
+fn utf8(bytes: BoundedVec<u8>) -> String {
+    String::from_utf8_lossy(&bytes.0).into_owned()
+}
+
+impl From<UserBrief> for FileOwner {
+    fn from(brief: UserBrief) -> Self {
+        Self {
+            account: ss58(brief.user),
+            file_name: utf8(brief.file_name),
+            bucket_name: utf8(brief.bucket_name),
+            territory_name: utf8(brief.territory_name),
+            label: None,
+        }
+    }
+}
+
+impl From<FragmentInfo> for FragmentInfoView {
+    fn from(fragment: FragmentInfo) -> Self {
+        Self {
+            hash: hash_to_string(&fragment.hash),
+            avail: fragment.avail,
+            tag: fragment.tag,
+            miner: ss58(fragment.miner),
+        }
+    }
+}
+
+impl From<SegmentInfo> for SegmentInfoView {
+    fn from(segment: SegmentInfo) -> Self {
+        Self {
+            hash: hash_to_string(&segment.hash),
+            fragments: segment
+                .fragment_list
+                .0
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl From<FileInfo> for FileMetadata {
+    fn from(file: FileInfo) -> Self {
+        Self {
+            segments: file.segment_list.0.into_iter().map(Into::into).collect(),
+            owners: file.owner.0.into_iter().map(Into::into).collect(),
+            file_size: file.file_size,
+            completion_block: file.completion,
+            status: file.stat.into(),
+        }
+    }
+}
+
+impl From<SegmentList> for SegmentListView {
+    fn from(segment: SegmentList) -> Self {
+        Self {
+            hash: hash_to_string(&segment.hash),
+            fragments: segment.fragment_list.0.iter().map(hash_to_string).collect(),
+        }
+    }
+}
+
+impl From<DealInfo> for StorageOrder {
+    fn from(deal: DealInfo) -> Self {
+        Self {
+            file_size: deal.file_size,
+            segments: deal.segment_list.0.into_iter().map(Into::into).collect(),
+            owner: deal.user.into(),
+            completed: deal
+                .complete_list
+                .0
+                .into_iter()
+                .map(|CompleteInfo { index, miner }| (index, ss58(miner)))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::core::Error;
+use crate::legacy_rpc_for;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use subxt::utils::AccountId32;
+use subxt::{Config, PolkadotConfig};
+
+/// Hands out nonces per account without waiting for earlier transactions to be included, so
+/// several transactions from one account can be submitted back to back.
+///
+/// The nonce of an account is fetched from the node on first use and counted locally after
+/// that. Clones share the same cache. See [`crate::chain::Call::sign_and_submit_with_nonce_manager`].
+#[derive(Debug, Clone, Default)]
+pub struct NonceManager {
+    nonces: Arc<Mutex<HashMap<AccountId32, u64>>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserves the next nonce of `account`.
+    pub async fn next(&self, account: &AccountId32) -> Result<u64, Error> {
+        self.next_for::<PolkadotConfig>(account).await
+    }
+
+    /// [`NonceManager::next`], fetching through the connection [`crate::init_api_for`] resolves
+    /// to for `C`.
+    pub async fn next_for<C>(&self, account: &AccountId32) -> Result<u64, Error>
+    where
+        C: Config<AccountId = AccountId32>,
+    {
+        if let Some(nonce) = self.reserve(account, None) {
+            return Ok(nonce);
+        }
+        // Unlike the runtime's account nonce, counts transactions still in the node's pool.
+        let fetched = legacy_rpc_for::<C>()
+            .await?
+            .system_account_next_index(account)
+            .await?;
+        // Another task may have fetched it meanwhile, its count wins.
+        Ok(self.reserve(account, Some(fetched)).unwrap_or(fetched))
+    }
+
+    /// Makes `next` the nonce handed out next for `account`, e.g. when it is known from a node
+    /// other than the connected one.
+    pub fn set(&self, account: &AccountId32, next: u64) {
+        self.nonces
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(account.clone(), next);
+    }
+
+    /// Drops the cached nonce of `account`, so the next one is fetched from the chain again.
+    pub fn invalidate(&self, account: &AccountId32) {
+        self.nonces
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(account);
+    }
+
+    /// Takes the cached nonce of `account`, caching `fetched` first when there is none.
+    fn reserve(&self, account: &AccountId32, fetched: Option<u64>) -> Option<u64> {
+        let mut nonces = self.nonces.lock().unwrap_or_else(|e| e.into_inner());
+        let nonce = match fetched {
+            Some(fetched) => nonces.entry(account.clone()).or_insert(fetched),
+            None => nonces.get_mut(account)?,
+        };
+        let reserved = *nonce;
+        *nonce += 1;
+        Some(reserved)
+    }
+}
+
+/// Whether a submission failed because of its nonce rather than its content.
+pub(crate) fn is_nonce_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "priority is too low",
+        "priority too low",
+        "outdated",
+        "stale",
+        "invalidnonce",
+        "invalid nonce",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
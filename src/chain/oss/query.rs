@@ -8,11 +8,16 @@ use crate::polkadot::{
     runtime_types::{bounded_collections::bounded_vec::BoundedVec, pallet_oss::types::OssInfo},
 };
 use crate::{impl_api_provider, H256};
-use subxt::utils::AccountId32;
+use subxt::backend::StreamOfResults;
+use subxt::storage::{address::StaticAddress, StorageKeyValuePair};
+use subxt::utils::{AccountId32, Yes};
 
 // impl ApiProvider for StorageApiProvider
 impl_api_provider!(StorageApiProvider, StorageApi, polkadot::storage().oss());
 
+/// Storage address iterating the whole `Oss` map.
+pub type OssIter = StaticAddress<(), OssInfo, (), (), Yes>;
+
 pub struct StorageQuery;
 
 impl Chain for StorageQuery {}
@@ -49,4 +54,15 @@ impl StorageQuery {
 
         Self::execute_query(&query, block_hash).await
     }
+
+    /// Lazily streams every registered OSS.
+    pub async fn oss_list(
+        &self,
+        block_hash: Option<H256>,
+    ) -> Result<StreamOfResults<StorageKeyValuePair<OssIter>>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.oss_iter();
+
+        Self::execute_iter(query, block_hash).await
+    }
 }
@@ -1,5 +1,5 @@
-use crate::chain::{Call, Chain};
-use crate::core::ApiProvider;
+use crate::chain::{Call, Chain, TxOptions};
+use crate::core::{ApiProvider, Error};
 use crate::impl_api_provider;
 use crate::polkadot::oss::calls::types::proxy_authorzie::Sig;
 use crate::polkadot::runtime_types::pallet_oss::types::ProxyAuthPayload;
@@ -9,9 +9,8 @@ use crate::polkadot::{
     oss::events::{Authorize, CancelAuthorize, OssDestroy, OssRegister, OssUpdate},
     runtime_types::bounded_collections::bounded_vec::BoundedVec,
 };
-use std::str::FromStr;
+use crate::utils::account::IntoAccount;
 use subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
-use subxt::ext::subxt_core::utils::AccountId32;
 use subxt::tx::PairSigner;
 use subxt::PolkadotConfig;
 
@@ -21,6 +20,7 @@ impl_api_provider!(TransactionApiProvider, TransactionApi, polkadot::tx().oss())
 pub type TxHash = String;
 pub struct StorageTransaction {
     pair: PairS,
+    options: TxOptions,
 }
 
 impl Chain for StorageTransaction {}
@@ -40,31 +40,49 @@ impl Call for StorageTransaction {
 impl StorageTransaction {
     pub fn new(mnemonic: &str) -> Self {
         let pair = PairS::from_string(mnemonic, None).unwrap();
-        Self { pair }
+        Self {
+            pair,
+            options: TxOptions::default(),
+        }
+    }
+
+    /// Applies `options` to every transaction submitted through this instance.
+    pub fn with_options(mut self, options: TxOptions) -> Self {
+        self.options = options;
+        self
     }
 
     pub async fn authorize(
         &self,
-        account: &str,
+        account: impl IntoAccount,
     ) -> Result<(TxHash, Authorize), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let tx = api.authorize(account);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<Authorize>(event)
     }
 
+    /// Fee [`Self::authorize`] would pay.
+    pub async fn estimate_authorize_fee(&self, account: impl IntoAccount) -> Result<u128, Error> {
+        let api = Self::get_api();
+        let account = account.into_account()?;
+        let tx = api.authorize(account);
+
+        self.estimate_partial_fee(&tx).await
+    }
+
     pub async fn cancel_authorize(
         &self,
-        account: &str,
+        account: impl IntoAccount,
     ) -> Result<(TxHash, CancelAuthorize), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let tx = api.cancel_authorize(account);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<CancelAuthorize>(event)
     }
@@ -77,7 +95,7 @@ impl StorageTransaction {
         let api = Self::get_api();
         let tx = api.register(endpoint, domain);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<OssRegister>(event)
     }
@@ -90,7 +108,7 @@ impl StorageTransaction {
         let api = Self::get_api();
         let tx = api.update(endpoint, domain);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<OssUpdate>(event)
     }
@@ -99,37 +117,37 @@ impl StorageTransaction {
         let api = Self::get_api();
         let tx = api.destroy();
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<OssDestroy>(event)
     }
 
     pub async fn proxy_authorize(
         &self,
-        account: &str,
+        account: impl IntoAccount,
         sig: Sig,
         payload: ProxyAuthPayload,
     ) -> Result<TxHash, Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let tx = api.proxy_authorzie(account.0, sig, payload);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
         let hash = event.extrinsic_hash();
         Ok(format!("0x{}", hex::encode(hash.0)))
     }
 
     pub async fn evm_proxy_authorzie(
         &self,
-        account: &str,
+        account: impl IntoAccount,
         sig: [u8; 65],
         payload: ProxyAuthPayload,
     ) -> Result<TxHash, Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let account = AccountId32::from_str(account)?;
+        let account = account.into_account()?;
         let tx = api.evm_proxy_authorzie(account.0, sig, payload);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
         let hash = event.extrinsic_hash();
         Ok(format!("0x{}", hex::encode(hash.0)))
     }
@@ -0,0 +1,120 @@
+//! Invariant checks on decoded chain values.
+//!
+//! Decoding against the wrong storage entry or drifted metadata usually yields absurd values
+//! rather than an error. Queries run the checks below and fail with [`Error::Insane`] instead of
+//! returning such values. [`set_sanity_checks`] turns them off to inspect raw chain data.
+
+use crate::constants::SEGMENT_SIZE;
+use crate::core::Error;
+use crate::polkadot::runtime_types::{
+    pallet_file_bank::types::FileInfo,
+    pallet_storage_handler::types::{ConsignmentInfo, TerritoryInfo},
+};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static SANITY_CHECKS: AtomicBool = AtomicBool::new(true);
+
+/// Enables or disables the checks for the whole process. They are enabled by default.
+pub fn set_sanity_checks(enabled: bool) {
+    SANITY_CHECKS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn sanity_checks_enabled() -> bool {
+    SANITY_CHECKS.load(Ordering::Relaxed)
+}
+
+/// Invariants a decoded value always satisfies on a healthy chain.
+pub trait Sanity {
+    fn check(&self) -> Result<(), Error>;
+}
+
+impl Sanity for TerritoryInfo {
+    fn check(&self) -> Result<(), Error> {
+        ensure(
+            self.used_space <= self.total_space,
+            "used_space",
+            self.used_space,
+            "used_space <= total_space",
+        )?;
+        ensure(
+            self.remaining_space <= self.total_space,
+            "remaining_space",
+            self.remaining_space,
+            "remaining_space <= total_space",
+        )?;
+        ensure(
+            self.start <= self.deadline,
+            "deadline",
+            self.deadline,
+            "start <= deadline",
+        )
+    }
+}
+
+impl Sanity for ConsignmentInfo {
+    fn check(&self) -> Result<(), Error> {
+        ensure(self.price > 0, "price", self.price, "price > 0")
+    }
+}
+
+impl Sanity for FileInfo {
+    fn check(&self) -> Result<(), Error> {
+        let segments = self.segment_list.0.len();
+        ensure(
+            segments > 0,
+            "segment_list",
+            segments,
+            "at least one segment",
+        )?;
+        ensure(
+            !self.owner.0.is_empty(),
+            "owner",
+            self.owner.0.len(),
+            "at least one owner",
+        )?;
+        ensure(
+            self.file_size <= segments as u128 * SEGMENT_SIZE as u128,
+            "file_size",
+            self.file_size,
+            "file_size <= segments * SEGMENT_SIZE",
+        )
+    }
+}
+
+impl<T: Sanity> Sanity for Vec<T> {
+    fn check(&self) -> Result<(), Error> {
+        self.iter().try_for_each(Sanity::check)
+    }
+}
+
+/// Runs the checks on a query result, unless they are disabled.
+pub(crate) fn checked<T: Sanity>(value: Option<T>) -> Result<Option<T>, Error> {
+    if let (Some(value), true) = (&value, sanity_checks_enabled()) {
+        value.check()?;
+    }
+    Ok(value)
+}
+
+pub(crate) fn check_unit_price(price: Option<u128>) -> Result<Option<u128>, Error> {
+    if let (Some(price), true) = (price, sanity_checks_enabled()) {
+        ensure(price > 0, "unit_price", price, "unit_price > 0")?;
+    }
+    Ok(price)
+}
+
+fn ensure(
+    holds: bool,
+    field: &'static str,
+    value: impl Debug,
+    invariant: &'static str,
+) -> Result<(), Error> {
+    if holds {
+        return Ok(());
+    }
+    Err(Error::Insane {
+        field,
+        value: format!("{:?}", value),
+        invariant,
+    })
+}
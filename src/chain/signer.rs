@@ -0,0 +1,48 @@
+//! Signers for key types other than the sr25519 pairs the `StorageTransaction` types hold.
+//!
+//! A [`DynSigner`] is accepted wherever the `Call` helpers take a signer, e.g.
+//! `sign_and_submit_tx_with_options`.
+
+use crate::core::Error;
+use subxt::ext::sp_core::{ed25519, Pair};
+use subxt::tx::{PairSigner, Signer};
+use subxt::{Config, PolkadotConfig};
+
+/// Any signer for the CESS chain, boxed. Signs for `PolkadotConfig` unless another `C` is given.
+pub struct DynSigner<C: Config = PolkadotConfig>(Box<dyn Signer<C> + Send + Sync>);
+
+impl<C: Config> DynSigner<C> {
+    pub fn new<S>(signer: S) -> Self
+    where
+        S: Signer<C> + Send + Sync + 'static,
+    {
+        Self(Box::new(signer))
+    }
+}
+
+impl<C: Config> Signer<C> for DynSigner<C> {
+    fn account_id(&self) -> C::AccountId {
+        self.0.account_id()
+    }
+
+    fn address(&self) -> C::Address {
+        self.0.address()
+    }
+
+    fn sign(&self, signer_payload: &[u8]) -> C::Signature {
+        self.0.sign(signer_payload)
+    }
+}
+
+pub fn dyn_signer_from_ed25519_mnemonic(mnemonic: &str) -> Result<DynSigner, Error> {
+    let pair = ed25519::Pair::from_string(mnemonic, None)
+        .map_err(|e| Error::InvalidInput(format!("Invalid ed25519 mnemonic: {:?}", e)))?;
+
+    Ok(DynSigner::new(PairSigner::<PolkadotConfig, _>::new(pair)))
+}
+
+pub fn dyn_signer_from_ed25519_seed(seed: &[u8; 32]) -> Result<DynSigner, Error> {
+    let pair = ed25519::Pair::from_seed(seed);
+
+    Ok(DynSigner::new(PairSigner::<PolkadotConfig, _>::new(pair)))
+}
@@ -0,0 +1,108 @@
+use crate::chain::{Chain, Query};
+use crate::core::ApiProvider;
+use crate::polkadot::{
+    self,
+    runtime_types::{
+        bounded_collections::bounded_vec::BoundedVec,
+        pallet_sminer::types::{MinerInfo, RestoralTargetInfo, Reward},
+    },
+    sminer::storage::StorageApi,
+};
+use crate::{impl_api_provider, H256};
+use std::str::FromStr;
+use subxt::utils::AccountId32;
+
+// impl ApiProvider for StorageApiProvider
+impl_api_provider!(StorageApiProvider, StorageApi, polkadot::storage().sminer());
+
+pub struct StorageQuery;
+
+impl Chain for StorageQuery {}
+
+impl Query for StorageQuery {
+    type Api = StorageApi;
+
+    fn get_api() -> Self::Api {
+        crate::core::get_api::<StorageApiProvider>()
+    }
+}
+
+impl StorageQuery {
+    pub async fn miner_items(
+        account: &str,
+        block_hash: Option<H256>,
+    ) -> Result<Option<MinerInfo>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let account = AccountId32::from_str(account)?;
+        let query = api.miner_items(account);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    /// Every registered miner with its info.
+    pub async fn miner_items_iter(
+        block_hash: Option<H256>,
+    ) -> Result<Vec<(AccountId32, MinerInfo)>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.miner_items_iter();
+
+        let mut stream = Self::execute_iter(query, block_hash).await?;
+        let mut results = Vec::new();
+        while let Some(result) = stream.next().await {
+            let key_value = result?;
+            // The map is keyed with a concat hasher, so the account ends the storage key.
+            let key_bytes = &key_value.key_bytes;
+            if key_bytes.len() < 32 {
+                return Err(
+                    format!("Malformed MinerItems key: 0x{}", hex::encode(key_bytes)).into(),
+                );
+            }
+            let mut account = [0u8; 32];
+            account.copy_from_slice(&key_bytes[key_bytes.len() - 32..]);
+            results.push((AccountId32(account), key_value.value));
+        }
+
+        Ok(results)
+    }
+
+    pub async fn all_miner(
+        block_hash: Option<H256>,
+    ) -> Result<Option<BoundedVec<AccountId32>>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.all_miner();
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn reward_map(
+        account: &str,
+        block_hash: Option<H256>,
+    ) -> Result<Option<Reward>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let account = AccountId32::from_str(account)?;
+        let query = api.reward_map(account);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    /// The `(k, n, d)` parameters of the idle file expanders.
+    pub async fn expenders(
+        block_hash: Option<H256>,
+    ) -> Result<Option<(u64, u64, u64)>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.expenders();
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn restoral_target(
+        account: &str,
+        block_hash: Option<H256>,
+    ) -> Result<Option<RestoralTargetInfo<AccountId32, u32>>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let account = AccountId32::from_str(account)?;
+        let query = api.restoral_target(account);
+
+        Self::execute_query(&query, block_hash).await
+    }
+}
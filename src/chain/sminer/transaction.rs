@@ -0,0 +1,147 @@
+use crate::chain::{Call, Chain, TxOptions};
+use crate::core::{ApiProvider, Error};
+use crate::impl_api_provider;
+use crate::polkadot::{
+    self,
+    sminer::calls::TransactionApi,
+    sminer::events::{
+        IncreaseCollateral, MinerExitPrep, Receive, Registered, UpdateBeneficiary, Withdraw,
+    },
+};
+use crate::utils::account::IntoAccount;
+use subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
+use subxt::tx::PairSigner;
+use subxt::PolkadotConfig;
+
+// impl ApiProvider for TransactionApiProvider
+impl_api_provider!(
+    TransactionApiProvider,
+    TransactionApi,
+    polkadot::tx().sminer()
+);
+
+pub type TxHash = String;
+pub struct StorageTransaction {
+    pair: PairS,
+    options: TxOptions,
+}
+
+impl Chain for StorageTransaction {}
+
+impl Call for StorageTransaction {
+    type Api = TransactionApi;
+
+    fn get_api() -> Self::Api {
+        crate::core::get_api::<TransactionApiProvider>()
+    }
+
+    fn get_pair_signer(&self) -> PairSigner<PolkadotConfig, PairS> {
+        PairSigner::new(self.pair.clone())
+    }
+}
+
+impl StorageTransaction {
+    pub fn new(mnemonic: &str) -> Self {
+        let pair = PairS::from_string(mnemonic, None).unwrap();
+        Self {
+            pair,
+            options: TxOptions::default(),
+        }
+    }
+
+    /// Applies `options` to every transaction submitted through this instance.
+    pub fn with_options(mut self, options: TxOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Registers the signer as a storage miner declaring `tib_count` TiB, staking `staking_val`.
+    pub async fn regnstk(
+        &self,
+        beneficiary: impl IntoAccount,
+        peer_id: [u8; 38],
+        staking_val: u128,
+        tib_count: u32,
+    ) -> Result<(TxHash, Registered), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let beneficiary = beneficiary.into_account()?;
+        let tx = api.regnstk(beneficiary, peer_id, staking_val, tib_count);
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        Self::find_first::<Registered>(event)
+    }
+
+    /// Fee [`Self::regnstk`] would pay with these arguments, excluding the staked collateral.
+    pub async fn estimate_regnstk_fee(
+        &self,
+        beneficiary: impl IntoAccount,
+        peer_id: [u8; 38],
+        staking_val: u128,
+        tib_count: u32,
+    ) -> Result<u128, Error> {
+        let api = Self::get_api();
+        let beneficiary = beneficiary.into_account()?;
+        let tx = api.regnstk(beneficiary, peer_id, staking_val, tib_count);
+
+        self.estimate_partial_fee(&tx).await
+    }
+
+    pub async fn increase_collateral(
+        &self,
+        miner: impl IntoAccount,
+        collaterals: u128,
+    ) -> Result<(TxHash, IncreaseCollateral), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let miner = miner.into_account()?;
+        let tx = api.increase_collateral(miner, collaterals);
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        Self::find_first::<IncreaseCollateral>(event)
+    }
+
+    pub async fn update_beneficiary(
+        &self,
+        beneficiary: impl IntoAccount,
+    ) -> Result<(TxHash, UpdateBeneficiary), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let beneficiary = beneficiary.into_account()?;
+        let tx = api.update_beneficiary(beneficiary);
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        Self::find_first::<UpdateBeneficiary>(event)
+    }
+
+    pub async fn receive_reward(&self) -> Result<(TxHash, Receive), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let tx = api.receive_reward();
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        Self::find_first::<Receive>(event)
+    }
+
+    pub async fn miner_exit_prep(
+        &self,
+        miner: impl IntoAccount,
+    ) -> Result<(TxHash, MinerExitPrep), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let miner = miner.into_account()?;
+        let tx = api.miner_exit_prep(miner);
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        Self::find_first::<MinerExitPrep>(event)
+    }
+
+    pub async fn miner_withdraw(&self) -> Result<(TxHash, Withdraw), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let tx = api.miner_withdraw();
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        Self::find_first::<Withdraw>(event)
+    }
+}
@@ -0,0 +1,187 @@
+use crate::chain::{Chain, Query};
+use crate::core::ApiProvider;
+use crate::polkadot::{
+    self,
+    runtime_types::pallet_cess_staking::{
+        ActiveEraInfo, EraRewardPoints, Nominations, StakingLedger, ValidatorPrefs,
+    },
+    staking::storage::StorageApi,
+};
+use crate::{impl_api_provider, H256};
+use std::str::FromStr;
+use subxt::utils::AccountId32;
+
+// impl ApiProvider for StorageApiProvider
+impl_api_provider!(
+    StorageApiProvider,
+    StorageApi,
+    polkadot::storage().staking()
+);
+
+/// Averages over the eras [`StorageQuery::validator_apy_estimate`] found data for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValidatorApyEstimate {
+    /// Share of the era reward points earned by the validator, between 0 and 1.
+    pub avg_points_share: f64,
+    /// Part of the era validator reward attributed to the validator by its points.
+    pub avg_reward: u128,
+    /// Total stake of the era.
+    pub total_stake: u128,
+}
+
+pub struct StorageQuery;
+
+impl Chain for StorageQuery {}
+
+impl Query for StorageQuery {
+    type Api = StorageApi;
+
+    fn get_api() -> Self::Api {
+        crate::core::get_api::<StorageApiProvider>()
+    }
+}
+
+impl StorageQuery {
+    pub async fn current_era(
+        block_hash: Option<H256>,
+    ) -> Result<Option<u32>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.current_era();
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn active_era(
+        block_hash: Option<H256>,
+    ) -> Result<Option<ActiveEraInfo>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.active_era();
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    /// The controller of the `stash` account.
+    pub async fn bonded(
+        stash: &str,
+        block_hash: Option<H256>,
+    ) -> Result<Option<AccountId32>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let stash = AccountId32::from_str(stash)?;
+        let query = api.bonded(stash);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn ledger(
+        controller: &str,
+        block_hash: Option<H256>,
+    ) -> Result<Option<StakingLedger>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let controller = AccountId32::from_str(controller)?;
+        let query = api.ledger(controller);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn validators(
+        stash: &str,
+        block_hash: Option<H256>,
+    ) -> Result<Option<ValidatorPrefs>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let stash = AccountId32::from_str(stash)?;
+        let query = api.validators(stash);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn nominators(
+        stash: &str,
+        block_hash: Option<H256>,
+    ) -> Result<Option<Nominations>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let stash = AccountId32::from_str(stash)?;
+        let query = api.nominators(stash);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn eras_reward_points(
+        era: u32,
+        block_hash: Option<H256>,
+    ) -> Result<Option<EraRewardPoints<AccountId32>>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.eras_reward_points(era);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn eras_validator_reward(
+        era: u32,
+        block_hash: Option<H256>,
+    ) -> Result<Option<u128>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.eras_validator_reward(era);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn eras_total_stake(
+        era: u32,
+        block_hash: Option<H256>,
+    ) -> Result<Option<u128>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.eras_total_stake(era);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    /// Averages the reward points share, reward and total stake of `validator` over the last
+    /// `eras` completed eras. Eras without a recorded reward are skipped, `Ok(None)` when none
+    /// has one.
+    pub async fn validator_apy_estimate(
+        validator: &str,
+        eras: u32,
+    ) -> Result<Option<ValidatorApyEstimate>, Box<dyn std::error::Error>> {
+        let account = AccountId32::from_str(validator)?;
+        let active = match Self::active_era(None).await? {
+            Some(active) => active.index,
+            None => return Ok(None),
+        };
+
+        let mut counted = 0u128;
+        let mut points_share = 0f64;
+        let mut reward = 0u128;
+        let mut total_stake = 0u128;
+        for era in active.saturating_sub(eras)..active {
+            let (points, era_reward, stake) = match (
+                Self::eras_reward_points(era, None).await?,
+                Self::eras_validator_reward(era, None).await?,
+                Self::eras_total_stake(era, None).await?,
+            ) {
+                (Some(points), Some(era_reward), Some(stake)) => (points, era_reward, stake),
+                _ => continue,
+            };
+
+            let earned = points
+                .individual
+                .iter()
+                .find(|(who, _)| *who == account)
+                .map_or(0, |(_, points)| *points);
+            if points.total > 0 {
+                points_share += earned as f64 / points.total as f64;
+                reward += era_reward * earned as u128 / points.total as u128;
+            }
+            total_stake += stake;
+            counted += 1;
+        }
+
+        if counted == 0 {
+            return Ok(None);
+        }
+        Ok(Some(ValidatorApyEstimate {
+            avg_points_share: points_share / counted as f64,
+            avg_reward: reward / counted,
+            total_stake: total_stake / counted,
+        }))
+    }
+}
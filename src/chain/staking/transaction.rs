@@ -0,0 +1,113 @@
+use crate::chain::{Call, Chain, TxOptions};
+use crate::core::ApiProvider;
+use crate::impl_api_provider;
+use crate::polkadot::{
+    self,
+    runtime_types::pallet_cess_staking::RewardDestination,
+    staking::calls::TransactionApi,
+    staking::events::{Bonded, PayoutStarted, Unbonded},
+};
+use crate::utils::account::IntoAccount;
+use subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
+use subxt::tx::PairSigner;
+use subxt::utils::{AccountId32, MultiAddress};
+use subxt::PolkadotConfig;
+
+// impl ApiProvider for TransactionApiProvider
+impl_api_provider!(
+    TransactionApiProvider,
+    TransactionApi,
+    polkadot::tx().staking()
+);
+
+pub type TxHash = String;
+pub struct StorageTransaction {
+    pair: PairS,
+    options: TxOptions,
+}
+
+impl Chain for StorageTransaction {}
+
+impl Call for StorageTransaction {
+    type Api = TransactionApi;
+
+    fn get_api() -> Self::Api {
+        crate::core::get_api::<TransactionApiProvider>()
+    }
+
+    fn get_pair_signer(&self) -> PairSigner<PolkadotConfig, PairS> {
+        PairSigner::new(self.pair.clone())
+    }
+}
+
+impl StorageTransaction {
+    pub fn new(mnemonic: &str) -> Self {
+        let pair = PairS::from_string(mnemonic, None).unwrap();
+        Self {
+            pair,
+            options: TxOptions::default(),
+        }
+    }
+
+    /// Applies `options` to every transaction submitted through this instance.
+    pub fn with_options(mut self, options: TxOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub async fn bond(
+        &self,
+        value: u128,
+        payee: RewardDestination<AccountId32>,
+    ) -> Result<(TxHash, Bonded), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let tx = api.bond(value, payee);
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        Self::find_first::<Bonded>(event)
+    }
+
+    pub async fn unbond(
+        &self,
+        value: u128,
+    ) -> Result<(TxHash, Unbonded), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let tx = api.unbond(value);
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        Self::find_first::<Unbonded>(event)
+    }
+
+    /// Nominates `targets` with the stake bonded by the signer.
+    pub async fn nominate<A: IntoAccount>(
+        &self,
+        targets: Vec<A>,
+    ) -> Result<TxHash, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let targets = targets
+            .into_iter()
+            .map(|target| Ok(MultiAddress::Id(target.into_account()?)))
+            .collect::<Result<Vec<_>, crate::core::Error>>()?;
+        let tx = api.nominate(targets);
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+        let hash = event.extrinsic_hash();
+        Ok(format!("0x{}", hex::encode(hash.0)))
+    }
+
+    pub async fn payout_stakers(
+        &self,
+        validator_stash: impl IntoAccount,
+        era: u32,
+    ) -> Result<(TxHash, PayoutStarted), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let validator_stash = validator_stash.into_account()?;
+        let tx = api.payout_stakers(validator_stash, era);
+        let from = self.get_pair_signer();
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+
+        Self::find_first::<PayoutStarted>(event)
+    }
+}
@@ -1,2 +1,6 @@
+pub mod events;
+pub mod history;
+pub mod orders;
 pub mod query;
 pub mod transaction;
+pub mod watch;
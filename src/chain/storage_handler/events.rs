@@ -0,0 +1,166 @@
+use super::query::StorageQuery;
+use crate::chain::events::subscribe_mapped;
+use crate::core::Error;
+use crate::polkadot::storage_handler::events::{
+    BuyConsignment, Consignment, ExpansionTerritory, MintTerritory, ReactivateTerritory,
+    RenewalTerritory,
+};
+use crate::utils::account::IntoAccount;
+use crate::{init_api, H256};
+use futures::{stream, Stream, StreamExt};
+use std::time::Duration;
+use subxt::events::{EventDetails, Phase};
+use subxt::utils::AccountId32;
+use subxt::PolkadotConfig;
+
+/// Territory lifecycle events of the `StorageHandler` pallet.
+#[derive(Debug)]
+pub enum TerritoryEvent {
+    Mint(MintTerritory),
+    Expansion(ExpansionTerritory),
+    Renewal(RenewalTerritory),
+    Reactivate(ReactivateTerritory),
+    Consignment(Consignment),
+    BuyConsignment(BuyConsignment),
+}
+
+impl TerritoryEvent {
+    pub fn name(&self) -> &[u8] {
+        match self {
+            TerritoryEvent::Mint(e) => &e.name.0,
+            TerritoryEvent::Expansion(e) => &e.name.0,
+            TerritoryEvent::Renewal(e) => &e.name.0,
+            TerritoryEvent::Reactivate(e) => &e.name.0,
+            TerritoryEvent::Consignment(e) => &e.name.0,
+            TerritoryEvent::BuyConsignment(e) => &e.name.0,
+        }
+    }
+
+    pub fn token(&self) -> Option<H256> {
+        match self {
+            TerritoryEvent::Mint(e) => Some(e.token),
+            TerritoryEvent::Consignment(e) => Some(e.token),
+            TerritoryEvent::BuyConsignment(e) => Some(e.token),
+            _ => None,
+        }
+    }
+
+    fn from_details(event: &EventDetails<PolkadotConfig>) -> Result<Option<Self>, Error> {
+        if let Some(e) = event.as_event::<MintTerritory>()? {
+            return Ok(Some(TerritoryEvent::Mint(e)));
+        }
+        if let Some(e) = event.as_event::<ExpansionTerritory>()? {
+            return Ok(Some(TerritoryEvent::Expansion(e)));
+        }
+        if let Some(e) = event.as_event::<RenewalTerritory>()? {
+            return Ok(Some(TerritoryEvent::Renewal(e)));
+        }
+        if let Some(e) = event.as_event::<ReactivateTerritory>()? {
+            return Ok(Some(TerritoryEvent::Reactivate(e)));
+        }
+        if let Some(e) = event.as_event::<Consignment>()? {
+            return Ok(Some(TerritoryEvent::Consignment(e)));
+        }
+        if let Some(e) = event.as_event::<BuyConsignment>()? {
+            return Ok(Some(TerritoryEvent::BuyConsignment(e)));
+        }
+        Ok(None)
+    }
+
+    /// Whether the event is about a territory of `account`, `block_hash` and `phase` being where
+    /// it was emitted.
+    ///
+    /// The events carry no account. Events of a signed extrinsic are attributed to its signer,
+    /// e.g. the buyer for [`TerritoryEvent::BuyConsignment`]. Others are looked up on chain at
+    /// `block_hash`: through the territory token when the event has one, otherwise by the
+    /// territory name among `account`'s territories.
+    async fn concerns(
+        &self,
+        block_hash: H256,
+        phase: Phase,
+        account: &AccountId32,
+    ) -> Result<bool, Error> {
+        if let Phase::ApplyExtrinsic(index) = phase {
+            if let Some(signer) = extrinsic_signer(block_hash, index).await? {
+                return Ok(signer == *account);
+            }
+        }
+
+        if let Some(token) = self.token() {
+            let token = format!("0x{}", hex::encode(token.0));
+            let owner = StorageQuery::territory_key(&token, Some(block_hash))
+                .await
+                .map_err(|e| Error::Custom(e.to_string()))?;
+            if let Some((owner, _)) = owner {
+                return Ok(owner.as_str().into_account()? == *account);
+            }
+        }
+
+        let name = String::from_utf8_lossy(self.name()).to_string();
+        let territory = StorageQuery::territory(&account.to_string(), &name, Some(block_hash))
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        Ok(territory.is_some())
+    }
+}
+
+/// Signer of extrinsic `index` of block `block_hash`, `None` when it is unsigned.
+async fn extrinsic_signer(block_hash: H256, index: u32) -> Result<Option<AccountId32>, Error> {
+    let api = init_api().await?;
+    let extrinsics = api.blocks().at(block_hash).await?.extrinsics().await?;
+    let extrinsic = match extrinsics.iter().nth(index as usize) {
+        Some(extrinsic) => extrinsic?,
+        None => return Ok(None),
+    };
+    // A `MultiAddress::Id` signer encodes as its variant index followed by the account.
+    Ok(match extrinsic.address_bytes() {
+        Some([0, rest @ ..]) => <[u8; 32]>::try_from(rest).ok().map(AccountId32),
+        _ => None,
+    })
+}
+
+/// Streams territory events from finalized blocks, only those about territories of `account`
+/// when given.
+///
+/// With `silence_timeout`, the stream ends once no matching event arrived for that long.
+pub async fn subscribe_territory_events(
+    account: Option<&str>,
+    silence_timeout: Option<Duration>,
+) -> Result<impl Stream<Item = Result<TerritoryEvent, Error>>, Error> {
+    init_api().await?;
+    let account = account.map(|a| a.into_account()).transpose()?;
+
+    let events = subscribe_mapped(|details| {
+        if details.pallet_name() != "StorageHandler" {
+            return Ok(None);
+        }
+        let phase = details.phase();
+        Ok(TerritoryEvent::from_details(details)?.map(|event| (phase, event)))
+    })
+    .filter_map(move |item| {
+        let account = account.clone();
+        async move {
+            let (block_hash, (phase, event)) = match item {
+                Ok(item) => item,
+                Err(e) => return Some(Err(e)),
+            };
+            match account {
+                Some(account) => match event.concerns(block_hash, phase, &account).await {
+                    Ok(true) => Some(Ok(event)),
+                    Ok(false) => None,
+                    Err(e) => Some(Err(e)),
+                },
+                None => Some(Ok(event)),
+            }
+        }
+    });
+
+    let events = Box::pin(events);
+    Ok(stream::unfold(events, move |mut events| async move {
+        let next = match silence_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, events.next()).await.ok()?,
+            None => events.next().await,
+        };
+        next.map(|item| (item, events))
+    }))
+}
@@ -0,0 +1,178 @@
+//! Territory usage at past blocks, e.g. for billing at month boundaries.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), cess_rust_sdk::core::Error> {
+//! use cess_rust_sdk::chain::storage_handler::history::{
+//!     monthly_points, territory_usage_series, UsagePoint,
+//! };
+//! use std::time::{Duration, SystemTime};
+//!
+//! let since = SystemTime::now() - Duration::from_secs(365 * 24 * 60 * 60);
+//! let blocks = monthly_points(since).await?;
+//! let account = "cXh5StobuVP4B7mGH9xn8dSsDtXks4qLAou8ZdkZ6DbB6zzxe";
+//! for point in territory_usage_series(account, "hello", &blocks).await? {
+//!     if let UsagePoint::Available(usage) = point {
+//!         println!("#{} used {} bytes", usage.block_number, usage.used_space);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use super::query::StorageQuery;
+use crate::chain::block_timestamp;
+use crate::chain::blocks::BlockClock;
+use crate::core::Error;
+use crate::utils::get_block_hash_by_number;
+use crate::{init_api, H256};
+use futures::stream::{self, StreamExt};
+use std::time::{Duration, SystemTime};
+
+/// Most points [`territory_usage_series`] fetches at once.
+const MAX_CONCURRENT_POINTS: usize = 8;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Space of a territory at one block.
+#[derive(Debug, Clone)]
+pub struct TerritoryUsage {
+    pub block_number: u64,
+    pub block_hash: H256,
+    /// Milliseconds since the epoch, from `Timestamp::Now`.
+    pub timestamp: u64,
+    pub total_space: u128,
+    pub used_space: u128,
+    pub remaining_space: u128,
+}
+
+#[derive(Debug, Clone)]
+pub enum UsagePoint {
+    Available(TerritoryUsage),
+    /// The territory did not exist at the block.
+    NoTerritory {
+        block_number: u64,
+    },
+    /// The block or its state could not be read, e.g. because the node pruned it.
+    Unavailable {
+        block_number: u64,
+        reason: String,
+    },
+}
+
+/// Usage of `territory_name` of `account` at each of `block_numbers`, in the same order.
+///
+/// Points whose state cannot be read are reported as [`UsagePoint::Unavailable`] rather than
+/// failing the whole series.
+pub async fn territory_usage_series(
+    account: &str,
+    territory_name: &str,
+    block_numbers: &[u64],
+) -> Result<Vec<UsagePoint>, Error> {
+    // Fail once up front if there is no connection at all, instead of at every point.
+    init_api().await?;
+
+    Ok(stream::iter(block_numbers.iter().copied())
+        .map(|block_number| async move {
+            match usage_at(account, territory_name, block_number).await {
+                Ok(Some(usage)) => UsagePoint::Available(usage),
+                Ok(None) => UsagePoint::NoTerritory { block_number },
+                Err(e) => UsagePoint::Unavailable {
+                    block_number,
+                    reason: e.to_string(),
+                },
+            }
+        })
+        .buffered(MAX_CONCURRENT_POINTS)
+        .collect()
+        .await)
+}
+
+async fn usage_at(
+    account: &str,
+    territory_name: &str,
+    block_number: u64,
+) -> Result<Option<TerritoryUsage>, Error> {
+    let block_hash = get_block_hash_by_number(block_number).await?;
+    let timestamp = block_timestamp(block_hash)
+        .await?
+        .ok_or_else(|| Error::Custom(format!("Block #{} has no timestamp", block_number)))?;
+    let territory = StorageQuery::territory(account, territory_name, Some(block_hash))
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?;
+
+    Ok(territory.map(|territory| TerritoryUsage {
+        block_number,
+        block_hash,
+        timestamp,
+        total_space: territory.total_space,
+        used_space: territory.used_space,
+        remaining_space: territory.remaining_space,
+    }))
+}
+
+/// Numbers of the first blocks of every UTC month from `since` until now, see
+/// [`BlockClock::estimate_block_at`].
+pub async fn monthly_points(since: SystemTime) -> Result<Vec<u64>, Error> {
+    let clock = BlockClock::new().await;
+    let mut points = Vec::new();
+    for start in month_starts(since, SystemTime::now()) {
+        points.push(clock.estimate_block_at(start).await?);
+    }
+    Ok(points)
+}
+
+/// Starts of the UTC months within `since..=until`.
+pub fn month_starts(since: SystemTime, until: SystemTime) -> Vec<SystemTime> {
+    let since_secs = since
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let (mut year, mut month, _) = civil_from_days((since_secs / SECONDS_PER_DAY) as i64);
+    let mut starts = Vec::new();
+    loop {
+        let start = month_start(year, month);
+        if start > until {
+            break;
+        }
+        if start >= since {
+            starts.push(start);
+        }
+        (year, month) = if month == 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+    }
+    starts
+}
+
+fn month_start(year: i64, month: u32) -> SystemTime {
+    let days = days_from_civil(year, month, 1).max(0) as u64;
+    SystemTime::UNIX_EPOCH + Duration::from_secs(days * SECONDS_PER_DAY)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
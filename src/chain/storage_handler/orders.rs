@@ -0,0 +1,326 @@
+use super::query::StorageQuery;
+use crate::chain::Chain;
+use crate::constants::{
+    MAX_TERRITORY_NAME_LENGTH, MIN_TERRITORY_DAYS, ONE_DAY_BLOCKS, SIZE_1_GI_B,
+};
+use crate::core::Error;
+use crate::polkadot::{
+    runtime_types::bounded_collections::bounded_vec::BoundedVec,
+    runtime_types::pallet_storage_handler::types::{OrderInfo, OrderType, TerritoryState},
+    storage_handler::events::{CreatePayOrder, PaidOrder},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use subxt::utils::AccountId32;
+
+/// Identifier of a territory pay order, displayed and parsed as `0x` prefixed hex.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrderId(Vec<u8>);
+
+impl OrderId {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl fmt::Display for OrderId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(&self.0))
+    }
+}
+
+impl FromStr for OrderId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("0x").unwrap_or(s);
+        if s.is_empty() {
+            return Err("Invalid order id: empty string".into());
+        }
+        let bytes =
+            hex::decode(s).map_err(|e| Error::InvalidInput(format!("Invalid order id: {}", e)))?;
+        Ok(Self(bytes))
+    }
+}
+
+impl From<Vec<u8>> for OrderId {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<BoundedVec<u8>> for OrderId {
+    fn from(bytes: BoundedVec<u8>) -> Self {
+        Self(bytes.0)
+    }
+}
+
+impl From<OrderId> for BoundedVec<u8> {
+    fn from(order_id: OrderId) -> Self {
+        BoundedVec(order_id.0)
+    }
+}
+
+impl From<&CreatePayOrder> for OrderId {
+    fn from(event: &CreatePayOrder) -> Self {
+        Self(event.order_hash.0.clone())
+    }
+}
+
+impl From<&PaidOrder> for OrderId {
+    fn from(event: &PaidOrder) -> Self {
+        Self(event.order_hash.0.clone())
+    }
+}
+
+impl Serialize for OrderId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for OrderId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        OrderId::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Pay order as handed to the account paying it, shared as
+/// `<order id>:<target account>:<pay>:<territory name>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentOrder {
+    pub order_id: OrderId,
+    pub target_acc: AccountId32,
+    pub pay: u128,
+    pub territory_name: String,
+}
+
+impl PaymentOrder {
+    /// The order `order_id` as stored on chain, see [`StorageQuery::pay_order`].
+    pub fn new(order_id: OrderId, info: &OrderInfo) -> Self {
+        Self {
+            order_id,
+            target_acc: info.target_acc.clone(),
+            pay: info.pay,
+            territory_name: String::from_utf8_lossy(&info.territory_name.0).into_owned(),
+        }
+    }
+}
+
+impl fmt::Display for PaymentOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}",
+            self.order_id, self.target_acc, self.pay, self.territory_name
+        )
+    }
+}
+
+impl FromStr for PaymentOrder {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The territory name goes last, it may contain the separator.
+        let mut parts = s.splitn(4, ':');
+        let (Some(order_id), Some(target_acc), Some(pay), Some(territory_name)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(Error::InvalidInput(format!("Invalid payment order: {}", s)));
+        };
+
+        Ok(Self {
+            order_id: order_id.parse()?,
+            target_acc: AccountId32::from_str(target_acc).map_err(|e| {
+                Error::InvalidInput(format!("Invalid payment order account: {}", e))
+            })?,
+            pay: pay
+                .parse()
+                .map_err(|e| Error::InvalidInput(format!("Invalid payment order pay: {}", e)))?,
+            territory_name: territory_name.to_string(),
+        })
+    }
+}
+
+impl Serialize for PaymentOrder {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for PaymentOrder {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        PaymentOrder::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Territory pay order, validated before it is submitted through `create_order`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderSpec {
+    Buy {
+        territory_name: String,
+        gib_count: u32,
+        days: u32,
+    },
+    Expansion {
+        territory_name: String,
+        gib_count: u32,
+    },
+    Renewal {
+        territory_name: String,
+        days: u32,
+    },
+}
+
+impl OrderSpec {
+    pub fn buy(territory_name: &str, gib_count: u32, days: u32) -> Self {
+        Self::Buy {
+            territory_name: territory_name.to_string(),
+            gib_count,
+            days,
+        }
+    }
+
+    pub fn expand(territory_name: &str, gib_count: u32) -> Self {
+        Self::Expansion {
+            territory_name: territory_name.to_string(),
+            gib_count,
+        }
+    }
+
+    pub fn renew(territory_name: &str, days: u32) -> Self {
+        Self::Renewal {
+            territory_name: territory_name.to_string(),
+            days,
+        }
+    }
+
+    pub fn territory_name(&self) -> &str {
+        match self {
+            Self::Buy { territory_name, .. }
+            | Self::Expansion { territory_name, .. }
+            | Self::Renewal { territory_name, .. } => territory_name,
+        }
+    }
+
+    pub fn order_type(&self) -> OrderType {
+        match self {
+            Self::Buy { .. } => OrderType::Buy,
+            Self::Expansion { .. } => OrderType::Expansion,
+            Self::Renewal { .. } => OrderType::Renewal,
+        }
+    }
+
+    /// `(gib_count, days)` as expected by `create_order`, unused values are zero.
+    pub fn args(&self) -> (u32, u32) {
+        match self {
+            Self::Buy {
+                gib_count, days, ..
+            } => (*gib_count, *days),
+            Self::Expansion { gib_count, .. } => (*gib_count, 0),
+            Self::Renewal { days, .. } => (0, *days),
+        }
+    }
+
+    /// Checks the parameters that can be validated without querying the chain.
+    pub fn validate(&self) -> Result<(), Error> {
+        let name = self.territory_name();
+        if name.is_empty() || name.len() > MAX_TERRITORY_NAME_LENGTH {
+            return Err(format!(
+                "Invalid territory name: length must be between 1 and {}.",
+                MAX_TERRITORY_NAME_LENGTH
+            )
+            .into());
+        }
+
+        match self {
+            Self::Buy {
+                gib_count, days, ..
+            } => {
+                if *gib_count == 0 {
+                    return Err("Invalid order: gib_count must be greater than 0.".into());
+                }
+                if *days < MIN_TERRITORY_DAYS {
+                    return Err(format!(
+                        "Invalid order: the number of days must be {} or more.",
+                        MIN_TERRITORY_DAYS
+                    )
+                    .into());
+                }
+            }
+            Self::Expansion { gib_count, .. } => {
+                if *gib_count == 0 {
+                    return Err("Invalid order: gib_count must be greater than 0.".into());
+                }
+            }
+            Self::Renewal { days, .. } => {
+                if *days == 0 {
+                    return Err("Invalid order: days must be greater than 0.".into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the order against the territory state of `target_acc` and
+    /// returns the price the order will charge.
+    pub async fn prepare(&self, target_acc: &str) -> Result<u128, Box<dyn std::error::Error>> {
+        self.validate()?;
+
+        let name = self.territory_name();
+        let territory = StorageQuery::territory(target_acc, name, None).await?;
+        let unit_price = StorageQuery::unit_price(None)
+            .await?
+            .ok_or("Unit price is not set on chain.")?;
+
+        match self {
+            Self::Buy {
+                gib_count, days, ..
+            } => {
+                if territory.is_some() {
+                    return Err(format!("Territory '{}' already exists.", name).into());
+                }
+                Ok(territory_price(unit_price, *gib_count, *days))
+            }
+            Self::Expansion { gib_count, .. } => {
+                let territory = territory.ok_or(format!("Territory '{}' does not exist.", name))?;
+                if !matches!(territory.state, TerritoryState::Active) {
+                    return Err(format!("Territory '{}' is not active.", name).into());
+                }
+                let now = StorageQuery::get_latest_block().await? as u32;
+                let remaining_days = territory.deadline.saturating_sub(now) / ONE_DAY_BLOCKS;
+                Ok(territory_price(unit_price, *gib_count, remaining_days))
+            }
+            Self::Renewal { days, .. } => {
+                let territory = territory.ok_or(format!("Territory '{}' does not exist.", name))?;
+                if matches!(
+                    territory.state,
+                    TerritoryState::Expired | TerritoryState::OnConsignment
+                ) {
+                    return Err(format!("Territory '{}' cannot be renewed.", name).into());
+                }
+                let gib_count = (territory.total_space / SIZE_1_GI_B as u128) as u32;
+                Ok(territory_price(unit_price, gib_count, *days))
+            }
+        }
+    }
+}
+
+/// Price of `gib_count` GiB for `days` days, `unit_price` being the price of 1 GiB for 30 days.
+///
+/// This is the `spend` `mint_territory` charges and reports in its `MintTerritory` event, and the
+/// basis of expansion and renewal prices. Every cost estimate of the SDK goes through it.
+pub fn territory_price(unit_price: u128, gib_count: u32, days: u32) -> u128 {
+    unit_price
+        .saturating_mul(gib_count as u128)
+        .saturating_mul(days as u128)
+        / 30
+}
@@ -1,5 +1,8 @@
+use super::orders::{territory_price, OrderId};
+use crate::chain::sanity::{check_unit_price, checked};
 use crate::chain::{Chain, Query};
-use crate::core::ApiProvider;
+use crate::constants::{ONE_DAY_BLOCKS, SIZE_1_GI_B};
+use crate::core::{ApiProvider, Error};
 use crate::polkadot::{
     self,
     runtime_types::bounded_collections::bounded_vec::BoundedVec,
@@ -8,6 +11,7 @@ use crate::polkadot::{
 };
 use crate::utils::get_ss58_address;
 use crate::{impl_api_provider, H256};
+use futures::StreamExt;
 use std::str::FromStr;
 use subxt::utils::AccountId32;
 
@@ -18,6 +22,25 @@ impl_api_provider!(
     polkadot::storage().storage_handler()
 );
 
+/// Territory extrinsic priced by [`StorageQuery::estimate_territory_cost`].
+#[derive(Debug, Clone, Copy)]
+pub enum TerritoryOp<'a> {
+    /// `mint_territory`, priced by `gib_count` and `days`.
+    Mint,
+    /// `expand_territory` of an existing territory, priced by `gib_count` and the whole days
+    /// left before its deadline. `days` is ignored.
+    Expand {
+        account: &'a str,
+        territory_name: &'a str,
+    },
+    /// `renew_territory` of an existing territory, priced by its total space and `days`.
+    /// `gib_count` is ignored.
+    Renew {
+        account: &'a str,
+        territory_name: &'a str,
+    },
+}
+
 pub struct StorageQuery;
 
 impl Chain for StorageQuery {}
@@ -59,7 +82,7 @@ impl StorageQuery {
         let territory_name = territory_name.as_bytes().to_vec();
         let query = api.territory(account, BoundedVec(territory_name));
 
-        Self::execute_query(&query, block_hash).await
+        Ok(checked(Self::execute_query(&query, block_hash).await?)?)
     }
 
     pub async fn territories_by_account(
@@ -80,7 +103,7 @@ impl StorageQuery {
         if results.is_empty() {
             Ok(None)
         } else {
-            Ok(Some(results))
+            Ok(checked(Some(results))?)
         }
     }
 
@@ -92,7 +115,7 @@ impl StorageQuery {
         let token = H256::from_str(token).unwrap();
         let query = api.consignment(token);
 
-        Self::execute_query(&query, block_hash).await
+        Ok(checked(Self::execute_query(&query, block_hash).await?)?)
     }
 
     pub async fn territory_frozen(
@@ -135,7 +158,9 @@ impl StorageQuery {
         let api = Self::get_api();
         let query = api.unit_price();
 
-        Self::execute_query(&query, block_hash).await
+        Ok(check_unit_price(
+            Self::execute_query(&query, block_hash).await?,
+        )?)
     }
 
     pub async fn total_power(
@@ -166,13 +191,57 @@ impl StorageQuery {
     }
 
     pub async fn pay_order(
-        order_hash: &str,
+        order_id: &OrderId,
         block_hash: Option<H256>,
     ) -> Result<Option<OrderInfo>, Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let order_hash = order_hash.as_bytes().to_vec();
-        let query = api.pay_order(BoundedVec(order_hash));
+        let query = api.pay_order(BoundedVec::from(order_id.clone()));
 
         Self::execute_query(&query, block_hash).await
     }
+
+    /// The fee `op` charges, see [`territory_price`].
+    pub async fn estimate_territory_cost(
+        gib_count: u32,
+        days: u32,
+        op: TerritoryOp<'_>,
+    ) -> Result<u128, Error> {
+        let unit_price = Self::unit_price(None)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?
+            .ok_or_else(|| Error::Custom("Unit price not set on chain".into()))?;
+
+        match op {
+            TerritoryOp::Mint => Ok(territory_price(unit_price, gib_count, days)),
+            TerritoryOp::Expand {
+                account,
+                territory_name,
+            } => {
+                let territory = Self::existing_territory(account, territory_name).await?;
+                let current = Self::get_latest_block().await? as u32;
+                let remaining_days = territory.deadline.saturating_sub(current) / ONE_DAY_BLOCKS;
+                Ok(territory_price(unit_price, gib_count, remaining_days))
+            }
+            TerritoryOp::Renew {
+                account,
+                territory_name,
+            } => {
+                let territory = Self::existing_territory(account, territory_name).await?;
+                let gib_count = (territory.total_space / SIZE_1_GI_B as u128) as u32;
+                Ok(territory_price(unit_price, gib_count, days))
+            }
+        }
+    }
+
+    async fn existing_territory(
+        account: &str,
+        territory_name: &str,
+    ) -> Result<TerritoryInfo, Error> {
+        Self::territory(account, territory_name, None)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?
+            .ok_or_else(|| Error::TerritoryNotFound {
+                name: territory_name.to_string(),
+            })
+    }
 }
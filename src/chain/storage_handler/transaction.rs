@@ -1,23 +1,26 @@
-use crate::chain::{Call, Chain};
-use crate::core::ApiProvider;
+use super::orders::{territory_price, OrderId, OrderSpec};
+use super::query::StorageQuery;
+use crate::chain::balances;
+use crate::chain::{Call, Chain, TxOptions};
+use crate::core::{ApiProvider, Error};
 use crate::impl_api_provider;
-use crate::polkadot::storage_handler::calls::types::exec_order::OrderId;
 use crate::polkadot::storage_handler::events::PaidOrder;
 use crate::polkadot::{
     self,
     runtime_types::bounded_collections::bounded_vec::BoundedVec,
-    runtime_types::pallet_storage_handler::types::OrderType,
+    runtime_types::pallet_storage_handler::types::{OrderType, TerritoryState},
     storage_handler::calls::TransactionApi,
     storage_handler::events::{
         BuyConsignment, CancelPurchaseAction, CancleConsignment, Consignment, CreatePayOrder,
         ExpansionTerritory, MintTerritory, ReactivateTerritory, RenewalTerritory,
     },
 };
+use crate::utils::account::IntoAccount;
 use crate::H256;
 use std::str::FromStr;
+use subxt::blocks::ExtrinsicEvents;
 use subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
-use subxt::ext::subxt_core::utils::AccountId32;
-use subxt::tx::PairSigner;
+use subxt::tx::{PairSigner, Payload};
 use subxt::PolkadotConfig;
 
 // impl ApiProvider for TransactionApiProvider
@@ -30,6 +33,8 @@ impl_api_provider!(
 pub type TxHash = String;
 pub struct StorageTransaction {
     pair: PairS,
+    options: TxOptions,
+    check_balance: bool,
 }
 
 impl Chain for StorageTransaction {}
@@ -49,7 +54,25 @@ impl Call for StorageTransaction {
 impl StorageTransaction {
     pub fn new(mnemonic: &str) -> Self {
         let pair = PairS::from_string(mnemonic, None).unwrap();
-        Self { pair }
+        Self {
+            pair,
+            options: TxOptions::default(),
+            check_balance: false,
+        }
+    }
+
+    /// Applies `options` to every transaction submitted through this instance.
+    pub fn with_options(mut self, options: TxOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Checks that the signer's free balance covers the price of `mint_territory` before
+    /// submitting it, failing with [`Error::InsufficientBalance`] instead of paying the fee of a
+    /// failing extrinsic.
+    pub fn with_balance_check(mut self, check_balance: bool) -> Self {
+        self.check_balance = check_balance;
+        self
     }
 
     pub async fn mint_territory(
@@ -64,24 +87,41 @@ impl StorageTransaction {
         if days < 30 {
             return Err("Invalid input: The number of days must be 30 or more.".into());
         }
+        if self.check_balance {
+            self.ensure_balance(gib_count, days).await?;
+        }
 
         let tx = api.mint_territory(gib_count, BoundedVec(territory_name), days);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<MintTerritory>(event)
     }
 
+    /// Fee [`Self::mint_territory`] would pay on top of the territory price, see
+    /// [`territory_price`].
+    pub async fn estimate_mint_territory_fee(
+        &self,
+        gib_count: u32,
+        territory_name: &str,
+        days: u32,
+    ) -> Result<u128, Error> {
+        let api = Self::get_api();
+        let territory_name = territory_name.as_bytes().to_vec();
+        let tx = api.mint_territory(gib_count, BoundedVec(territory_name), days);
+
+        self.estimate_partial_fee(&tx).await
+    }
+
     pub async fn expand_territory(
         &self,
         territory_name: &str,
         gib_count: u32,
     ) -> Result<(TxHash, ExpansionTerritory), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let territory_name = territory_name.as_bytes().to_vec();
-        let tx = api.expanding_territory(BoundedVec(territory_name), gib_count);
-        let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let name = territory_name.as_bytes().to_vec();
+        let tx = api.expanding_territory(BoundedVec(name), gib_count);
+        let event = self.submit_for_territory(&tx, territory_name).await?;
 
         Self::find_first::<ExpansionTerritory>(event)
     }
@@ -91,13 +131,38 @@ impl StorageTransaction {
         territory_name: &str,
         days: u32,
     ) -> Result<(TxHash, RenewalTerritory), Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let name = territory_name.as_bytes().to_vec();
+        let tx = api.renewal_territory(BoundedVec(name), days);
+        let event = self.submit_for_territory(&tx, territory_name).await?;
+
+        Self::find_first::<RenewalTerritory>(event)
+    }
+
+    /// Fee [`Self::expand_territory`] would pay on top of the territory price.
+    pub async fn estimate_expand_territory_fee(
+        &self,
+        territory_name: &str,
+        gib_count: u32,
+    ) -> Result<u128, Error> {
+        let api = Self::get_api();
+        let territory_name = territory_name.as_bytes().to_vec();
+        let tx = api.expanding_territory(BoundedVec(territory_name), gib_count);
+
+        self.estimate_partial_fee(&tx).await
+    }
+
+    /// Fee [`Self::renew_territory`] would pay on top of the territory price.
+    pub async fn estimate_renew_territory_fee(
+        &self,
+        territory_name: &str,
+        days: u32,
+    ) -> Result<u128, Error> {
         let api = Self::get_api();
         let territory_name = territory_name.as_bytes().to_vec();
         let tx = api.renewal_territory(BoundedVec(territory_name), days);
-        let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
 
-        Self::find_first::<RenewalTerritory>(event)
+        self.estimate_partial_fee(&tx).await
     }
 
     pub async fn reactivate_territory(
@@ -106,10 +171,9 @@ impl StorageTransaction {
         days: u32,
     ) -> Result<(TxHash, ReactivateTerritory), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let territory_name = territory_name.as_bytes().to_vec();
-        let tx = api.reactivate_territory(BoundedVec(territory_name), days);
-        let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let name = territory_name.as_bytes().to_vec();
+        let tx = api.reactivate_territory(BoundedVec(name), days);
+        let event = self.submit_for_territory(&tx, territory_name).await?;
 
         Self::find_first::<ReactivateTerritory>(event)
     }
@@ -120,10 +184,9 @@ impl StorageTransaction {
         price: u128,
     ) -> Result<(TxHash, Consignment), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let territory_name = territory_name.as_bytes().to_vec();
-        let tx = api.territory_consignment(BoundedVec(territory_name), price);
-        let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let name = territory_name.as_bytes().to_vec();
+        let tx = api.territory_consignment(BoundedVec(name), price);
+        let event = self.submit_for_territory(&tx, territory_name).await?;
 
         Self::find_first::<Consignment>(event)
     }
@@ -138,7 +201,7 @@ impl StorageTransaction {
         let rename = rename.as_bytes().to_vec();
         let tx = api.buy_consignment(token, BoundedVec(rename));
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<BuyConsignment>(event)
     }
@@ -148,10 +211,9 @@ impl StorageTransaction {
         territory_name: &str,
     ) -> Result<(TxHash, CancleConsignment), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let territory_name = territory_name.as_bytes().to_vec();
-        let tx = api.cancel_consignment(BoundedVec(territory_name));
-        let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let name = territory_name.as_bytes().to_vec();
+        let tx = api.cancel_consignment(BoundedVec(name));
+        let event = self.submit_for_territory(&tx, territory_name).await?;
 
         Self::find_first::<CancleConsignment>(event)
     }
@@ -164,7 +226,7 @@ impl StorageTransaction {
         let token = H256::from_str(token).unwrap();
         let tx = api.cancel_purchase_action(token);
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<CancelPurchaseAction>(event)
     }
@@ -172,14 +234,13 @@ impl StorageTransaction {
     pub async fn territory_grants(
         &self,
         territory_name: &str,
-        receiver: &str,
+        receiver: impl IntoAccount,
     ) -> Result<TxHash, Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let territory_name = territory_name.as_bytes().to_vec();
-        let receiver = AccountId32::from_str(receiver)?;
-        let tx = api.territory_grants(BoundedVec(territory_name), receiver);
-        let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let name = territory_name.as_bytes().to_vec();
+        let receiver = receiver.into_account()?;
+        let tx = api.territory_grants(BoundedVec(name), receiver);
+        let event = self.submit_for_territory(&tx, territory_name).await?;
         let hash = event.extrinsic_hash();
         Ok(format!("0x{}", hex::encode(hash.0)))
     }
@@ -190,6 +251,7 @@ impl StorageTransaction {
         new_territory_name: &str,
     ) -> Result<TxHash, Box<dyn std::error::Error>> {
         let api = Self::get_api();
+        let territory_name = old_territory_name;
 
         let old_territory_name = if old_territory_name.starts_with("0x") {
             hex::decode(
@@ -211,23 +273,22 @@ impl StorageTransaction {
             BoundedVec(old_territory_name),
             BoundedVec(new_territory_name),
         );
-        let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = self.submit_for_territory(&tx, territory_name).await?;
         let hash = event.extrinsic_hash();
         Ok(format!("0x{}", hex::encode(hash.0)))
     }
 
     pub async fn create_order(
         &self,
-        target_acc: &str,
+        target_acc: impl IntoAccount,
         territory_name: &str,
         order_type: OrderType,
         gib_count: u32,
         days: u32,
         expired: u32,
-    ) -> Result<(TxHash, CreatePayOrder), Box<dyn std::error::Error>> {
+    ) -> Result<(TxHash, OrderId), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let target_acc = AccountId32::from_str(target_acc)?;
+        let target_acc = target_acc.into_account()?;
         let territory_name = territory_name.as_bytes().to_vec();
         let tx = api.create_order(
             target_acc,
@@ -238,20 +299,100 @@ impl StorageTransaction {
             expired,
         );
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
+        let (tx_hash, created) = Self::find_first::<CreatePayOrder>(event)?;
+
+        Ok((tx_hash, OrderId::from(&created)))
+    }
 
-        Self::find_first::<CreatePayOrder>(event)
+    /// Validates `spec` against the target territory and creates the order,
+    /// returning the expected price alongside the order id.
+    pub async fn create_order_with_spec(
+        &self,
+        target_acc: &str,
+        spec: &OrderSpec,
+        expired: u32,
+    ) -> Result<(TxHash, OrderId, u128), Box<dyn std::error::Error>> {
+        let price = spec.prepare(target_acc).await?;
+        let (gib_count, days) = spec.args();
+        let (tx_hash, order_id) = self
+            .create_order(
+                target_acc,
+                spec.territory_name(),
+                spec.order_type(),
+                gib_count,
+                days,
+                expired,
+            )
+            .await?;
+
+        Ok((tx_hash, order_id, price))
     }
 
     pub async fn exec_order(
         &self,
-        order_id: OrderId,
+        order_id: &OrderId,
     ) -> Result<(TxHash, PaidOrder), Box<dyn std::error::Error>> {
         let api = Self::get_api();
-        let tx = api.exec_order(order_id);
+        let tx = api.exec_order(BoundedVec::from(order_id.clone()));
         let from = self.get_pair_signer();
-        let event = Self::sign_and_submit_tx_then_watch_default(&tx, &from).await?;
+        let event = Self::sign_and_submit_tx_with_options(&tx, &from, &self.options).await?;
 
         Self::find_first::<PaidOrder>(event)
     }
+
+    /// Submits `tx` acting on `territory_name` of the signer. When the territory is frozen, the
+    /// error tells the lease deadline it froze at.
+    async fn submit_for_territory<C: Payload + Sync>(
+        &self,
+        tx: &C,
+        territory_name: &str,
+    ) -> Result<ExtrinsicEvents<PolkadotConfig>, Box<dyn std::error::Error>> {
+        let from = self.get_pair_signer();
+        let result = Self::sign_and_submit_tx_with_options(tx, &from, &self.options).await;
+        // Decided before the lookup, which must not hold the non-`Send` error across an await.
+        let events = match result {
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<Error>(),
+                    Some(Error::TerritoryFrozen { deadline: None })
+                ) =>
+            {
+                None
+            }
+            other => Some(other?),
+        };
+        match events {
+            Some(events) => Ok(events),
+            None => Err(self.territory_frozen(territory_name).await.into()),
+        }
+    }
+
+    async fn territory_frozen(&self, territory_name: &str) -> Error {
+        let account = self.get_pair_signer().account_id().to_string();
+        let territory = StorageQuery::territory(&account, territory_name, None)
+            .await
+            .ok()
+            .flatten();
+        let deadline = territory
+            .filter(|territory| matches!(territory.state, TerritoryState::Frozen))
+            .map(|territory| territory.deadline);
+        Error::TerritoryFrozen { deadline }
+    }
+
+    async fn ensure_balance(&self, gib_count: u32, days: u32) -> Result<(), Error> {
+        let unit_price = StorageQuery::unit_price(None)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?
+            .ok_or_else(|| Error::Custom("Unit price not set on chain".into()))?;
+        let needed = territory_price(unit_price, gib_count, days);
+        let account = self.get_pair_signer().account_id().clone();
+        let available = balances::query::StorageQuery::free_balance(account, None)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        if available < needed {
+            return Err(Error::InsufficientBalance { needed, available });
+        }
+        Ok(())
+    }
 }
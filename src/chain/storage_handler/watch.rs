@@ -0,0 +1,180 @@
+use super::query::StorageQuery;
+use crate::chain::blocks::BlockClock;
+use crate::chain::Chain;
+use crate::constants::BLOCK_INTERVAL;
+use crate::core::Error;
+use crate::H256;
+use futures::{stream, Stream};
+use log::warn;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A territory got closer to its deadline than one of the watcher thresholds.
+#[derive(Debug, Clone)]
+pub struct TerritoryAlert {
+    pub name: String,
+    pub token: H256,
+    pub blocks_remaining: u32,
+    pub estimated_expiry: SystemTime,
+    /// The threshold crossed.
+    pub threshold: Duration,
+}
+
+/// Warns before the territories of an account expire.
+///
+/// ```no_run
+/// # async fn run() {
+/// use cess_rust_sdk::chain::storage_handler::watch::TerritoryWatcher;
+/// use futures::StreamExt;
+///
+/// let account = "cXh5StobuVP4B7mGH9xn8dSsDtXks4qLAou8ZdkZ6DbB6zzxe";
+/// let mut alerts = Box::pin(TerritoryWatcher::new(account).watch());
+/// while let Some(alert) = alerts.next().await {
+///     println!("{:?}", alert);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TerritoryWatcher {
+    account: String,
+    thresholds: Vec<Duration>,
+    refresh_interval: Duration,
+}
+
+impl TerritoryWatcher {
+    /// Watches `account` with thresholds of 7 days and 1 day, refreshing every 10 minutes.
+    pub fn new(account: &str) -> Self {
+        Self {
+            account: account.to_string(),
+            thresholds: vec![7 * DAY, DAY],
+            refresh_interval: Duration::from_secs(10 * 60),
+        }
+    }
+
+    /// Time left before the deadline at which to alert. Each threshold alerts once per territory,
+    /// until the territory is renewed past it.
+    pub fn thresholds(mut self, thresholds: Vec<Duration>) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Time between two refreshes, counted in finalized blocks at the observed block time.
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Streams alerts as territories cross the thresholds. Failed refreshes, e.g. while the RPC
+    /// connection is down, are yielded as errors and retried on the next interval without ending
+    /// the stream.
+    ///
+    /// A territory renewed past some thresholds alerts again when it gets close to them anew.
+    pub fn watch(mut self) -> impl Stream<Item = Result<TerritoryAlert, Error>> {
+        // Longest first, so a higher index is a more urgent threshold.
+        self.thresholds.sort_by(|a, b| b.cmp(a));
+        let state = WatchState {
+            watcher: self,
+            alerted: HashMap::new(),
+            pending: VecDeque::new(),
+            clock: None,
+        };
+
+        stream::unfold(state, |mut state| async move {
+            let item = state.next_alert().await;
+            Some((item, state))
+        })
+    }
+}
+
+struct WatchState {
+    watcher: TerritoryWatcher,
+    /// Index of the most urgent threshold alerted, by territory token.
+    alerted: HashMap<H256, usize>,
+    pending: VecDeque<TerritoryAlert>,
+    /// Paces refreshes, `None` until the first one is done.
+    clock: Option<BlockClock>,
+}
+
+impl WatchState {
+    async fn next_alert(&mut self) -> Result<TerritoryAlert, Error> {
+        loop {
+            if let Some(alert) = self.pending.pop_front() {
+                return Ok(alert);
+            }
+
+            match self.clock.as_mut() {
+                Some(clock) => {
+                    let block_ms = clock.average_block_time().as_millis().max(1);
+                    let blocks = self.watcher.refresh_interval.as_millis() / block_ms;
+                    clock
+                        .wait_blocks(blocks.clamp(1, u32::MAX as u128) as u32)
+                        .await?;
+                }
+                None => self.clock = Some(BlockClock::new().await),
+            }
+            if let Err(e) = self.refresh().await {
+                warn!(target: "SDK", "Territory refresh failed: {}", e);
+                return Err(e);
+            }
+        }
+    }
+
+    async fn refresh(&mut self) -> Result<(), Error> {
+        let current = StorageQuery::get_latest_block().await? as u32;
+        let territories = StorageQuery::territories_by_account(&self.watcher.account, None)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?
+            .unwrap_or_default();
+
+        let block_time = self
+            .clock
+            .as_ref()
+            .map_or(BLOCK_INTERVAL, BlockClock::average_block_time);
+        for territory in territories {
+            let blocks_remaining = territory.deadline.saturating_sub(current);
+            let remaining = block_time * blocks_remaining;
+            let crossed = self
+                .watcher
+                .thresholds
+                .iter()
+                .rposition(|threshold| remaining <= *threshold);
+
+            let crossed = match crossed {
+                Some(crossed) => crossed,
+                None => {
+                    // Renewed past every threshold.
+                    self.alerted.remove(&territory.token);
+                    continue;
+                }
+            };
+            if let Some(alerted) = self.alerted.get_mut(&territory.token) {
+                if *alerted >= crossed {
+                    // Renewed past the more urgent thresholds, which alert again once crossed.
+                    *alerted = crossed;
+                    continue;
+                }
+            }
+
+            let token = format!("{:?}", territory.token);
+            let name = match StorageQuery::territory_key(&token, None)
+                .await
+                .map_err(|e| Error::Custom(e.to_string()))?
+            {
+                Some((_, name)) => name,
+                None => continue,
+            };
+            self.alerted.insert(territory.token, crossed);
+            self.pending.push_back(TerritoryAlert {
+                name,
+                token: territory.token,
+                blocks_remaining,
+                estimated_expiry: SystemTime::now() + remaining,
+                threshold: self.watcher.thresholds[crossed],
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use crate::chain::{Chain, Query};
+use crate::core::ApiProvider;
+use crate::polkadot::{
+    self, runtime_types::pallet_tee_worker::pallet::WorkerInfo, tee_worker::storage::StorageApi,
+};
+use crate::{impl_api_provider, H256};
+use subxt::utils::AccountId32;
+
+// impl ApiProvider for StorageApiProvider
+impl_api_provider!(
+    StorageApiProvider,
+    StorageApi,
+    polkadot::storage().tee_worker()
+);
+
+/// Public key identifying a TEE worker.
+pub type WorkerPubkey = [u8; 32];
+
+pub struct StorageQuery;
+
+impl Chain for StorageQuery {}
+
+impl Query for StorageQuery {
+    type Api = StorageApi;
+
+    fn get_api() -> Self::Api {
+        crate::core::get_api::<StorageApiProvider>()
+    }
+}
+
+impl StorageQuery {
+    pub async fn master_pubkey(
+        block_hash: Option<H256>,
+    ) -> Result<Option<WorkerPubkey>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.master_pubkey();
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn worker(
+        pubkey: WorkerPubkey,
+        block_hash: Option<H256>,
+    ) -> Result<Option<WorkerInfo<AccountId32>>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.workers(pubkey);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn worker_count(
+        block_hash: Option<H256>,
+    ) -> Result<Option<u32>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.counter_for_workers();
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    pub async fn endpoint(
+        pubkey: WorkerPubkey,
+        block_hash: Option<H256>,
+    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.endpoints(pubkey);
+
+        Self::execute_query(&query, block_hash).await
+    }
+
+    /// All registered workers, each with its public key and the endpoint it is reachable at.
+    pub async fn tee_worker_list(
+        block_hash: Option<H256>,
+    ) -> Result<Vec<(WorkerPubkey, String)>, Box<dyn std::error::Error>> {
+        let api = Self::get_api();
+        let query = api.endpoints_iter();
+
+        let mut stream = Self::execute_iter(query, block_hash).await?;
+        let mut results = Vec::new();
+        while let Some(result) = stream.next().await {
+            let key_value = result?;
+            // The map is keyed with a concat hasher, so the key itself ends the storage key.
+            let key_bytes = &key_value.key_bytes;
+            if key_bytes.len() < 32 {
+                return Err(
+                    format!("Malformed Endpoints key: 0x{}", hex::encode(key_bytes)).into(),
+                );
+            }
+            let mut pubkey = [0u8; 32];
+            pubkey.copy_from_slice(&key_bytes[key_bytes.len() - 32..]);
+            results.push((pubkey, key_value.value));
+        }
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,170 @@
+//! Follows an extrinsic submitted without watching it, until it lands in a finalized block or
+//! is given up on.
+//!
+//! ```no_run
+//! # async fn run(tx_hash: subxt::utils::H256, submitted_at: u64) -> Result<(), cess_rust_sdk::core::Error> {
+//! use cess_rust_sdk::chain::tracker::{TxStatus, TxTracker};
+//!
+//! match TxTracker::new().track(tx_hash, submitted_at).await? {
+//!     TxStatus::Included { block, success } => println!("In {:?}, success: {}", block, success),
+//!     TxStatus::Dropped => println!("Not included"),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::CessConfig;
+use crate::core::Error;
+use crate::polkadot::system::events::ExtrinsicFailed;
+use crate::{init_api, H256};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use std::future::Future;
+use subxt::blocks::{Block, ExtrinsicEvents};
+use subxt::config::{substrate::BlakeTwo256, Hasher};
+use subxt::{Config, OnlineClient};
+
+/// Blocks after submission an extrinsic is looked for by default, the mortality window
+/// wallets commonly sign with.
+pub const DEFAULT_TRACKING_BLOCKS: u64 = 64;
+
+type Resubmit = Box<dyn Fn() -> BoxFuture<'static, Result<H256, Error>> + Send + Sync>;
+
+/// Final status of a tracked extrinsic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxStatus {
+    /// Included in the finalized `block`, `success` is false when it dispatched with an error.
+    Included { block: H256, success: bool },
+    /// Not included within the tracked window.
+    Dropped,
+}
+
+/// An extrinsic submitted by [`crate::chain::Call::sign_and_submit_tracked`].
+pub struct TrackedTx {
+    pub tx_hash: H256,
+    /// Resolves once the extrinsic is included or dropped. Blocks finalized before it is first
+    /// polled are searched too, so it can be awaited late.
+    pub status: BoxFuture<'static, Result<TxStatus, Error>>,
+}
+
+/// Watches finalized blocks for an extrinsic by hash.
+pub struct TxTracker {
+    max_blocks: u64,
+    resubmit: Option<Resubmit>,
+}
+
+impl Default for TxTracker {
+    fn default() -> Self {
+        Self {
+            max_blocks: DEFAULT_TRACKING_BLOCKS,
+            resubmit: None,
+        }
+    }
+}
+
+impl TxTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blocks after submission the extrinsic is looked for before it counts as dropped,
+    /// [`DEFAULT_TRACKING_BLOCKS`] by default. Match the mortality it was signed with.
+    pub fn max_blocks(mut self, max_blocks: u64) -> Self {
+        self.max_blocks = max_blocks;
+        self
+    }
+
+    /// Calls `resubmit` once when the extrinsic is dropped, then tracks the hash it returns.
+    pub fn on_dropped<F, Fut>(mut self, resubmit: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<H256, Error>> + Send + 'static,
+    {
+        self.resubmit = Some(Box::new(move || Box::pin(resubmit())));
+        self
+    }
+
+    /// Follows `tx_hash`, submitted when block `submitted_at_block` was the latest, until it is
+    /// included in a finalized block or `max_blocks` blocks pass without it.
+    pub async fn track(&self, tx_hash: H256, submitted_at_block: u64) -> Result<TxStatus, Error> {
+        let api = init_api().await?;
+        self.track_on(&api, tx_hash, submitted_at_block).await
+    }
+
+    /// [`TxTracker::track`] on `api`, the client the extrinsic was submitted through.
+    pub async fn track_on<C: CessConfig>(
+        &self,
+        api: &OnlineClient<C>,
+        tx_hash: H256,
+        submitted_at_block: u64,
+    ) -> Result<TxStatus, Error> {
+        let mut tx_hash = tx_hash;
+        let mut window_start = submitted_at_block;
+        let mut resubmitted = false;
+        let mut scanned = submitted_at_block;
+
+        let mut blocks = api.blocks().subscribe_finalized().await?;
+        while let Some(block) = blocks.next().await {
+            let block = block?;
+            let number: u64 = block.number().into();
+            if number <= scanned {
+                continue;
+            }
+
+            // Finalized blocks the subscription skipped, e.g. before the status was polled.
+            let mut missed = Vec::new();
+            let mut parent = block.header().parent_hash;
+            for _ in (scanned + 1)..number {
+                let earlier = api.blocks().at(parent).await?;
+                parent = earlier.header().parent_hash;
+                missed.push(earlier);
+            }
+            for block in missed.into_iter().rev().chain(std::iter::once(block)) {
+                if let Some(status) = find_in_block(&block, tx_hash).await? {
+                    return Ok(status);
+                }
+            }
+            scanned = number;
+
+            if number > window_start + self.max_blocks {
+                match (&self.resubmit, resubmitted) {
+                    (Some(resubmit), false) => {
+                        tx_hash = resubmit().await?;
+                        window_start = number;
+                        resubmitted = true;
+                    }
+                    _ => return Ok(TxStatus::Dropped),
+                }
+            }
+        }
+
+        Err("Finalized block subscription ended".into())
+    }
+}
+
+async fn find_in_block<C: CessConfig>(
+    block: &Block<C, OnlineClient<C>>,
+    tx_hash: H256,
+) -> Result<Option<TxStatus>, Error> {
+    match find_extrinsic(block, tx_hash).await? {
+        Some(events) => Ok(Some(TxStatus::Included {
+            block: block.hash(),
+            success: !events.has::<ExtrinsicFailed>()?,
+        })),
+        None => Ok(None),
+    }
+}
+
+/// Events of the extrinsic hashing to `tx_hash`, if `block` contains it.
+pub(crate) async fn find_extrinsic<C: Config>(
+    block: &Block<C, OnlineClient<C>>,
+    tx_hash: H256,
+) -> Result<Option<ExtrinsicEvents<C>>, Error> {
+    for extrinsic in block.extrinsics().await?.iter() {
+        let extrinsic = extrinsic?;
+        if BlakeTwo256::hash(extrinsic.bytes()) == tx_hash {
+            return Ok(Some(extrinsic.events().await?));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,244 @@
+//! Connections that live outside the global `init_api` client.
+
+use crate::core::Error;
+use crate::{connect_any, try_connect, ChainInfo, Connection, ConnectionTasks, Network, H256};
+use std::any::Any;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use subxt::backend::rpc::RpcClient;
+use subxt::config::substrate::{BlakeTwo256, SubstrateHeader};
+use subxt::config::DefaultExtrinsicParams;
+use subxt::{Config, OnlineClient, PolkadotConfig};
+use tokio::task::JoinHandle;
+
+tokio::task_local! {
+    static SCOPED_CLIENT: Connection;
+    static SCOPED_CUSTOM_CLIENT: CustomScope;
+}
+
+/// The `OnlineClient` of a [`CessClient::scope`] over a custom [`Config`], with its RPC client.
+#[derive(Clone)]
+struct CustomScope {
+    api: Arc<dyn Any + Send + Sync>,
+    rpc: Option<RpcClient>,
+}
+
+/// Configs the `Chain`, `Query` and `Call` traits run on: substrate hashes, headers and signed
+/// extensions, with any account, address and signature types, e.g. for ECDSA signed accounts.
+pub trait CessConfig:
+    Config<
+    Hash = H256,
+    Hasher = BlakeTwo256,
+    Header = SubstrateHeader<u32, BlakeTwo256>,
+    ExtrinsicParams = DefaultExtrinsicParams<Self>,
+>
+{
+}
+
+impl<C> CessConfig for C where
+    C: Config<
+        Hash = H256,
+        Hasher = BlakeTwo256,
+        Header = SubstrateHeader<u32, BlakeTwo256>,
+        ExtrinsicParams = DefaultExtrinsicParams<C>,
+    >
+{
+}
+
+/// The client of the innermost [`CessClient::scope`] the current task is running in, if any.
+pub(crate) fn scoped_client() -> Option<OnlineClient<PolkadotConfig>> {
+    scoped_connection().map(|connection| connection.api)
+}
+
+/// The client of the innermost [`CessClient::scope`] over `C` the current task is running in.
+pub(crate) fn scoped_client_for<C: Config>() -> Option<OnlineClient<C>> {
+    scoped_connection_for::<C>().map(|(api, _)| api)
+}
+
+/// [`scoped_client_for`] with its RPC client, `None` for clients built outside the SDK.
+pub(crate) fn scoped_connection_for<C: Config>() -> Option<(OnlineClient<C>, Option<RpcClient>)> {
+    let custom = SCOPED_CUSTOM_CLIENT
+        .try_with(|scope| {
+            let api = scope.api.downcast_ref::<OnlineClient<C>>()?.clone();
+            Some((api, scope.rpc.clone()))
+        })
+        .ok()
+        .flatten();
+    if custom.is_some() {
+        return custom;
+    }
+    let connection = scoped_connection()?;
+    let api: Box<dyn Any> = Box::new(connection.api);
+    let api = api.downcast::<OnlineClient<C>>().ok()?;
+    Some((*api, connection.rpc))
+}
+
+/// [`scoped_client`] with its RPC client.
+pub(crate) fn scoped_connection() -> Option<Connection> {
+    SCOPED_CLIENT.try_with(|connection| connection.clone()).ok()
+}
+
+/// `tokio::spawn` that keeps the [`CessClient::scope`] of the calling task, if any.
+pub fn spawn<F>(f: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let custom = SCOPED_CUSTOM_CLIENT.try_with(|scope| scope.clone()).ok();
+    match (scoped_connection(), custom) {
+        (Some(connection), Some(scope)) => {
+            tokio::spawn(SCOPED_CLIENT.scope(connection, SCOPED_CUSTOM_CLIENT.scope(scope, f)))
+        }
+        (Some(connection), None) => tokio::spawn(SCOPED_CLIENT.scope(connection, f)),
+        (None, Some(scope)) => tokio::spawn(SCOPED_CUSTOM_CLIENT.scope(scope, f)),
+        (None, None) => tokio::spawn(f),
+    }
+}
+
+/// An RPC connection owned by the caller, independent of the global client used by `init_api`.
+///
+/// Queries and transactions run inside [`CessClient::scope`] use this connection, so several
+/// networks can be used from one process:
+///
+/// ```no_run
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// use cess_rust_sdk::chain::file_bank::query::StorageQuery;
+/// use cess_rust_sdk::client::CessClient;
+///
+/// let testnet = CessClient::connect("wss://testnet-rpc.cess.cloud:443/ws/").await?;
+/// let devnet = CessClient::connect("ws://127.0.0.1:9944").await?;
+/// let fid = "48609e0f30979f40f838deeed66da835086f787fe6dae2f8dbe364afd28793b6";
+/// let a = testnet.scope(StorageQuery::file(fid, None)).await?;
+/// let b = devnet.scope(StorageQuery::file(fid, None)).await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Clients of a custom [`Config`] are created with [`CessClient::connect_with_config`]. Inside
+/// their `scope`, [`crate::init_api_for`] and the `Chain`, `Query` and `Call` traits over that
+/// config resolve to them, while the module helpers keep using `PolkadotConfig`.
+pub struct CessClient<C: Config = PolkadotConfig> {
+    url: Option<String>,
+    api: OnlineClient<C>,
+    /// The RPC client `api` runs on, for the node methods `OnlineClient` does not expose.
+    rpc: Option<RpcClient>,
+    tasks: Option<Arc<ConnectionTasks>>,
+}
+
+impl<C: Config> Clone for CessClient<C> {
+    fn clone(&self) -> Self {
+        Self {
+            url: self.url.clone(),
+            api: self.api.clone(),
+            rpc: self.rpc.clone(),
+            tasks: self.tasks.clone(),
+        }
+    }
+}
+
+impl<C: Config> CessClient<C> {
+    /// Connects with a custom `Config`, without the retrying RPC client used by `connect`.
+    /// Plain `ws://` urls are accepted, e.g. for a local dev node.
+    pub async fn connect_with_config(url: &str) -> Result<Self, Error> {
+        let (endpoint, tunnel) = crate::core::proxy::rpc_endpoint(url).await?;
+        let tasks = Arc::new(ConnectionTasks(tunnel.into_iter().collect()));
+        let rpc = RpcClient::from_insecure_url(&endpoint).await?;
+        let api = OnlineClient::<C>::from_rpc_client(rpc.clone()).await?;
+        Ok(Self {
+            url: Some(url.to_string()),
+            api,
+            rpc: Some(rpc),
+            tasks: Some(tasks),
+        })
+    }
+
+    /// Wraps a client built elsewhere. Helpers needing node RPC methods, such as
+    /// [`crate::ping`], fail inside its scope.
+    pub fn from_client(api: OnlineClient<C>) -> Self {
+        Self {
+            url: None,
+            api,
+            rpc: None,
+            tasks: None,
+        }
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    pub fn api(&self) -> OnlineClient<C> {
+        self.api.clone()
+    }
+
+    /// Runs `f` with every `init_api` call inside it, including the ones made by `Query` and
+    /// `Call` implementations, resolving to this client. For a custom `Config`, only
+    /// [`crate::init_api_for`] over that config does.
+    ///
+    /// The scope is task-local: work started with `tokio::spawn` inside `f` uses the global
+    /// client. Start it with [`spawn`] to keep the scope.
+    pub async fn scope<F: Future>(&self, f: F) -> F::Output {
+        let api: Box<dyn Any> = Box::new(self.api.clone());
+        match api.downcast::<OnlineClient<PolkadotConfig>>() {
+            Ok(api) => {
+                let connection = Connection {
+                    api: *api,
+                    rpc: self.rpc.clone(),
+                    tasks: self.tasks.clone(),
+                };
+                SCOPED_CLIENT.scope(connection, f).await
+            }
+            Err(_) => {
+                let scope = CustomScope {
+                    api: Arc::new(self.api.clone()),
+                    rpc: self.rpc.clone(),
+                };
+                SCOPED_CUSTOM_CLIENT.scope(scope, f).await
+            }
+        }
+    }
+}
+
+impl CessClient {
+    pub async fn connect(url: &str) -> Result<Self, Error> {
+        let connection = try_connect(Some(url)).await?;
+        crate::protocol::verify_once(&connection.api)?;
+        Ok(Self::from_connection(connection, url.to_string()))
+    }
+
+    /// Connects to the first endpoint of `network` that answers.
+    pub async fn connect_network(network: Network) -> Result<Self, Error> {
+        let urls: Vec<String> = network
+            .rpc_urls()
+            .iter()
+            .map(|url| url.to_string())
+            .collect();
+        let (connection, url) = connect_any(&urls).await?;
+        crate::protocol::verify_once(&connection.api)?;
+        Ok(Self::from_connection(connection, url))
+    }
+
+    fn from_connection(connection: Connection, url: String) -> Self {
+        Self {
+            url: Some(url),
+            api: connection.api,
+            rpc: connection.rpc,
+            tasks: connection.tasks,
+        }
+    }
+
+    /// [`crate::chain_info`] of this client, queried over its own RPC connection.
+    pub async fn chain_info(&self) -> Result<ChainInfo, Error> {
+        let info = self.scope(crate::chain_info()).await?;
+        Ok(ChainInfo {
+            rpc_url: self.url.clone(),
+            ..info
+        })
+    }
+
+    /// [`crate::ping`] over the RPC connection of this client.
+    pub async fn ping(&self) -> Result<Duration, Error> {
+        self.scope(crate::ping()).await
+    }
+}
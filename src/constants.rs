@@ -4,11 +4,20 @@ use std::time::Duration;
 pub const TOKEN_PRECISION_CESS: &str = "000000000000";
 
 pub const BLOCK_INTERVAL: Duration = Duration::from_secs(6);
+pub const ONE_DAY_BLOCKS: u32 = 14400;
 
 pub const MAX_SUBMITED_IDLE_FILE_META: usize = 30;
 
 pub const MIN_BUCKET_NAME_LENGTH: usize = 3;
 pub const MAX_BUCKET_NAME_LENGHT: usize = 63;
+pub const MAX_TERRITORY_NAME_LENGTH: usize = 63;
+pub const MAX_FILE_NAME_LENGTH: usize = 63;
+
+pub const MAX_SEGMENT_COUNT: usize = 1000;
+pub const MAX_FRAGMENT_COUNT: usize = 12;
+pub const MIN_TERRITORY_DAYS: u32 = 30;
+// Default limit of file deletions in one batch extrinsic
+pub const MAX_BATCH_DELETIONS: usize = 100;
 
 pub const SIZE_1_KI_B: u32 = 1024;
 pub const SIZE_1_MI_B: u32 = 1024 * SIZE_1_KI_B;
@@ -1,3 +1,10 @@
+pub mod proxy;
+pub mod rng;
+
+use crate::gateway::error::RequestError;
+use subxt::error::{DispatchError, RpcError, StorageError};
+use subxt::ext::codec;
+use subxt::utils::H256;
 use subxt::Error as SubxtError;
 
 pub trait ApiProvider {
@@ -48,10 +55,171 @@ pub enum Error {
     Custom(String),
 
     #[error(transparent)]
-    Subxt(#[from] SubxtError),
+    Subxt(SubxtError),
+
+    /// The RPC connection or a call over it failed.
+    #[error("RPC error: {0}")]
+    Rpc(#[source] RpcError),
+
+    /// A storage address or key could not be built or read.
+    #[error("Storage error: {0}")]
+    Storage(#[source] StorageError),
+
+    /// SCALE encoded bytes could not be decoded.
+    #[error("Codec error: {0}")]
+    Codec(#[from] codec::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// An argument was rejected before anything was sent, e.g. a malformed key or url.
+    #[error("{0}")]
+    InvalidInput(String),
+
+    /// A gateway request failed, with the id it was sent with. [`GatewayError::Api`] carries the
+    /// raw status and body of responses that could not be classified.
+    ///
+    /// [`GatewayError::Api`]: crate::gateway::error::GatewayError::Api
+    #[error(transparent)]
+    Gateway(#[from] RequestError),
 
     #[error(transparent)]
     Application(#[from] Box<dyn std::error::Error + Send + Sync>),
+
+    /// The connection dropped after submission and the outcome of the extrinsic is unknown.
+    #[error("Extrinsic {0:?} was submitted but its inclusion could not be confirmed")]
+    SubmittedButUnconfirmed(H256),
+
+    /// A call of a `batch_all` failed, reverting the whole batch.
+    #[error("Batch call {index} failed: {error}")]
+    BatchItemFailed { index: usize, error: String },
+
+    /// The connected chain is not the one a transaction was bound to with `TxOptions`.
+    #[error(
+        "Connected to network with genesis {connected:?}, transaction is bound to {expected:?}"
+    )]
+    WrongNetwork { expected: H256, connected: H256 },
+
+    /// A file needs more segments than the chain accepts for one file.
+    #[error("File of {size} bytes needs {segments} segments, limit {max_segments} ({max} bytes)")]
+    FileTooLarge {
+        size: u64,
+        max: u64,
+        segments: u64,
+        max_segments: u32,
+    },
+
+    /// The account has no territory of this name.
+    #[error("Territory {name} not found")]
+    TerritoryNotFound { name: String },
+
+    /// A territory has less space left than a file needs, see
+    /// [`crate::gateway::file::preflight`].
+    #[error("Territory {name} has {remaining} bytes left, the file needs {required}")]
+    InsufficientTerritorySpace {
+        name: String,
+        remaining: u128,
+        required: u128,
+    },
+
+    /// A file or fragment hash is not a 64 character hex string.
+    #[error("Invalid hash length: expected {expected} hex characters, got {got}")]
+    InvalidHashLength { expected: usize, got: usize },
+
+    /// A decoded chain value violates an invariant, see [`crate::chain::sanity`].
+    #[error("Implausible chain value {field} = {value}, expected {invariant}")]
+    Insane {
+        field: &'static str,
+        value: String,
+        invariant: &'static str,
+    },
+
+    /// The global client was replaced while a stream created from it was still being read.
+    #[error("The RPC client was replaced, rebuild the stream")]
+    ClientReplaced,
+
+    /// The connected runtime disagrees with [`crate::protocol::ASSUMPTIONS`].
+    #[error("Protocol assumption {field} is {assumed} in the SDK but {chain} on chain")]
+    ProtocolMismatch {
+        field: &'static str,
+        assumed: String,
+        chain: String,
+    },
+
+    /// A pallet rejected the call, e.g. `FileBank::FileNonExistent`.
+    #[error("{pallet}::{error}: {docs}")]
+    Pallet {
+        pallet: String,
+        error: String,
+        docs: String,
+    },
+
+    /// The territory is frozen. `deadline`, when known, is the end of its lease, the block it
+    /// froze at. It stays frozen until reactivated with `reactivate_territory`, and expires once
+    /// the pallet's `FrozenDays` have passed.
+    #[error("Territory is frozen{}", frozen_since(.deadline))]
+    TerritoryFrozen { deadline: Option<u32> },
+
+    /// The territory is on consignment, cancel it with `cancel_consignment` to use the
+    /// territory again.
+    #[error("Territory is on consignment")]
+    TerritoryOnConsignment,
+
+    /// The consignment is locked by a purchase in progress.
+    #[error("Consignment is locked by a purchase in progress")]
+    ConsignmentLocked,
+
+    /// The signer cannot pay for a transaction, checked before submitting it.
+    #[error("Insufficient balance: {needed} needed, {available} available")]
+    InsufficientBalance { needed: u128, available: u128 },
+}
+
+fn frozen_since(deadline: &Option<u32>) -> String {
+    deadline
+        .map(|block| format!(" since its lease ended at block #{}", block))
+        .unwrap_or_default()
+}
+
+/// Message carried by the `subxt::Error::Other` that SDK streams yield once their client is
+/// replaced; converts back into [`Error::ClientReplaced`].
+pub(crate) const CLIENT_REPLACED: &str = "cess-rust-sdk: client replaced";
+
+impl From<SubxtError> for Error {
+    fn from(e: SubxtError) -> Self {
+        match e {
+            SubxtError::Other(msg) if msg == CLIENT_REPLACED => Error::ClientReplaced,
+            SubxtError::Runtime(e) => e.into(),
+            SubxtError::Rpc(e) => Error::Rpc(e),
+            SubxtError::Storage(e) => Error::Storage(e),
+            SubxtError::Codec(e) => Error::Codec(e),
+            SubxtError::Io(e) => Error::Io(e),
+            e => Error::Subxt(e),
+        }
+    }
+}
+
+impl From<DispatchError> for Error {
+    /// Names module errors after their pallet and variant in the metadata, when it has them.
+    fn from(e: DispatchError) -> Self {
+        if let DispatchError::Module(module) = &e {
+            if let Ok(details) = module.details() {
+                match (details.pallet.name(), details.variant.name.as_str()) {
+                    ("StorageHandler", "LeaseFreeze") => {
+                        return Error::TerritoryFrozen { deadline: None }
+                    }
+                    ("StorageHandler", "OnConsignment") => return Error::TerritoryOnConsignment,
+                    ("StorageHandler", "ConsignmentLocked") => return Error::ConsignmentLocked,
+                    _ => {}
+                }
+                return Error::Pallet {
+                    pallet: details.pallet.name().to_string(),
+                    error: details.variant.name.clone(),
+                    docs: details.variant.docs.join(" "),
+                };
+            }
+        }
+        Error::Subxt(SubxtError::Runtime(e))
+    }
 }
 
 impl From<&str> for Error {
@@ -0,0 +1,401 @@
+//! Proxying of the RPC websocket.
+//!
+//! The websocket client has no proxy support, so a proxied connection is pointed at a local
+//! listener instead. Every connection accepted there is tunnelled to the node through the proxy,
+//! with HTTP `CONNECT` or SOCKS5, and TLS to the node is done on the tunnel. Reconnects of the
+//! RPC client open a fresh tunnel the same way.
+
+use crate::core::Error;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use log::warn;
+use once_cell::sync::Lazy;
+use reqwest::Url;
+use std::env;
+use std::net::IpAddr;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::AbortHandle;
+use tokio_native_tls::{native_tls, TlsConnector};
+
+/// Longest request or response head read while setting up a tunnel.
+const MAX_HEAD_LEN: usize = 16 * 1024;
+
+static RPC_PROXY: Lazy<RwLock<Option<ProxyConfig>>> = Lazy::new(|| RwLock::new(None));
+
+/// An `http://` or `socks5://` proxy for RPC connections, see [`set_rpc_proxy`].
+///
+/// ```no_run
+/// # fn run() -> Result<(), cess_rust_sdk::core::Error> {
+/// use cess_rust_sdk::core::proxy::{set_rpc_proxy, ProxyConfig};
+///
+/// let proxy = ProxyConfig::new("socks5://proxy.internal:1080")?
+///     .auth("user", "password")
+///     .no_proxy([".internal.example.com"]);
+/// set_rpc_proxy(proxy);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    url: String,
+    credentials: Option<(String, String)>,
+    no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    pub fn new(url: &str) -> Result<Self, Error> {
+        let parsed = Url::parse(url)
+            .map_err(|e| Error::InvalidInput(format!("Invalid proxy url {}: {}", url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "socks5" | "socks5h") || parsed.host().is_none() {
+            return Err(Error::InvalidInput(format!(
+                "RPC connections can only be proxied through http:// or socks5:// proxies, got {}",
+                url
+            )));
+        }
+        Ok(Self {
+            url: url.to_string(),
+            credentials: None,
+            no_proxy: Vec::new(),
+        })
+    }
+
+    /// Credentials for the proxy, sent as basic auth to HTTP proxies and as username/password
+    /// authentication to SOCKS5 proxies. Credentials in the url are used when none are set.
+    pub fn auth(mut self, username: &str, password: &str) -> Self {
+        self.credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Hosts or domains (`.example.com`) reached without the proxy. `*` matches every host.
+    pub fn no_proxy<S: Into<String>>(mut self, destinations: impl IntoIterator<Item = S>) -> Self {
+        self.no_proxy
+            .extend(destinations.into_iter().map(Into::into));
+        self
+    }
+
+    /// The proxy the environment sets for `scheme`: `HTTPS_PROXY` for `wss`, `HTTP_PROXY` for
+    /// `ws`, `ALL_PROXY` for both, each also read in lower case, with `NO_PROXY` as exceptions.
+    /// Unusable values are skipped with a warning.
+    pub fn from_env(scheme: &str) -> Option<Self> {
+        fn var(key: &str) -> Option<String> {
+            env::var(key)
+                .or_else(|_| env::var(key.to_lowercase()))
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+        }
+
+        let specific = match scheme {
+            "wss" | "https" => var("HTTPS_PROXY"),
+            _ => var("HTTP_PROXY"),
+        };
+        let url = specific.or_else(|| var("ALL_PROXY"))?;
+        match Self::new(url.trim()) {
+            Ok(proxy) => {
+                let no_proxy = var("NO_PROXY").unwrap_or_default();
+                Some(proxy.no_proxy(no_proxy.split(',').map(str::trim).filter(|s| !s.is_empty())))
+            }
+            Err(e) => {
+                warn!(target: "SDK", "Ignoring proxy from the environment: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Whether `host` is reached directly. Loopback addresses always are, so a local node keeps
+    /// working with a proxy in the environment.
+    fn bypasses(&self, host: &str) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.eq_ignore_ascii_case("localhost")
+            || host.parse::<IpAddr>().map_or(false, |ip| ip.is_loopback())
+        {
+            return true;
+        }
+        self.no_proxy.iter().any(|entry| {
+            let entry = entry.trim();
+            if entry == "*" {
+                return true;
+            }
+            let domain = entry.trim_start_matches('.');
+            host.eq_ignore_ascii_case(domain)
+                || (host.len() > domain.len()
+                    && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+                    && host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+        })
+    }
+
+    fn credentials(&self, url: &Url) -> Option<(String, String)> {
+        self.credentials.clone().or_else(|| {
+            (!url.username().is_empty()).then(|| {
+                (
+                    url.username().to_string(),
+                    url.password().unwrap_or_default().to_string(),
+                )
+            })
+        })
+    }
+
+    /// Opens a TCP stream to `host:port` through the proxy.
+    async fn connect(&self, host: &str, port: u16) -> Result<TcpStream, Error> {
+        let url = Url::parse(&self.url)
+            .map_err(|e| Error::InvalidInput(format!("Invalid proxy url {}: {}", self.url, e)))?;
+        let proxy_host = url
+            .host_str()
+            .ok_or_else(|| Error::InvalidInput(format!("Proxy url {} has no host", self.url)))?;
+        let proxy_port = url.port_or_known_default().unwrap_or(1080);
+        let mut stream = TcpStream::connect((unbracket(proxy_host), proxy_port)).await?;
+        let credentials = self.credentials(&url);
+        if url.scheme() == "http" {
+            http_connect(&mut stream, host, port, credentials).await?;
+        } else {
+            socks5_connect(&mut stream, host, port, credentials).await?;
+        }
+        Ok(stream)
+    }
+}
+
+/// Routes RPC connections opened from now on through `proxy`, instead of the proxy of the
+/// environment. Gateway traffic is proxied with [`crate::gateway::http::HttpConfig::proxy`].
+pub fn set_rpc_proxy(proxy: ProxyConfig) {
+    *RPC_PROXY.write().unwrap_or_else(|e| e.into_inner()) = Some(proxy);
+}
+
+/// Undoes [`set_rpc_proxy`], leaving RPC connections to the proxy of the environment.
+pub fn clear_rpc_proxy() {
+    *RPC_PROXY.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The url the RPC client should connect to to reach `url`: `url` itself when no proxy applies,
+/// otherwise the local end of a tunnel, with the task accepting connections on it.
+pub(crate) async fn rpc_endpoint(url: &str) -> Result<(String, Option<AbortHandle>), Error> {
+    let target = Url::parse(url)
+        .map_err(|e| Error::InvalidInput(format!("Invalid RPC url {}: {}", url, e)))?;
+    let host = match target.host_str() {
+        Some(host) => host.to_string(),
+        None => return Ok((url.to_string(), None)),
+    };
+    let configured = RPC_PROXY.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let proxy = match configured.or_else(|| ProxyConfig::from_env(target.scheme())) {
+        Some(proxy) if !proxy.bypasses(&host) => proxy,
+        _ => return Ok((url.to_string(), None)),
+    };
+
+    let tls = match target.scheme() {
+        "wss" => true,
+        "ws" => false,
+        scheme => {
+            return Err(Error::InvalidInput(format!(
+                "Only ws:// and wss:// RPC urls can be proxied, got {}",
+                scheme
+            )))
+        }
+    };
+    let port = target
+        .port_or_known_default()
+        .ok_or_else(|| Error::InvalidInput(format!("RPC url {} has no port", url)))?;
+    let authority = match target.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.clone(),
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let mut local = target.clone();
+    if local.set_scheme("ws").is_err()
+        || local.set_ip_host(IpAddr::from([127, 0, 0, 1])).is_err()
+        || local.set_port(Some(listener.local_addr()?.port())).is_err()
+    {
+        return Err(Error::Custom(format!(
+            "Failed to build the tunnel url for {}",
+            url
+        )));
+    }
+
+    let task = tokio::spawn(async move {
+        loop {
+            let socket = match listener.accept().await {
+                Ok((socket, _)) => socket,
+                Err(e) => {
+                    warn!(target: "SDK", "RPC proxy tunnel stopped accepting: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            let (proxy, host, authority) = (proxy.clone(), host.clone(), authority.clone());
+            tokio::spawn(async move {
+                if let Err(e) = tunnel(socket, &proxy, &host, port, &authority, tls).await {
+                    warn!(target: "SDK", "RPC proxy tunnel to {} failed: {}", authority, e);
+                }
+            });
+        }
+    });
+
+    Ok((local.to_string(), Some(task.abort_handle())))
+}
+
+/// Carries one websocket connection of the RPC client to the node.
+async fn tunnel(
+    mut local: TcpStream,
+    proxy: &ProxyConfig,
+    host: &str,
+    port: u16,
+    authority: &str,
+    tls: bool,
+) -> Result<(), Error> {
+    // The handshake names the local listener as host, the node may route on it.
+    let head = with_host(&read_head(&mut local).await?, authority)?;
+    let upstream = proxy.connect(unbracket(host), port).await?;
+    if tls {
+        let connector = native_tls::TlsConnector::new()
+            .map_err(|e| Error::Custom(format!("Failed to set up TLS: {}", e)))?;
+        let upstream = TlsConnector::from(connector)
+            .connect(unbracket(host), upstream)
+            .await
+            .map_err(|e| Error::Custom(format!("TLS handshake with {} failed: {}", host, e)))?;
+        relay(local, upstream, &head).await
+    } else {
+        relay(local, upstream, &head).await
+    }
+}
+
+async fn relay<S>(mut local: TcpStream, mut upstream: S, head: &[u8]) -> Result<(), Error>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    upstream.write_all(head).await?;
+    tokio::io::copy_bidirectional(&mut local, &mut upstream).await?;
+    Ok(())
+}
+
+async fn http_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    credentials: Option<(String, String)>,
+) -> Result<(), Error> {
+    let target = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => format!("[{}]:{}", host, port),
+        _ => format!("{}:{}", host, port),
+    };
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    if let Some((username, password)) = credentials {
+        let token = STANDARD.encode(format!("{}:{}", username, password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    let response = read_head(stream).await?;
+    let status_line = String::from_utf8_lossy(&response)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    match status_line.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(Error::Custom(format!(
+            "Proxy refused to connect to {}: {}",
+            target, status_line
+        ))),
+    }
+}
+
+async fn socks5_connect(
+    stream: &mut TcpStream,
+    host: &str,
+    port: u16,
+    credentials: Option<(String, String)>,
+) -> Result<(), Error> {
+    let refused =
+        |reason: &str| Error::Custom(format!("SOCKS5 proxy refused {}: {}", host, reason));
+
+    let greeting: &[u8] = match credentials {
+        Some(_) => &[5, 2, 0, 2],
+        None => &[5, 1, 0],
+    };
+    stream.write_all(greeting).await?;
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).await?;
+    match (choice[1], &credentials) {
+        (0, _) => {}
+        (2, Some((username, password))) => {
+            if username.len() > 255 || password.len() > 255 {
+                return Err(Error::InvalidInput(
+                    "SOCKS5 credentials are limited to 255 bytes".to_string(),
+                ));
+            }
+            let mut auth = vec![1, username.len() as u8];
+            auth.extend_from_slice(username.as_bytes());
+            auth.push(password.len() as u8);
+            auth.extend_from_slice(password.as_bytes());
+            stream.write_all(&auth).await?;
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await?;
+            if status[1] != 0 {
+                return Err(refused("authentication failed"));
+            }
+        }
+        _ => return Err(refused("no acceptable authentication method")),
+    }
+
+    if host.len() > 255 {
+        return Err(Error::InvalidInput(format!(
+            "Host name {} is too long",
+            host
+        )));
+    }
+    let mut request = vec![5, 1, 0, 3, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0 {
+        return Err(refused(&format!("reply code {}", reply[1])));
+    }
+    let address_len = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => stream.read_u8().await? as usize,
+        kind => return Err(refused(&format!("unknown address type {}", kind))),
+    };
+    let mut bound = vec![0u8; address_len + 2];
+    stream.read_exact(&mut bound).await?;
+    Ok(())
+}
+
+/// Reads up to and including the blank line ending an HTTP head, one byte at a time so nothing
+/// sent after it is consumed.
+async fn read_head<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Vec<u8>, Error> {
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() >= MAX_HEAD_LEN {
+            return Err(Error::Custom("HTTP head too long".to_string()));
+        }
+        head.push(stream.read_u8().await?);
+    }
+    Ok(head)
+}
+
+/// `head` with its `Host` header replaced by `authority`.
+fn with_host(head: &[u8], authority: &str) -> Result<Vec<u8>, Error> {
+    let head = std::str::from_utf8(head)
+        .map_err(|_| Error::Custom("Websocket handshake is not valid UTF-8".to_string()))?;
+    let lines: Vec<String> = head
+        .trim_end_matches("\r\n")
+        .split("\r\n")
+        .map(|line| match line.split_once(':') {
+            Some((name, _)) if name.trim().eq_ignore_ascii_case("host") => {
+                format!("Host: {}", authority)
+            }
+            _ => line.to_string(),
+        })
+        .collect();
+    Ok(format!("{}\r\n\r\n", lines.join("\r\n")).into_bytes())
+}
+
+fn unbracket(host: &str) -> &str {
+    host.trim_start_matches('[').trim_end_matches(']')
+}
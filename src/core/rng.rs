@@ -0,0 +1,129 @@
+//! Random source of the SDK, e.g. for the message codes signed for the gateway.
+//!
+//! [`RngProvider`] draws from `OsRng`. With the `testing` feature, [`SeededRng::install`] makes
+//! the current thread draw from a seeded generator until the returned guard is dropped, so runs
+//! that record HTTP traffic can be replayed.
+//!
+//! The seed is thread-local: it does not follow work spawned onto other threads, e.g. tasks
+//! `tokio::spawn` hands to another worker of a multi-threaded runtime, which keep drawing from
+//! `OsRng`. Tests relying on it should run on a current-thread runtime or draw on the thread
+//! that installed the seed.
+
+use rand::rngs::OsRng;
+#[cfg(not(feature = "testing"))]
+use rand::CryptoRng;
+use rand::RngCore;
+#[cfg(feature = "testing")]
+use {
+    rand::{rngs::StdRng, SeedableRng},
+    std::cell::RefCell,
+    std::marker::PhantomData,
+};
+
+#[cfg(feature = "testing")]
+thread_local! {
+    static SEEDED: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// The generator SDK code draws random values from.
+///
+/// It only implements `CryptoRng` without the `testing` feature, so code that must not be
+/// seeded can require `CryptoRng` and fails to compile in builds where it could be.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RngProvider;
+
+impl RngCore for RngProvider {
+    fn next_u32(&mut self) -> u32 {
+        draw(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        draw(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        draw(|rng| rng.fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        draw(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+#[cfg(not(feature = "testing"))]
+impl CryptoRng for RngProvider {}
+
+/// Bound of generators behind security-sensitive values, e.g. message codes signed for the
+/// gateway. It requires `CryptoRng` outside the `testing` feature.
+#[cfg(not(feature = "testing"))]
+pub trait SecureRng: RngCore + CryptoRng {}
+
+#[cfg(not(feature = "testing"))]
+impl<R: RngCore + CryptoRng> SecureRng for R {}
+
+/// Bound of generators behind security-sensitive values. With the `testing` feature it also
+/// accepts the seeded [`RngProvider`].
+#[cfg(feature = "testing")]
+pub trait SecureRng: RngCore {}
+
+#[cfg(feature = "testing")]
+impl<R: RngCore> SecureRng for R {}
+
+/// The generator of security-sensitive values, checked against [`SecureRng`] at compile time.
+pub(crate) fn secure_rng() -> impl SecureRng {
+    RngProvider
+}
+
+#[cfg(feature = "testing")]
+fn draw<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    SEEDED.with(|seeded| match seeded.borrow_mut().as_mut() {
+        Some(rng) => f(rng),
+        None => f(&mut OsRng),
+    })
+}
+
+#[cfg(not(feature = "testing"))]
+fn draw<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    f(&mut OsRng)
+}
+
+/// Guard of a seeded generator installed on the current thread, see [`SeededRng::install`].
+///
+/// ```ignore
+/// use cess_rust_sdk::core::rng::SeededRng;
+/// use cess_rust_sdk::utils::str::get_random_code;
+///
+/// let first = {
+///     let _rng = SeededRng::install(7);
+///     get_random_code(16).unwrap()
+/// };
+/// let _rng = SeededRng::install(7);
+/// assert_eq!(get_random_code(16).unwrap(), first);
+/// ```
+#[cfg(feature = "testing")]
+pub struct SeededRng {
+    previous: Option<StdRng>,
+    // Bound to the thread it was installed on.
+    _thread: PhantomData<*const ()>,
+}
+
+#[cfg(feature = "testing")]
+impl SeededRng {
+    /// Makes [`RngProvider`] on this thread draw from a generator seeded with `seed` until the
+    /// guard is dropped, when the previous source is restored. Other threads are unaffected.
+    pub fn install(seed: u64) -> Self {
+        let previous = SEEDED.with(|seeded| seeded.replace(Some(StdRng::seed_from_u64(seed))));
+        Self {
+            previous,
+            _thread: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl Drop for SeededRng {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        SEEDED.with(|seeded| *seeded.borrow_mut() = previous);
+    }
+}
@@ -1,3 +1,7 @@
+pub mod canary;
+pub mod error;
 pub mod file;
+pub mod http;
 pub mod object;
+pub mod progress;
 pub mod upload_response;
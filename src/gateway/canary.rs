@@ -0,0 +1,274 @@
+//! End to end canary of a gateway: upload a small unique file, wait until it is stored on chain,
+//! download it back, compare, then delete it.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), cess_rust_sdk::core::Error> {
+//! use cess_rust_sdk::gateway::canary::{self, CanaryOptions};
+//!
+//! let options = CanaryOptions::default().on_phase(|result| {
+//!     println!("{:?} took {} ms", result.phase, result.elapsed_ms);
+//! });
+//! let report = canary::run("https://deoss.example.com", "<mnemonic>", "canary", options).await?;
+//! println!("{}", serde_json::to_string(&report).unwrap());
+//! # Ok(())
+//! # }
+//! ```
+
+use super::file::{download, upload};
+use crate::chain::blocks::BlockClock;
+use crate::chain::file_bank::{query::StorageQuery, transaction::StorageTransaction};
+use crate::core::Error;
+use crate::health::CheckStatus;
+use crate::utils::str::get_random_code;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
+use tokio::fs;
+
+/// Start of the name of every canary file.
+pub const CANARY_PREFIX: &str = "cess-canary-";
+
+type PhaseHook = Arc<dyn Fn(&PhaseResult) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CanaryPhase {
+    Upload,
+    /// Waiting for the uploaded file to be stored on chain.
+    Activation,
+    Download,
+    Verify,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseResult {
+    pub phase: CanaryPhase,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub elapsed_ms: u128,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CanaryReport {
+    pub file_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fid: Option<String>,
+    pub phases: Vec<PhaseResult>,
+    /// First phase that failed, `None` when the canary succeeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_phase: Option<CanaryPhase>,
+    pub total_ms: u128,
+}
+
+impl CanaryReport {
+    pub fn succeeded(&self) -> bool {
+        self.failed_phase.is_none()
+    }
+}
+
+/// Timeouts of each phase, and the hook told about every phase as it ends.
+#[derive(Clone)]
+pub struct CanaryOptions {
+    pub bucket: String,
+    pub upload_timeout: Duration,
+    /// How long to wait for the file to be stored, checked at every finalized block.
+    pub activation_timeout: Duration,
+    pub download_timeout: Duration,
+    pub delete_timeout: Duration,
+    on_phase: Option<PhaseHook>,
+}
+
+impl Default for CanaryOptions {
+    fn default() -> Self {
+        Self {
+            bucket: "canary".to_string(),
+            upload_timeout: Duration::from_secs(30),
+            activation_timeout: Duration::from_secs(180),
+            download_timeout: Duration::from_secs(30),
+            delete_timeout: Duration::from_secs(60),
+            on_phase: None,
+        }
+    }
+}
+
+impl CanaryOptions {
+    /// Calls `hook` with the result of every phase, e.g. to record latency metrics.
+    pub fn on_phase(mut self, hook: impl Fn(&PhaseResult) + Send + Sync + 'static) -> Self {
+        self.on_phase = Some(Arc::new(hook));
+        self
+    }
+}
+
+/// Runs the canary against `gateway_url` with the account of `mnemonic`, storing in `territory`.
+///
+/// Phase failures are reported in the returned [`CanaryReport`]; `Err` is only returned when
+/// the canary cannot start. The uploaded file is deleted whatever later phase failed. A file
+/// that was never stored in time cannot be deleted yet; its fid is in the report.
+pub async fn run(
+    gateway_url: &str,
+    mnemonic: &str,
+    territory: &str,
+    options: CanaryOptions,
+) -> Result<CanaryReport, Error> {
+    PairS::from_string(mnemonic, None)
+        .map_err(|e| Error::InvalidInput(format!("Invalid mnemonic: {:?}", e)))?;
+    let start = Instant::now();
+
+    let suffix = get_random_code(8).map_err(|e| Error::Custom(e.to_string()))?;
+    let millis = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let file_name = format!("{}{}-{}", CANARY_PREFIX, millis, suffix);
+    // Unique content, so the gateway cannot answer from an already stored copy.
+    let content = format!("{} {} {}\n", CANARY_PREFIX, millis, suffix).into_bytes();
+    let upload_path = std::env::temp_dir().join(&file_name);
+    let download_path = upload_path.with_extension("download");
+    fs::write(&upload_path, &content)
+        .await
+        .map_err(|e| Error::Custom(format!("Failed to write canary file: {}", e)))?;
+
+    let mut recorder = Recorder {
+        phases: Vec::new(),
+        hook: options.on_phase.clone(),
+    };
+
+    let upload_path_str = upload_path.to_string_lossy().into_owned();
+    let fid = recorder
+        .phase(CanaryPhase::Upload, options.upload_timeout, async {
+            upload(
+                gateway_url,
+                &upload_path_str,
+                &options.bucket,
+                territory,
+                mnemonic,
+            )
+            .await
+            .map(|response| response.fid)
+            .map_err(|e| e.to_string())
+        })
+        .await;
+
+    if let Some(fid) = &fid {
+        let stored = recorder
+            .phase(
+                CanaryPhase::Activation,
+                options.activation_timeout,
+                wait_for_activation(fid),
+            )
+            .await
+            .is_some();
+
+        // A file that never got stored cannot be deleted yet.
+        if stored {
+            let download_path_str = download_path.to_string_lossy().into_owned();
+            let downloaded = recorder
+                .phase(CanaryPhase::Download, options.download_timeout, async {
+                    download(gateway_url, fid, mnemonic, &download_path_str)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    fs::read(&download_path).await.map_err(|e| e.to_string())
+                })
+                .await;
+            if let Some(downloaded) = downloaded {
+                let verified = if downloaded == content {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "Downloaded {} bytes differ from the {} uploaded",
+                        downloaded.len(),
+                        content.len()
+                    ))
+                };
+                recorder.record(CanaryPhase::Verify, Instant::now(), verified);
+            }
+
+            recorder
+                .phase(CanaryPhase::Delete, options.delete_timeout, async {
+                    StorageTransaction::new(mnemonic)
+                        .delete_own_file(fid)
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .await;
+        }
+    }
+
+    fs::remove_file(&upload_path).await.ok();
+    fs::remove_file(&download_path).await.ok();
+
+    let failed_phase = recorder
+        .phases
+        .iter()
+        .find(|result| result.status == CheckStatus::Fail)
+        .map(|result| result.phase);
+    Ok(CanaryReport {
+        file_name,
+        fid,
+        phases: recorder.phases,
+        failed_phase,
+        total_ms: start.elapsed().as_millis(),
+    })
+}
+
+async fn wait_for_activation(fid: &str) -> Result<(), String> {
+    let mut clock = BlockClock::new().await;
+    loop {
+        match StorageQuery::file(fid, None).await {
+            Ok(Some(_)) => return Ok(()),
+            Ok(None) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+        clock.wait_next_block().await.map_err(|e| e.to_string())?;
+    }
+}
+
+struct Recorder {
+    phases: Vec<PhaseResult>,
+    hook: Option<PhaseHook>,
+}
+
+impl Recorder {
+    /// Runs `phase` within `timeout`, recording how it went. `None` when it failed.
+    async fn phase<T, F>(&mut self, phase: CanaryPhase, timeout: Duration, f: F) -> Option<T>
+    where
+        F: Future<Output = Result<T, String>>,
+    {
+        let start = Instant::now();
+        let result = match tokio::time::timeout(timeout, f).await {
+            Ok(result) => result,
+            Err(_) => Err(format!("Timed out after {:?}", timeout)),
+        };
+        self.record(phase, start, result)
+    }
+
+    /// Records `result` of `phase`, started at `start`, and tells the hook.
+    fn record<T>(
+        &mut self,
+        phase: CanaryPhase,
+        start: Instant,
+        result: Result<T, String>,
+    ) -> Option<T> {
+        let (value, status, detail) = match result {
+            Ok(value) => (Some(value), CheckStatus::Pass, None),
+            Err(e) => (None, CheckStatus::Fail, Some(e)),
+        };
+
+        let result = PhaseResult {
+            phase,
+            status,
+            detail,
+            elapsed_ms: start.elapsed().as_millis(),
+        };
+        if let Some(hook) = &self.hook {
+            hook(&result);
+        }
+        self.phases.push(result);
+        value
+    }
+}
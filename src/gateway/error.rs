@@ -0,0 +1,135 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Response, StatusCode};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// DeOSS failure, classified from the status code and error body.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GatewayError {
+    #[error("Territory expired, renew it before uploading")]
+    TerritoryExpired,
+
+    #[error("Not enough territory space, expand the territory or free space")]
+    InsufficientTerritorySpace,
+
+    #[error("Gateway authentication failed: {reason}")]
+    AuthFailed { reason: String },
+
+    #[error("File not found on the gateway")]
+    FidNotFound,
+
+    #[error("Rate limited by the gateway")]
+    RateLimited { retry_after: Option<Duration> },
+
+    #[error("Gateway returned {status}: {body}")]
+    Api { status: u16, body: String },
+
+    /// No complete response arrived, e.g. the connection was refused or dropped mid-body.
+    #[error("Gateway request failed: {reason}")]
+    Transport { reason: String },
+}
+
+/// A failed gateway request, with the [`REQUEST_ID_HEADER`] it was sent with when there was
+/// one. Quote the id to the gateway operator to find the request in their logs.
+///
+/// [`REQUEST_ID_HEADER`]: super::http::REQUEST_ID_HEADER
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{error}{}", request_id_suffix(.request_id))]
+pub struct RequestError {
+    pub request_id: Option<String>,
+    pub error: GatewayError,
+}
+
+fn request_id_suffix(request_id: &Option<String>) -> String {
+    request_id
+        .as_ref()
+        .map(|id| format!(" (request id {})", id))
+        .unwrap_or_default()
+}
+
+/// JSON error body returned by DeOSS, e.g. `{"code":403,"msg":"...","data":null}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GatewayErrorBody {
+    #[serde(default)]
+    pub code: Option<i64>,
+    #[serde(default, alias = "message", alias = "error")]
+    pub msg: Option<String>,
+}
+
+impl GatewayErrorBody {
+    /// Classifies a failed response. Plain text bodies are matched the same way as `msg`.
+    pub fn parse(status: u16, body: &str, retry_after: Option<Duration>) -> GatewayError {
+        let message = match serde_json::from_str::<GatewayErrorBody>(body) {
+            Ok(GatewayErrorBody { msg: Some(msg), .. }) => msg,
+            _ => body.trim().to_string(),
+        };
+        let lower = message.to_lowercase();
+        let mentions = |patterns: &[&str]| patterns.iter().any(|p| lower.contains(p));
+
+        if status == StatusCode::TOO_MANY_REQUESTS.as_u16()
+            || mentions(&["too many requests", "rate limit"])
+        {
+            GatewayError::RateLimited { retry_after }
+        } else if lower.contains("territory") && mentions(&["expired", "frozen"]) {
+            GatewayError::TerritoryExpired
+        } else if mentions(&[
+            "space not enough",
+            "not enough space",
+            "insufficient space",
+            "insufficient territory space",
+        ]) {
+            GatewayError::InsufficientTerritorySpace
+        } else if status == StatusCode::UNAUTHORIZED.as_u16()
+            || mentions(&[
+                "signature",
+                "unauthorized",
+                "authorization",
+                "permission denied",
+            ])
+        {
+            GatewayError::AuthFailed { reason: message }
+        } else if status == StatusCode::NOT_FOUND.as_u16()
+            || mentions(&["not found", "file does not exist", "fid not exist"])
+        {
+            GatewayError::FidNotFound
+        } else {
+            GatewayError::Api {
+                status,
+                body: body.to_string(),
+            }
+        }
+    }
+}
+
+/// A request sent with `request_id` that got no complete response.
+pub(crate) fn transport_error(
+    reason: impl std::fmt::Display,
+    request_id: Option<String>,
+) -> RequestError {
+    RequestError {
+        request_id,
+        error: GatewayError::Transport {
+            reason: reason.to_string(),
+        },
+    }
+}
+
+/// Consumes a failed response to the request sent with `request_id` and classifies it.
+pub(crate) async fn error_from_response(
+    response: Response,
+    request_id: Option<String>,
+) -> RequestError {
+    let status = response.status().as_u16();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let body = response.text().await.unwrap_or_default();
+
+    RequestError {
+        request_id,
+        error: GatewayErrorBody::parse(status, &body, retry_after),
+    }
+}
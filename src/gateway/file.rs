@@ -1,18 +1,31 @@
+use super::error::{error_from_response, transport_error};
+use super::http::{apply_request_hook, header_value, http_client};
+use super::progress::{track, Progress};
 use super::upload_response::UploadResponse;
+use crate::chain::storage_handler;
+use crate::constants::{MAX_SEGMENT_COUNT, SEGMENT_SIZE};
+use crate::core::Error;
 use crate::utils::{
-    account::get_pair_address_as_ss58_address, bucket::is_valid_bucket_name, str::get_random_code,
+    account::get_pair_address_as_ss58_address, bucket::is_valid_bucket_name,
+    cost::required_territory_space, str::get_random_code,
 };
+use crate::{init_api, polkadot};
 use base58::ToBase58;
+use futures::{Stream, StreamExt};
 use reqwest::{
-    header::{HeaderMap, HeaderValue},
-    multipart, Client, RequestBuilder,
+    header::{
+        HeaderMap, HeaderName, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+    },
+    multipart, Body, RequestBuilder, StatusCode,
 };
+use serde::{Deserialize, Serialize};
 use std::os::unix::fs::MetadataExt;
 use subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
 use tokio::{
     fs::{self, File},
-    io::{AsyncReadExt, AsyncWriteExt as _},
+    io::AsyncWriteExt as _,
 };
+use tokio_util::io::ReaderStream;
 
 pub async fn upload(
     gateway_url: &str,
@@ -20,70 +33,187 @@ pub async fn upload(
     bucket: &str,
     territory: &str,
     mnemonic: &str,
-) -> Result<UploadResponse, Box<dyn std::error::Error>> {
+) -> Result<UploadResponse, Error> {
+    upload_with_progress(gateway_url, file_path, bucket, territory, mnemonic, None).await
+}
+
+/// Like [`upload`], streaming the file from disk and reporting the bytes sent to `progress`.
+pub async fn upload_with_progress(
+    gateway_url: &str,
+    file_path: &str,
+    bucket: &str,
+    territory: &str,
+    mnemonic: &str,
+    progress: Option<Progress>,
+) -> Result<UploadResponse, Error> {
     let metadata = fs::metadata(file_path).await?;
 
     if metadata.is_dir() {
-        return Err("Given path is not a file.".into());
+        return Err(Error::InvalidInput("Given path is not a file.".into()));
     }
 
     if metadata.size() == 0 {
-        return Err("File is an empty file.".into());
+        return Err(Error::InvalidInput("File is an empty file.".into()));
     }
 
     if !is_valid_bucket_name(bucket) {
-        return Err("Invalid bucket name.".into());
+        return Err(Error::InvalidInput("Invalid bucket name.".into()));
     }
 
-    let pair = PairS::from_string(mnemonic, None)?;
-    let acc = get_pair_address_as_ss58_address(pair.clone())?;
-    let message = get_random_code(16)?;
+    let pair = PairS::from_string(mnemonic, None)
+        .map_err(|e| Error::InvalidInput(format!("Invalid mnemonic: {:?}", e)))?;
+    let acc =
+        get_pair_address_as_ss58_address(pair.clone()).map_err(|e| Error::Custom(e.to_string()))?;
+    preflight(metadata.size(), &acc, territory).await?;
+    let message = get_random_code(16).map_err(|e| Error::Custom(e.to_string()))?;
     let signed_msg = pair.sign(message.as_bytes());
 
     let mut headers = HeaderMap::new();
 
-    headers.insert("Bucket", HeaderValue::from_str(bucket)?);
-    headers.insert("Territory", HeaderValue::from_str(territory)?);
-    headers.insert("Account", HeaderValue::from_str(&acc)?);
-    headers.insert("Message", HeaderValue::from_str(&message)?);
-    headers.insert(
-        "Signature",
-        HeaderValue::from_str(&signed_msg.0.to_base58())?,
-    );
+    headers.insert("Bucket", header_value(bucket)?);
+    headers.insert("Territory", header_value(territory)?);
+    headers.insert("Account", header_value(&acc)?);
+    headers.insert("Message", header_value(&message)?);
+    headers.insert("Signature", header_value(&signed_msg.0.to_base58())?);
 
     let mut form = multipart::Form::new();
 
     let upload_url = format!("{}/file", gateway_url);
 
-    let mut file = File::open(file_path).await?;
-    let mut file_content = Vec::new();
-    file.read_to_end(&mut file_content).await?;
+    let file = File::open(file_path).await?;
+    let body = Body::wrap_stream(track(
+        ReaderStream::new(file),
+        Some(metadata.size()),
+        progress,
+    ));
 
     form = form.part(
         "file",
-        multipart::Part::stream(file_content.clone()).file_name(file_path.to_string()),
+        multipart::Part::stream_with_length(body, metadata.size()).file_name(file_path.to_string()),
     );
 
-    let client = Client::builder().build()?;
+    let request_id = apply_request_hook(&mut headers);
+    let client = http_client()?;
 
     let request_builder: RequestBuilder = client.put(upload_url).headers(headers).multipart(form);
 
-    let response = request_builder.send().await?;
-    let status_code = response.status();
-    let upload_response: UploadResponse = response.json().await?;
-
-    if !status_code.is_success() {
-        return Err("DeOss service failure, please retry or contact administrator.".into());
+    let response = request_builder
+        .send()
+        .await
+        .map_err(|e| transport_error(e, request_id.clone()))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response, request_id).await.into());
     }
+    let upload_response: UploadResponse = response
+        .json()
+        .await
+        .map_err(|e| transport_error(e, request_id))?;
     Ok(upload_response)
 }
 
+/// Checks that a file of `size` bytes can be stored in `territory` of `account`, before
+/// anything is sent to the gateway. [`upload`] runs it, so does
+/// [`super::object::upload_with_size`].
+///
+/// Fails with [`Error::FileTooLarge`] when the file needs more segments than the chain accepts
+/// per file, [`Error::TerritoryNotFound`] or [`Error::InsufficientTerritorySpace`] otherwise.
+pub async fn preflight(size: u64, account: &str, territory: &str) -> Result<(), Error> {
+    let max_segments = max_segment_count().await;
+    let segments = size.div_ceil(SEGMENT_SIZE as u64);
+    if segments > max_segments as u64 {
+        return Err(Error::FileTooLarge {
+            size,
+            max: max_segments as u64 * SEGMENT_SIZE as u64,
+            segments,
+            max_segments,
+        });
+    }
+
+    let info = storage_handler::query::StorageQuery::territory(account, territory, None)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?
+        .ok_or_else(|| Error::TerritoryNotFound {
+            name: territory.to_string(),
+        })?;
+    let required = required_territory_space(size);
+    if required > info.remaining_space {
+        return Err(Error::InsufficientTerritorySpace {
+            name: territory.to_string(),
+            remaining: info.remaining_space,
+            required,
+        });
+    }
+
+    Ok(())
+}
+
+/// Segment limit per file from the connected runtime, the compiled value when unavailable.
+async fn max_segment_count() -> u32 {
+    let address = polkadot::constants().file_bank().segment_count();
+    match init_api().await {
+        Ok(api) => api
+            .constants()
+            .at(&address)
+            .unwrap_or(MAX_SEGMENT_COUNT as u32),
+        Err(_) => MAX_SEGMENT_COUNT as u32,
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheValidator {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadOutcome {
+    /// The gateway answered `304 Not Modified`, the output file was not touched.
+    NotModified,
+    /// The file was written, `validator` should be persisted for the next call.
+    Downloaded { validator: CacheValidator },
+}
+
 pub async fn download(
     gateway_url: &str,
     fid: &str,
     mnemonic: &str,
     save_path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), Error> {
+    if let Ok(metadata) = fs::metadata(save_path).await {
+        if !metadata.is_dir() && metadata.len() == 0 {
+            return Ok(());
+        }
+    }
+
+    download_if_modified(gateway_url, fid, mnemonic, save_path, None).await?;
+    Ok(())
+}
+
+/// Downloads `fid` unless it is unchanged since the download that produced `validator`.
+///
+/// Sends `If-None-Match`/`If-Modified-Since` so CDN backed gateways can answer with 304.
+pub async fn download_if_modified(
+    gateway_url: &str,
+    fid: &str,
+    mnemonic: &str,
+    save_path: &str,
+    validator: Option<&CacheValidator>,
+) -> Result<DownloadOutcome, Error> {
+    download_with_progress(gateway_url, fid, mnemonic, save_path, validator, None).await
+}
+
+/// Like [`download_if_modified`], reporting the bytes received to `progress`.
+///
+/// The body is written to disk as it arrives rather than buffered in memory, and only replaces
+/// `save_path` once it was received in full.
+pub async fn download_with_progress(
+    gateway_url: &str,
+    fid: &str,
+    mnemonic: &str,
+    save_path: &str,
+    validator: Option<&CacheValidator>,
+    progress: Option<Progress>,
+) -> Result<DownloadOutcome, Error> {
     let mut save_path = String::from(save_path);
     let mut gateway_url = String::from(gateway_url);
 
@@ -91,14 +221,10 @@ pub async fn download(
         if metadata.is_dir() {
             save_path = format!("{}/{}", save_path, fid);
         }
-
-        if metadata.len() == 0 {
-            return Ok(());
-        }
     }
 
     if gateway_url.is_empty() {
-        return Err("Invalid gateway url.".into());
+        return Err(Error::InvalidInput("Invalid gateway url.".into()));
     }
 
     if !gateway_url.ends_with('/') {
@@ -107,40 +233,108 @@ pub async fn download(
 
     let download_url = format!("{}download/", gateway_url);
 
-    let pair = PairS::from_string(mnemonic, None)?;
-    let acc = get_pair_address_as_ss58_address(pair.clone())?;
-    let message = get_random_code(16)?;
+    let pair = PairS::from_string(mnemonic, None)
+        .map_err(|e| Error::InvalidInput(format!("Invalid mnemonic: {:?}", e)))?;
+    let acc =
+        get_pair_address_as_ss58_address(pair.clone()).map_err(|e| Error::Custom(e.to_string()))?;
+    let message = get_random_code(16).map_err(|e| Error::Custom(e.to_string()))?;
     let signed_msg = pair.sign(message.as_bytes());
 
     let mut headers = HeaderMap::new();
 
     headers.insert("Operation", HeaderValue::from_static("download"));
-    headers.insert("Account", HeaderValue::from_str(&acc)?);
-    headers.insert("Message", HeaderValue::from_str(&message)?);
-    headers.insert(
-        "Signature",
-        HeaderValue::from_str(&signed_msg.0.to_base58())?,
-    );
+    headers.insert("Account", header_value(&acc)?);
+    headers.insert("Message", header_value(&message)?);
+    headers.insert("Signature", header_value(&signed_msg.0.to_base58())?);
+
+    if let Some(validator) = validator {
+        if let Some(etag) = &validator.etag {
+            headers.insert(IF_NONE_MATCH, header_value(etag)?);
+        }
+        if let Some(last_modified) = &validator.last_modified {
+            headers.insert(IF_MODIFIED_SINCE, header_value(last_modified)?);
+        }
+    }
 
-    let client = Client::new();
+    let request_id = apply_request_hook(&mut headers);
+    let client = http_client()?;
     let request_builder: RequestBuilder = client
         .get(format!("{}{}", download_url, fid))
         .headers(headers);
 
-    let f = File::create(&save_path).await?;
-    let response = request_builder.send().await?;
+    let response = request_builder
+        .send()
+        .await
+        .map_err(|e| transport_error(e, request_id.clone()))?;
     let status_code = response.status();
 
+    if status_code == StatusCode::NOT_MODIFIED {
+        return Ok(DownloadOutcome::NotModified);
+    }
+
     if !status_code.is_success() {
-        return Err("Failed to download.".into());
+        return Err(error_from_response(response, request_id).await.into());
     }
-    let mut writer = f;
 
-    let mut response_body = response.bytes().await?;
-    while !response_body.is_empty() {
-        let bytes_written = writer.write(&response_body).await?;
-        response_body = response_body[bytes_written..].to_vec().into();
+    let header_string = |name: HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+    let validator = CacheValidator {
+        etag: header_string(ETAG),
+        last_modified: header_string(LAST_MODIFIED),
+    };
+
+    // Written next to the destination and renamed over it once complete, so a failed transfer
+    // leaves a previous copy of the file untouched.
+    let suffix = get_random_code(8).map_err(|e| Error::Custom(e.to_string()))?;
+    let partial_path = format!("{}.{}.part", save_path, suffix);
+    let total = response.content_length();
+    let body = track(response.bytes_stream(), total, progress);
+    let written = match write_body(&partial_path, body, total, request_id).await {
+        Ok(()) => fs::rename(&partial_path, &save_path)
+            .await
+            .map_err(Error::from),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = written {
+        fs::remove_file(&partial_path).await.ok();
+        return Err(e);
     }
 
-    Ok(())
+    Ok(DownloadOutcome::Downloaded { validator })
+}
+
+/// Streams `body` of the request sent with `request_id` into a new file at `path`, failing
+/// unless all `total` bytes arrive.
+async fn write_body<S, T>(
+    path: &str,
+    body: S,
+    total: Option<u64>,
+    request_id: Option<String>,
+) -> Result<(), Error>
+where
+    S: Stream<Item = reqwest::Result<T>>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = File::create(path).await?;
+    let mut body = Box::pin(body);
+    let mut written = 0u64;
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| transport_error(e, request_id.clone()))?;
+        writer.write_all(chunk.as_ref()).await?;
+        written += chunk.as_ref().len() as u64;
+    }
+    writer.flush().await?;
+
+    match total {
+        Some(total) if total != written => {
+            let reason = format!("body truncated, received {} of {} bytes", written, total);
+            Err(transport_error(reason, request_id).into())
+        }
+        _ => Ok(()),
+    }
 }
@@ -0,0 +1,260 @@
+use crate::core::rng::RngProvider;
+use crate::core::Error;
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Certificate, Client, ClientBuilder, Identity, NoProxy, Proxy};
+use std::sync::{Arc, RwLock};
+use uuid::Builder;
+
+/// Header carrying the id generated for every gateway request, see
+/// [`HttpConfig::send_request_id`].
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+type RequestHook = Arc<dyn Fn(&mut HeaderMap) + Send + Sync>;
+
+/// Per request settings of the last [`set_http_config`].
+#[derive(Clone, Default)]
+struct RequestSettings {
+    hook: Option<RequestHook>,
+    omit_request_id: bool,
+}
+
+static HTTP_CLIENT: Lazy<RwLock<Option<Client>>> = Lazy::new(|| RwLock::new(None));
+static REQUEST_SETTINGS: Lazy<RwLock<RequestSettings>> =
+    Lazy::new(|| RwLock::new(RequestSettings::default()));
+
+/// Settings of the HTTP client shared by every gateway helper.
+///
+/// Certificates are parsed as they are added, so a bad PEM fails before any request is made.
+///
+/// ```no_run
+/// # fn run() -> Result<(), cess_rust_sdk::core::Error> {
+/// use cess_rust_sdk::gateway::http::{set_http_config, HttpConfig};
+///
+/// let ca = std::fs::read("ca.pem").unwrap();
+/// let cert = std::fs::read("client.pem").unwrap();
+/// let key = std::fs::read("client.key").unwrap();
+/// let config = HttpConfig::new()
+///     .add_root_certificate(&ca)?
+///     .identity_pem(&cert, &key)?;
+/// set_http_config(&config)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct HttpConfig {
+    root_certificates: Vec<Certificate>,
+    identity: Option<Identity>,
+    accept_invalid_hostnames: bool,
+    proxy: Option<String>,
+    proxy_credentials: Option<(String, String)>,
+    no_proxy: Vec<String>,
+    request_hook: Option<RequestHook>,
+    user_agent_suffix: Option<String>,
+    omit_user_agent: bool,
+    omit_request_id: bool,
+}
+
+impl HttpConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trusts `pem` in addition to the system roots, e.g. the CA of a private gateway.
+    pub fn add_root_certificate(mut self, pem: &[u8]) -> Result<Self, Error> {
+        let certificate = Certificate::from_pem(pem)
+            .map_err(|e| Error::InvalidInput(format!("Invalid root certificate PEM: {}", e)))?;
+        self.root_certificates.push(certificate);
+        Ok(self)
+    }
+
+    /// Client certificate for mutual TLS, from a PEM certificate chain and PKCS#8 PEM key.
+    pub fn identity_pem(mut self, cert_pem: &[u8], key_pem: &[u8]) -> Result<Self, Error> {
+        let identity = Identity::from_pkcs8_pem(cert_pem, key_pem).map_err(|e| {
+            Error::InvalidInput(format!("Invalid client certificate or key PEM: {}", e))
+        })?;
+        self.identity = Some(identity);
+        Ok(self)
+    }
+
+    /// Client certificate for mutual TLS, from a DER encoded PKCS#12 archive.
+    pub fn identity_pkcs12(mut self, der: &[u8], password: &str) -> Result<Self, Error> {
+        let identity = Identity::from_pkcs12_der(der, password)
+            .map_err(|e| Error::InvalidInput(format!("Invalid PKCS#12 identity: {}", e)))?;
+        self.identity = Some(identity);
+        Ok(self)
+    }
+
+    /// Skips hostname verification of gateway certificates. Only meant for test deployments
+    /// whose certificates do not name the host they are served from.
+    pub fn danger_accept_invalid_hostnames(mut self, accept: bool) -> Self {
+        self.accept_invalid_hostnames = accept;
+        self
+    }
+
+    /// Routes gateway traffic through an `http://`, `https://` or `socks5://` proxy.
+    ///
+    /// Without one, the `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` environment
+    /// variables apply. RPC connections are proxied with [`set_rpc_proxy`].
+    ///
+    /// [`set_rpc_proxy`]: crate::core::proxy::set_rpc_proxy
+    pub fn proxy(mut self, url: &str) -> Result<Self, Error> {
+        Proxy::all(url)
+            .map_err(|e| Error::InvalidInput(format!("Invalid proxy url {}: {}", url, e)))?;
+        self.proxy = Some(url.to_string());
+        Ok(self)
+    }
+
+    /// Basic auth credentials for the proxy set with [`HttpConfig::proxy`], before or after it.
+    pub fn proxy_auth(mut self, username: &str, password: &str) -> Self {
+        self.proxy_credentials = Some((username.to_string(), password.to_string()));
+        self
+    }
+
+    /// Hosts, domains (`.example.com`) or CIDR ranges reached without the proxy set with
+    /// [`HttpConfig::proxy`], before or after it.
+    pub fn no_proxy<S: Into<String>>(mut self, destinations: impl IntoIterator<Item = S>) -> Self {
+        self.no_proxy
+            .extend(destinations.into_iter().map(Into::into));
+        self
+    }
+
+    /// Appended to the [`default_user_agent`], e.g. `my-app/1.2`.
+    pub fn user_agent_suffix(mut self, suffix: &str) -> Self {
+        self.user_agent_suffix = Some(suffix.to_string());
+        self
+    }
+
+    /// Whether requests identify the SDK with a `User-Agent` header, true by default.
+    pub fn send_user_agent(mut self, send: bool) -> Self {
+        self.omit_user_agent = !send;
+        self
+    }
+
+    /// Whether every request carries a random [`REQUEST_ID_HEADER`], true by default. Failures
+    /// of such requests are reported as [`RequestError`] with the id, to quote to the gateway
+    /// operator.
+    ///
+    /// [`RequestError`]: super::error::RequestError
+    pub fn send_request_id(mut self, send: bool) -> Self {
+        self.omit_request_id = !send;
+        self
+    }
+
+    /// Lets `hook` add headers to every gateway request, e.g. W3C `traceparent`/`tracestate`
+    /// taken from the active span. The [`REQUEST_ID_HEADER`] is already set when it runs, so
+    /// the hook can also record it on the span.
+    pub fn on_request(mut self, hook: impl Fn(&mut HeaderMap) + Send + Sync + 'static) -> Self {
+        self.request_hook = Some(Arc::new(hook));
+        self
+    }
+
+    pub(crate) fn client_builder(&self) -> Result<ClientBuilder, Error> {
+        let mut builder =
+            Client::builder().danger_accept_invalid_hostnames(self.accept_invalid_hostnames);
+        if !self.omit_user_agent {
+            let user_agent = match &self.user_agent_suffix {
+                Some(suffix) => format!("{} {}", default_user_agent(), suffix),
+                None => default_user_agent(),
+            };
+            builder = builder.user_agent(user_agent);
+        }
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(identity.clone());
+        }
+        if let Some(url) = &self.proxy {
+            let mut proxy = Proxy::all(url)
+                .map_err(|e| Error::InvalidInput(format!("Invalid proxy url {}: {}", url, e)))?;
+            if let Some((username, password)) = &self.proxy_credentials {
+                proxy = proxy.basic_auth(username, password);
+            }
+            if !self.no_proxy.is_empty() {
+                proxy = proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(",")));
+            }
+            builder = builder.proxy(proxy);
+        }
+        Ok(builder)
+    }
+
+    pub fn build(&self) -> Result<Client, Error> {
+        self.client_builder()?
+            .build()
+            .map_err(|e| Error::Custom(format!("Failed to build HTTP client: {}", e)))
+    }
+}
+
+/// Makes every gateway helper use a client built from `config`.
+pub fn set_http_config(config: &HttpConfig) -> Result<(), Error> {
+    let client = config.build()?;
+    *HTTP_CLIENT.write().unwrap_or_else(|e| e.into_inner()) = Some(client);
+    *REQUEST_SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = RequestSettings {
+        hook: config.request_hook.clone(),
+        omit_request_id: config.omit_request_id,
+    };
+    Ok(())
+}
+
+/// `cess-rust-sdk/<version> (<os>; <arch>)`, sent as `User-Agent` unless disabled with
+/// [`HttpConfig::send_user_agent`].
+pub fn default_user_agent() -> String {
+    format!(
+        "cess-rust-sdk/{} ({}; {})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
+/// Sets the [`REQUEST_ID_HEADER`] of a request, then applies the hook set with
+/// [`HttpConfig::on_request`]. Returns the id, if one was set.
+pub(crate) fn apply_request_hook(headers: &mut HeaderMap) -> Option<String> {
+    let settings = REQUEST_SETTINGS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    let request_id = (!settings.omit_request_id).then(|| {
+        let mut bytes = [0u8; 16];
+        RngProvider.fill_bytes(&mut bytes);
+        Builder::from_random_bytes(bytes).into_uuid().to_string()
+    });
+    if let Some(id) = &request_id {
+        if let Ok(value) = HeaderValue::from_str(id) {
+            headers.insert(REQUEST_ID_HEADER, value);
+        }
+    }
+    if let Some(hook) = settings.hook {
+        hook(headers);
+    }
+    request_id
+}
+
+/// `value` as a header value, rejecting characters headers cannot carry.
+pub(crate) fn header_value(value: &str) -> Result<HeaderValue, Error> {
+    HeaderValue::from_str(value)
+        .map_err(|e| Error::InvalidInput(format!("Invalid header value {:?}: {}", value, e)))
+}
+
+/// The shared client, built from the default [`HttpConfig`] until one is set.
+pub(crate) fn http_client() -> Result<Client, Error> {
+    if let Some(client) = HTTP_CLIENT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+    {
+        return Ok(client.clone());
+    }
+
+    let mut slot = HTTP_CLIENT.write().unwrap_or_else(|e| e.into_inner());
+    match slot.as_ref() {
+        Some(client) => Ok(client.clone()),
+        None => {
+            let client = HttpConfig::default().build()?;
+            *slot = Some(client.clone());
+            Ok(client)
+        }
+    }
+}
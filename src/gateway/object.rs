@@ -1,4 +1,8 @@
+use super::error::{error_from_response, transport_error};
+use super::file::preflight;
+use super::http::{apply_request_hook, header_value, http_client};
 use super::upload_response::UploadResponse;
+use crate::core::Error;
 use crate::utils::{
     account::get_pair_address_as_ss58_address, bucket::is_valid_bucket_name, str::get_random_code,
 };
@@ -6,7 +10,7 @@ use base58::ToBase58;
 use futures_util::stream::StreamExt;
 use reqwest::{
     header::{HeaderMap, HeaderValue},
-    Body, Client, RequestBuilder,
+    Body, RequestBuilder,
 };
 use subxt::ext::sp_core::{sr25519::Pair as PairS, Pair};
 use tokio::io::AsyncRead;
@@ -18,30 +22,46 @@ pub async fn upload<R: AsyncRead + Send + Sync + Unpin + 'static>(
     bucket: &str,
     territory: &str,
     mnemonic: &str,
-) -> Result<UploadResponse, Box<dyn std::error::Error>> {
+) -> Result<UploadResponse, Error> {
+    upload_with_size(gateway_url, reader, None, bucket, territory, mnemonic).await
+}
+
+/// Like [`upload`], with the number of bytes `reader` yields when it is known. A known size is
+/// checked with [`preflight`] before anything is sent to the gateway.
+pub async fn upload_with_size<R: AsyncRead + Send + Sync + Unpin + 'static>(
+    gateway_url: &str,
+    reader: R,
+    size: Option<u64>,
+    bucket: &str,
+    territory: &str,
+    mnemonic: &str,
+) -> Result<UploadResponse, Error> {
     if !is_valid_bucket_name(bucket) {
-        return Err("Invalid bucket name.".into());
+        return Err(Error::InvalidInput("Invalid bucket name.".into()));
     }
 
-    let pair = PairS::from_string(mnemonic, None)?;
-    let acc = get_pair_address_as_ss58_address(pair.clone())?;
-    let message = get_random_code(16)?;
+    let pair = PairS::from_string(mnemonic, None)
+        .map_err(|e| Error::InvalidInput(format!("Invalid mnemonic: {:?}", e)))?;
+    let acc =
+        get_pair_address_as_ss58_address(pair.clone()).map_err(|e| Error::Custom(e.to_string()))?;
+    if let Some(size) = size {
+        preflight(size, &acc, territory).await?;
+    }
+    let message = get_random_code(16).map_err(|e| Error::Custom(e.to_string()))?;
     let signed_msg = pair.sign(message.as_bytes());
 
     let mut headers = HeaderMap::new();
 
-    headers.insert("Bucket", HeaderValue::from_str(bucket)?);
-    headers.insert("Territory", HeaderValue::from_str(territory)?);
-    headers.insert("Account", HeaderValue::from_str(&acc)?);
-    headers.insert("Message", HeaderValue::from_str(&message)?);
-    headers.insert(
-        "Signature",
-        HeaderValue::from_str(&signed_msg.0.to_base58())?,
-    );
+    headers.insert("Bucket", header_value(bucket)?);
+    headers.insert("Territory", header_value(territory)?);
+    headers.insert("Account", header_value(&acc)?);
+    headers.insert("Message", header_value(&message)?);
+    headers.insert("Signature", header_value(&signed_msg.0.to_base58())?);
 
     let upload_url = format!("{}/object", gateway_url);
 
-    let client = Client::builder().build()?;
+    let request_id = apply_request_hook(&mut headers);
+    let client = http_client()?;
 
     let stream = ReaderStream::new(reader);
     let body = Body::wrap_stream(
@@ -50,13 +70,17 @@ pub async fn upload<R: AsyncRead + Send + Sync + Unpin + 'static>(
 
     let request_builder = client.put(upload_url).headers(headers).body(body);
 
-    let response = request_builder.send().await?;
-    let status_code = response.status();
-    let upload_response: UploadResponse = response.json().await?;
-
-    if !status_code.is_success() {
-        return Err("DeOss service failure, please retry or contact administrator.".into());
+    let response = request_builder
+        .send()
+        .await
+        .map_err(|e| transport_error(e, request_id.clone()))?;
+    if !response.status().is_success() {
+        return Err(error_from_response(response, request_id).await.into());
     }
+    let upload_response: UploadResponse = response
+        .json()
+        .await
+        .map_err(|e| transport_error(e, request_id))?;
 
     Ok(upload_response)
 }
@@ -65,11 +89,11 @@ pub async fn download(
     gateway_url: &str,
     fid: &str,
     mnemonic: &str,
-) -> Result<impl AsyncRead + Unpin, Box<dyn std::error::Error>> {
+) -> Result<impl AsyncRead + Unpin, Error> {
     let mut gateway_url = String::from(gateway_url);
 
     if gateway_url.is_empty() {
-        return Err("Invalid gateway url.".into());
+        return Err(Error::InvalidInput("Invalid gateway url.".into()));
     }
 
     if !gateway_url.ends_with('/') {
@@ -78,36 +102,45 @@ pub async fn download(
 
     let download_url = format!("{}download/", gateway_url);
 
-    let pair = PairS::from_string(mnemonic, None)?;
-    let acc = get_pair_address_as_ss58_address(pair.clone())?;
-    let message = get_random_code(16)?;
+    let pair = PairS::from_string(mnemonic, None)
+        .map_err(|e| Error::InvalidInput(format!("Invalid mnemonic: {:?}", e)))?;
+    let acc =
+        get_pair_address_as_ss58_address(pair.clone()).map_err(|e| Error::Custom(e.to_string()))?;
+    let message = get_random_code(16).map_err(|e| Error::Custom(e.to_string()))?;
     let signed_msg = pair.sign(message.as_bytes());
 
     let mut headers = HeaderMap::new();
 
     headers.insert("Operation", HeaderValue::from_static("download"));
-    headers.insert("Account", HeaderValue::from_str(&acc)?);
-    headers.insert("Message", HeaderValue::from_str(&message)?);
-    headers.insert(
-        "Signature",
-        HeaderValue::from_str(&signed_msg.0.to_base58())?,
-    );
+    headers.insert("Account", header_value(&acc)?);
+    headers.insert("Message", header_value(&message)?);
+    headers.insert("Signature", header_value(&signed_msg.0.to_base58())?);
 
-    let client = Client::new();
+    let request_id = apply_request_hook(&mut headers);
+    let client = http_client()?;
     let request_builder: RequestBuilder = client
         .get(format!("{}{}", download_url, fid))
         .headers(headers);
 
-    let response = request_builder.send().await?;
+    let response = request_builder
+        .send()
+        .await
+        .map_err(|e| transport_error(e, request_id.clone()))?;
     let status_code = response.status();
 
     if !status_code.is_success() {
-        return Err("Failed to download.".into());
+        return Err(error_from_response(response, request_id).await.into());
     }
 
-    let stream = response
-        .bytes_stream()
-        .map(|result| result.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)));
+    // Read errors carry the request id too, as the source of the `io::Error`.
+    let stream = response.bytes_stream().map(move |result| {
+        result.map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::Other,
+                transport_error(e, request_id.clone()),
+            )
+        })
+    });
     let reader = StreamReader::new(stream);
 
     Ok(reader)
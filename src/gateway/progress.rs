@@ -0,0 +1,42 @@
+use futures::{Stream, StreamExt};
+use std::sync::Arc;
+
+/// Bytes moved so far by an upload or download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressEvent {
+    pub transferred: u64,
+    /// Size of the whole transfer, `None` when the gateway does not announce it.
+    pub total: Option<u64>,
+}
+
+impl ProgressEvent {
+    pub fn percent(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(100.0),
+            Some(total) => Some(self.transferred as f64 * 100.0 / total as f64),
+            None => None,
+        }
+    }
+}
+
+/// Called once per transferred chunk.
+pub type Progress = Arc<dyn Fn(ProgressEvent) + Send + Sync>;
+
+/// Reports every chunk passing through `stream` to `progress`.
+pub(crate) fn track<S, T, E>(
+    stream: S,
+    total: Option<u64>,
+    progress: Option<Progress>,
+) -> impl Stream<Item = Result<T, E>>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: AsRef<[u8]>,
+{
+    let mut transferred = 0u64;
+    stream.inspect(move |chunk| {
+        if let (Ok(chunk), Some(progress)) = (chunk, &progress) {
+            transferred += chunk.as_ref().len() as u64;
+            progress(ProgressEvent { transferred, total });
+        }
+    })
+}
@@ -0,0 +1,205 @@
+//! Readiness summary for services embedding the SDK.
+
+use crate::chain::storage_at;
+use crate::core::Error;
+use crate::gateway::error::error_from_response;
+use crate::gateway::http::{apply_request_hook, http_client};
+use crate::{init_api, polkadot};
+use futures::StreamExt;
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::future::Future;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use subxt::config::Header;
+use subxt::utils::AccountId32;
+
+/// Which checks to run. Checks left as `None` are reported as skipped.
+#[derive(Debug, Clone)]
+pub struct HealthCheckConfig {
+    /// Maximum distance between the best and the finalized head, `None` skips the chain check.
+    pub max_finality_lag: Option<u64>,
+    pub gateway_url: Option<String>,
+    /// SS58 account and the minimum free balance it must hold.
+    pub funded_account: Option<(String, u128)>,
+    /// Timeout applied to each check separately.
+    pub timeout: Duration,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            max_finality_lag: Some(10),
+            gateway_url: None,
+            funded_account: None,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    /// Every configured check passed.
+    Ready,
+    /// The chain is usable but an auxiliary check failed.
+    Degraded,
+    /// The chain check failed.
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub elapsed_ms: u128,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub verdict: Verdict,
+    pub checks: Vec<CheckResult>,
+}
+
+/// Runs the configured checks concurrently and summarizes them.
+pub async fn check(config: HealthCheckConfig) -> HealthReport {
+    let timeout = config.timeout;
+    let (chain, gateway, balance) = tokio::join!(
+        run_check("chain", timeout, config.max_finality_lag.map(check_chain)),
+        run_check(
+            "gateway",
+            timeout,
+            config
+                .gateway_url
+                .as_deref()
+                .map(|url| check_gateway(url, timeout))
+        ),
+        run_check(
+            "balance",
+            timeout,
+            config
+                .funded_account
+                .as_ref()
+                .map(|(account, min_free)| check_balance(account, *min_free))
+        ),
+    );
+
+    let verdict = if chain.status == CheckStatus::Fail {
+        Verdict::Unavailable
+    } else if gateway.status == CheckStatus::Fail || balance.status == CheckStatus::Fail {
+        Verdict::Degraded
+    } else {
+        Verdict::Ready
+    };
+
+    HealthReport {
+        verdict,
+        checks: vec![chain, gateway, balance],
+    }
+}
+
+async fn run_check<F>(name: &'static str, timeout: Duration, check: Option<F>) -> CheckResult
+where
+    F: Future<Output = Result<String, Error>>,
+{
+    let check = match check {
+        Some(check) => check,
+        None => {
+            return CheckResult {
+                name,
+                status: CheckStatus::Skipped,
+                detail: None,
+                elapsed_ms: 0,
+            }
+        }
+    };
+
+    let start = Instant::now();
+    let (status, detail) = match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(detail)) => (CheckStatus::Pass, detail),
+        Ok(Err(e)) => (CheckStatus::Fail, e.to_string()),
+        Err(_) => (CheckStatus::Fail, format!("Timed out after {:?}", timeout)),
+    };
+
+    CheckResult {
+        name,
+        status,
+        detail: Some(detail),
+        elapsed_ms: start.elapsed().as_millis(),
+    }
+}
+
+async fn check_chain(max_finality_lag: u64) -> Result<String, Error> {
+    let api = init_api().await?;
+    let finalized: u64 = api.blocks().at_latest().await?.number().into();
+    let best: u64 = match api
+        .backend()
+        .stream_best_block_headers()
+        .await?
+        .next()
+        .await
+    {
+        Some(header) => header?.0.number().into(),
+        None => return Err("Best block stream ended".into()),
+    };
+
+    let lag = best.saturating_sub(finalized);
+    if lag > max_finality_lag {
+        return Err(format!(
+            "Finalized #{} is {} blocks behind best #{}",
+            finalized, lag, best
+        )
+        .into());
+    }
+
+    Ok(format!("best #{}, finalized #{}", best, finalized))
+}
+
+async fn check_gateway(url: &str, timeout: Duration) -> Result<String, Error> {
+    let mut headers = HeaderMap::new();
+    let request_id = apply_request_hook(&mut headers);
+    let response = http_client()?
+        .get(url)
+        .headers(headers)
+        .timeout(timeout)
+        .send()
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?;
+
+    // Any answer short of a server error means the gateway is up.
+    if response.status().is_server_error() {
+        return Err(error_from_response(response, request_id)
+            .await
+            .to_string()
+            .into());
+    }
+
+    Ok(format!("HTTP {}", response.status()))
+}
+
+async fn check_balance(account: &str, min_free: u128) -> Result<String, Error> {
+    let account = AccountId32::from_str(account).map_err(|e| Error::Custom(e.to_string()))?;
+    let query = polkadot::storage().system().account(account);
+    let free = storage_at(None)
+        .await?
+        .fetch(&query)
+        .await?
+        .map(|info| info.data.free)
+        .unwrap_or_default();
+
+    if free < min_free {
+        return Err(format!("Free balance {} below threshold {}", free, min_free).into());
+    }
+
+    Ok(format!("free balance {}", free))
+}
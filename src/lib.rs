@@ -1,40 +1,96 @@
 #![recursion_limit = "1024"]
 
 pub mod chain;
+pub mod client;
 pub mod constants;
 pub mod core;
 pub mod gateway;
+pub mod health;
+pub mod protocol;
 pub mod utils;
 
 use core::Error;
 use dotenv::dotenv;
 use futures::future;
-use log::info;
+use log::{info, warn};
 use once_cell::sync::Lazy;
+use std::any::Any;
 use std::env;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{Duration, Instant};
 pub use subxt;
+use subxt::backend::legacy::LegacyRpcMethods;
 use subxt::backend::rpc::reconnecting_rpc_client::{Client, ExponentialBackoff};
+use subxt::backend::rpc::RpcClient;
 use subxt::utils::Yes;
 use subxt::{
-    config::substrate::H256, storage::Address as StorageAddress, OnlineClient, PolkadotConfig,
+    config::substrate::H256, storage::Address as StorageAddress, Config, OnlineClient,
+    PolkadotConfig,
 };
-use tokio::sync::Mutex;
+use tokio::sync::{watch, Mutex};
 use tokio::task;
 
-static CHAIN_API: Lazy<Arc<Mutex<Option<OnlineClient<PolkadotConfig>>>>> =
-    Lazy::new(|| Arc::new(Mutex::new(None)));
+static CHAIN_API: Lazy<Arc<Mutex<Option<Connection>>>> = Lazy::new(|| Arc::new(Mutex::new(None)));
+
+/// Endpoint the global client is connected to, see [`ChainInfo::rpc_url`].
+static CHAIN_URL: Lazy<StdMutex<Option<String>>> = Lazy::new(|| StdMutex::new(None));
+
+/// Node `init_api` connects to when neither `RPC_URL` nor other endpoints are given.
+const DEFAULT_RPC_URL: &str = "ws://127.0.0.1:9944";
 
 #[subxt::subxt(runtime_metadata_path = "metadata/metadata.scale")]
 pub mod polkadot {}
 
+/// Reconnection policy of the RPC client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcClientConfig {
+    pub initial_delay_ms: u64,
+    pub max_delay_secs: u64,
+    pub max_retries: usize,
+}
+
+impl Default for RpcClientConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: 100,
+            max_delay_secs: 10,
+            max_retries: 3,
+        }
+    }
+}
+
+impl RpcClientConfig {
+    /// Reads `RPC_RETRY_INITIAL_MS`, `RPC_RETRY_MAX_SECS` and `RPC_RETRY_COUNT`, keeping the
+    /// defaults for unset or unparsable values. Connections the SDK opens itself, through
+    /// [`init_api`] or [`client::CessClient::connect`], use this policy.
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(key: &str) -> Option<T> {
+            env::var(key).ok().and_then(|v| v.trim().parse().ok())
+        }
+
+        let default = Self::default();
+        Self {
+            initial_delay_ms: var("RPC_RETRY_INITIAL_MS").unwrap_or(default.initial_delay_ms),
+            max_delay_secs: var("RPC_RETRY_MAX_SECS").unwrap_or(default.max_delay_secs),
+            max_retries: var("RPC_RETRY_COUNT").unwrap_or(default.max_retries),
+        }
+    }
+}
+
 async fn prepare_rpc_client(url: &str) -> Result<Client, Error> {
+    dotenv().ok();
+    prepare_rpc_client_with_config(url, RpcClientConfig::from_env()).await
+}
+
+pub async fn prepare_rpc_client_with_config(
+    url: &str,
+    config: RpcClientConfig,
+) -> Result<Client, Error> {
     let client = Client::builder()
         .retry_policy(
-            ExponentialBackoff::from_millis(100)
-                .max_delay(Duration::from_secs(10))
-                .take(3),
+            ExponentialBackoff::from_millis(config.initial_delay_ms)
+                .max_delay(Duration::from_secs(config.max_delay_secs))
+                .take(config.max_retries),
         )
         .build(url.to_string())
         .await
@@ -43,15 +99,47 @@ async fn prepare_rpc_client(url: &str) -> Result<Client, Error> {
     Ok(client)
 }
 
-async fn try_connect(url: Option<&str>) -> Result<OnlineClient<PolkadotConfig>, Error> {
-    let rpc = match url {
-        Some(url) => prepare_rpc_client(url).await?,
-        None => prepare_rpc_client("ws://127.0.0.1:9944").await?,
-    };
+/// A client with the RPC client it runs on, for the node methods `OnlineClient` does not expose.
+#[derive(Clone)]
+pub(crate) struct Connection {
+    pub(crate) api: OnlineClient<PolkadotConfig>,
+    /// `None` for clients built outside the SDK, see [`client::CessClient::from_client`].
+    pub(crate) rpc: Option<RpcClient>,
+    pub(crate) tasks: Option<Arc<ConnectionTasks>>,
+}
+
+/// Stops the tasks serving a connection, the reconnect log and the proxy tunnel, once the last
+/// copy of the connection is dropped. The reconnect log holds the websocket too, so it would
+/// otherwise keep it open for good.
+pub(crate) struct ConnectionTasks(pub(crate) Vec<task::AbortHandle>);
+
+impl Drop for ConnectionTasks {
+    fn drop(&mut self) {
+        for task in &self.0 {
+            task.abort();
+        }
+    }
+}
+
+impl Connection {
+    /// Legacy RPC methods over the same connection as `api`.
+    pub(crate) fn legacy_rpc(&self) -> Result<LegacyRpcMethods<PolkadotConfig>, Error> {
+        match &self.rpc {
+            Some(rpc) => Ok(LegacyRpcMethods::new(rpc.clone())),
+            None => Err("No RPC client for this connection, connect through the SDK.".into()),
+        }
+    }
+}
+
+async fn try_connect(url: Option<&str>) -> Result<Connection, Error> {
+    let (endpoint, tunnel) = core::proxy::rpc_endpoint(url.unwrap_or(DEFAULT_RPC_URL)).await?;
+    // Aborts the tunnel if connecting fails.
+    let mut tasks = ConnectionTasks(tunnel.into_iter().collect());
+    let rpc = prepare_rpc_client(&endpoint).await?;
     let api = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc.clone()).await?;
 
     let rpc2 = rpc.clone();
-    tokio::spawn(async move {
+    let reconnect_log = tokio::spawn(async move {
         loop {
             let reconnected = rpc2.reconnect_initiated().await;
             let now = std::time::Instant::now();
@@ -63,75 +151,312 @@ async fn try_connect(url: Option<&str>) -> Result<OnlineClient<PolkadotConfig>,
         }
     });
 
-    Ok(api)
+    tasks.0.push(reconnect_log.abort_handle());
+    Ok(Connection {
+        api,
+        rpc: Some(RpcClient::new(rpc)),
+        tasks: Some(Arc::new(tasks)),
+    })
 }
 
-async fn try_default_connect() -> Result<OnlineClient<PolkadotConfig>, Error> {
-    let mut urls = [
-        "wss://testnet-rpc.cess.cloud:443/ws/",
-        "wss://testnet-rpc0.cess.cloud:443/ws/",
-        "wss://testnet-rpc1.cess.cloud:443/ws/",
-        "wss://testnet-rpc2.cess.cloud:443/ws/",
-    ]
-    .iter()
-    .map(|&s| s.to_string())
-    .collect::<Vec<String>>();
+/// A network the SDK knows public RPC endpoints for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Local,
+}
 
-    if let Ok(is_mainnet) = env::var("RPC_NETWORK").map(|val| val == "mainnet") {
-        if is_mainnet {
+impl Network {
+    /// `Mainnet` when `RPC_NETWORK` is `mainnet`, `Testnet` otherwise.
+    pub fn from_env() -> Self {
+        match env::var("RPC_NETWORK") {
+            Ok(val) if val == "mainnet" => Network::Mainnet,
+            _ => Network::Testnet,
+        }
+    }
+
+    pub fn rpc_urls(&self) -> &'static [&'static str] {
+        match self {
             // TODO: Replace with mainnet URLs when mainnet launch
-            urls = [
+            Network::Mainnet => &[
                 "wss://devnet-rpc.cess.cloud/ws/", // This is devnet
-            ]
-            .iter()
-            .map(|&s| s.to_string())
-            .collect::<Vec<String>>();
+            ],
+            Network::Testnet => &[
+                "wss://testnet-rpc.cess.cloud:443/ws/",
+                "wss://testnet-rpc0.cess.cloud:443/ws/",
+                "wss://testnet-rpc1.cess.cloud:443/ws/",
+                "wss://testnet-rpc2.cess.cloud:443/ws/",
+            ],
+            Network::Local => &["ws://127.0.0.1:9944"],
         }
     }
+}
+
+async fn try_default_connect() -> Result<(Connection, String), Error> {
+    let urls = Network::from_env()
+        .rpc_urls()
+        .iter()
+        .map(|&s| s.to_string())
+        .collect::<Vec<String>>();
 
-    let tasks: Vec<_> = urls
-        .into_iter()
-        .map(|url| task::spawn(async move { try_connect(Some(&url)).await.ok() }))
-        .collect();
+    connect_any(&urls).await
+}
+
+/// Races connections to `urls` and returns the first one established, with its url.
+async fn connect_any(urls: &[String]) -> Result<(Connection, String), Error> {
+    if urls.is_empty() {
+        return Err("No RPC endpoints given.".into());
+    }
+
+    let tasks: Vec<_> =
+        urls.iter()
+            .cloned()
+            .map(|url| {
+                Box::pin(async move {
+                    let connect = task::spawn(async move {
+                        try_connect(Some(&url)).await.map(|conn| (conn, url))
+                    });
+                    match connect.await {
+                        Ok(result) => result,
+                        Err(e) => Err(Error::Custom(e.to_string())),
+                    }
+                })
+            })
+            .collect();
 
     // Returns the first successful connection or an error
     match future::select_ok(tasks).await {
-        Ok((Some(api), _)) => Ok(api),
-        Ok((None, _)) => Err("No successful connection.".into()),
-        Err(_) => Err("All connections failed.".into()),
+        Ok((connected, _)) => Ok(connected),
+        Err(e) => Err(format!(
+            "All connections failed, tried: {} (last error: {})",
+            urls.join(", "),
+            e
+        )
+        .into()),
     }
 }
 
+/// Bumped whenever the global client is replaced, see [`client_generation`].
+static CLIENT_GENERATION: Lazy<watch::Sender<u64>> = Lazy::new(|| watch::channel(0).0);
+
+/// How long a replaced global client is kept alive for requests already in flight.
+const CLIENT_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// Identifies the current global client. Streams created by the SDK end with
+/// [`Error::ClientReplaced`] once this changes.
+pub fn client_generation() -> u64 {
+    *CLIENT_GENERATION.borrow()
+}
+
+/// Notified whenever [`client_generation`] changes.
+pub(crate) fn client_replacements() -> watch::Receiver<u64> {
+    CLIENT_GENERATION.subscribe()
+}
+
+/// Takes the global client out of `slot`, closing it after [`CLIENT_GRACE_PERIOD`]. The socket
+/// closes once clients handed out before are dropped too.
+fn retire_client(slot: &mut Option<Connection>) {
+    if let Some(old) = slot.take() {
+        *CHAIN_URL.lock().unwrap() = None;
+        CLIENT_GENERATION.send_modify(|generation| *generation += 1);
+        tokio::spawn(async move {
+            tokio::time::sleep(CLIENT_GRACE_PERIOD).await;
+            drop(old);
+        });
+    }
+}
+
+/// Like [`init_api`], but drops the cached global client first when `force` is set.
+///
+/// The replaced client stays open for a grace period, while streams the SDK created from it end
+/// with [`Error::ClientReplaced`].
+///
+/// With `urls`, the global client is connected to the first of them that answers instead of
+/// `RPC_URL` or the default endpoints.
+pub async fn init_api_with_force(
+    force: bool,
+    urls: Option<&[&str]>,
+) -> Result<OnlineClient<PolkadotConfig>, Error> {
+    let urls = match urls {
+        Some(urls) => urls,
+        None => {
+            if force {
+                retire_client(&mut *CHAIN_API.lock().await);
+            }
+            return init_api().await;
+        }
+    };
+
+    let mut chain_api = CHAIN_API.lock().await;
+    if let (false, Some(connection)) = (force, chain_api.as_ref()) {
+        return Ok(connection.api.clone());
+    }
+
+    let urls: Vec<String> = urls.iter().map(|url| url.to_string()).collect();
+    let (connection, url) = connect_any(&urls).await?;
+    protocol::verify_once(&connection.api)?;
+    retire_client(&mut chain_api);
+    let api = connection.api.clone();
+    *chain_api = Some(connection);
+    *CHAIN_URL.lock().unwrap() = Some(url);
+    Ok(api)
+}
+
+/// Connects the global client to the first of `urls` that answers, replacing any cached client.
+pub async fn init_api_with_urls(urls: &[&str]) -> Result<OnlineClient<PolkadotConfig>, Error> {
+    init_api_with_force(true, Some(urls)).await
+}
+
+/// Connects the global client to the first endpoint of `network` that answers, replacing any
+/// cached client.
+pub async fn init_api_with_network(
+    network: Network,
+) -> Result<OnlineClient<PolkadotConfig>, Error> {
+    init_api_with_urls(network.rpc_urls()).await
+}
+
+/// Returns the client of the enclosing [`client::CessClient::scope`], or the global client.
 pub async fn init_api() -> Result<OnlineClient<PolkadotConfig>, Error> {
+    Ok(connection().await?.api)
+}
+
+/// [`init_api`] over any `Config`: the client of the enclosing [`client::CessClient::scope`] over
+/// `C`, or the global client when `C` is `PolkadotConfig`. The global client only exists for
+/// `PolkadotConfig`, so custom configs fail outside a scope.
+pub async fn init_api_for<C: Config>() -> Result<OnlineClient<C>, Error> {
+    if let Some(api) = client::scoped_client_for::<C>() {
+        return Ok(api);
+    }
+    let api: Box<dyn Any> = Box::new(init_api().await?);
+    api.downcast::<OnlineClient<C>>()
+        .map(|api| *api)
+        .map_err(|_| "No client for this Config, run inside CessClient::scope.".into())
+}
+
+/// Legacy RPC methods over the connection [`init_api_for`] resolves to.
+pub(crate) async fn legacy_rpc_for<C: Config>() -> Result<LegacyRpcMethods<C>, Error> {
+    let rpc = match client::scoped_connection_for::<C>() {
+        Some((_, rpc)) => rpc,
+        None => {
+            // Fails for custom configs, which only exist inside a scope.
+            init_api_for::<C>().await?;
+            connection().await?.rpc
+        }
+    };
+    rpc.map(LegacyRpcMethods::new)
+        .ok_or_else(|| "No RPC client for this connection, connect through the SDK.".into())
+}
+
+/// The connection [`init_api`] resolves to.
+pub(crate) async fn connection() -> Result<Connection, Error> {
+    if let Some(connection) = client::scoped_connection() {
+        return Ok(connection);
+    }
+
     dotenv().ok();
 
     let url = env::var("RPC_URL").ok();
 
     let mut chain_api = CHAIN_API.lock().await;
 
-    if let Some(ref api) = *chain_api {
-        Ok(api.clone())
+    if let Some(ref connection) = *chain_api {
+        Ok(connection.clone())
     } else {
-        let api = if let Some(url) = url {
+        let (connection, url) = if let Some(url) = url {
             match try_connect(Some(&url)).await {
-                Ok(api) => {
-                    info!(target: "SDK", "Connected to: {}", url);
-                    api
-                }
+                Ok(connection) => (connection, url),
                 Err(_) => match try_default_connect().await {
-                    Ok(api) => {
-                        info!(target: "SDK", "Connected to official RPC server");
-                        api
-                    }
+                    Ok(connected) => connected,
                     Err(_) => return Err("All connections failed.".into()),
                 },
             }
         } else {
-            try_connect(None)
+            let connection = try_connect(None)
                 .await
-                .map_err(|_| Error::Custom("All connections failed.".into()))?
+                .map_err(|_| Error::Custom("All connections failed.".into()))?;
+            (connection, DEFAULT_RPC_URL.to_string())
         };
-        *chain_api = Some(api.clone());
-        Ok(api)
+        info!(target: "SDK", "Connected to: {}", url);
+        protocol::verify_once(&connection.api)?;
+        *chain_api = Some(connection.clone());
+        *CHAIN_URL.lock().unwrap() = Some(url);
+        Ok(connection)
+    }
+}
+
+/// Confirms the cached global client still reaches the chain.
+///
+/// On failure the cached client is dropped so the next [`init_api`] call reconnects, unless
+/// another task replaced it while it was being checked.
+pub async fn check_api_health() -> Result<(), Error> {
+    let (api, generation) = match CHAIN_API.lock().await.as_ref() {
+        Some(connection) => (connection.api.clone(), client_generation()),
+        None => return Err("No active connection.".into()),
+    };
+
+    if let Err(e) = api.blocks().at_latest().await {
+        let mut chain_api = CHAIN_API.lock().await;
+        if client_generation() == generation {
+            retire_client(&mut chain_api);
+        }
+        return Err(e.into());
     }
+
+    Ok(())
+}
+
+/// The chain a client is connected to, see [`chain_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainInfo {
+    pub chain_name: String,
+    /// Spec version of the connected runtime, to compare with the metadata the SDK bundles.
+    pub spec_version: u32,
+    pub genesis_hash: H256,
+    pub latest_finalized_block: u64,
+    /// Endpoint of the global client, `None` inside [`client::CessClient::scope`].
+    pub rpc_url: Option<String>,
+}
+
+/// Describes the chain [`init_api`] resolves to, e.g. for readiness probes.
+pub async fn chain_info() -> Result<ChainInfo, Error> {
+    let connection = connection().await?;
+    let api = &connection.api;
+    let rpc = connection.legacy_rpc()?;
+    let chain_name = rpc.system_chain().await?;
+    let finalized = rpc.chain_get_finalized_head().await?;
+    let latest_finalized_block = api.blocks().at(finalized).await?.number().into();
+    let rpc_url = match client::scoped_client() {
+        Some(_) => None,
+        None => CHAIN_URL.lock().unwrap().clone(),
+    };
+
+    Ok(ChainInfo {
+        chain_name,
+        spec_version: api.runtime_version().spec_version,
+        genesis_hash: api.genesis_hash(),
+        latest_finalized_block,
+        rpc_url,
+    })
+}
+
+/// Round trip time of a `system_health` call through the client [`init_api`] resolves to.
+pub async fn ping() -> Result<Duration, Error> {
+    let rpc = connection().await?.legacy_rpc()?;
+    let start = Instant::now();
+    rpc.system_health().await?;
+    Ok(start.elapsed())
+}
+
+/// Runs [`check_api_health`] every `interval` in a background task.
+pub fn spawn_health_check_task(interval: Duration) -> task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = check_api_health().await {
+                warn!(target: "SDK", "RPC health check failed: {}", e);
+            }
+        }
+    })
 }
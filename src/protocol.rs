@@ -0,0 +1,131 @@
+//! Storage format assumptions compiled into the SDK.
+//!
+//! Files prepared by a build of the SDK are only readable by gateways and miners sharing the same
+//! assumptions. [`ASSUMPTIONS`] lists them so embedders can compare builds, and
+//! [`verify_against_chain`] checks the ones the runtime exposes. The global client runs that check
+//! once per process, see [`set_mismatch_policy`].
+
+use crate::constants::{
+    DATA_SHARDS, FRAEMENT_SIZE, MAX_FRAGMENT_COUNT, MAX_SEGMENT_COUNT, PAR_SHARDS, SEGMENT_SIZE,
+};
+use crate::core::Error;
+use crate::polkadot;
+use log::warn;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use subxt::ext::sp_core::crypto::Ss58AddressFormatRegistry;
+use subxt::{OnlineClient, PolkadotConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProtocolAssumptions {
+    /// Bumped whenever any other field changes.
+    pub version: u32,
+    pub segment_size: u32,
+    pub fragment_size: u32,
+    pub data_shards: u32,
+    pub parity_shards: u32,
+    /// Most segments a single file may have.
+    pub max_segment_count: u32,
+    /// Most fragments recorded per segment.
+    pub max_fragment_count: u32,
+    /// Hash function deriving fids and fragment hashes.
+    pub fid_hash: &'static str,
+    /// How the fid is built from the segment hashes.
+    pub merkle_construction: &'static str,
+    pub ss58_prefix: u16,
+}
+
+pub const ASSUMPTIONS: ProtocolAssumptions = ProtocolAssumptions {
+    version: 1,
+    segment_size: SEGMENT_SIZE,
+    fragment_size: FRAEMENT_SIZE,
+    data_shards: DATA_SHARDS,
+    parity_shards: PAR_SHARDS,
+    max_segment_count: MAX_SEGMENT_COUNT as u32,
+    max_fragment_count: MAX_FRAGMENT_COUNT as u32,
+    fid_hash: "sha256",
+    merkle_construction: "sha256 binary tree over segment hashes",
+    ss58_prefix: Ss58AddressFormatRegistry::CessTestnetAccount as u16,
+};
+
+/// What to do when the connected runtime disagrees with [`ASSUMPTIONS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MismatchPolicy {
+    /// Log a warning and continue. The default.
+    Warn,
+    /// Fail connecting the global client with [`Error::ProtocolMismatch`].
+    Error,
+}
+
+static POLICY: AtomicU8 = AtomicU8::new(0);
+static VERIFIED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_mismatch_policy(policy: MismatchPolicy) {
+    POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+pub fn mismatch_policy() -> MismatchPolicy {
+    match POLICY.load(Ordering::Relaxed) {
+        0 => MismatchPolicy::Warn,
+        _ => MismatchPolicy::Error,
+    }
+}
+
+/// Compares [`ASSUMPTIONS`] with the constants of the runtime `api` is connected to.
+///
+/// Only the segment and fragment limits and the SS58 prefix are observable on-chain, the other
+/// fields are not checked. Fails with the first mismatch found.
+pub fn verify_against_chain(api: &OnlineClient<PolkadotConfig>) -> Result<(), Error> {
+    let constants = api.constants();
+    let file_bank = polkadot::constants().file_bank();
+    let observed = [
+        (
+            "max_segment_count",
+            ASSUMPTIONS.max_segment_count as u64,
+            constants.at(&file_bank.segment_count())? as u64,
+        ),
+        (
+            "max_fragment_count",
+            ASSUMPTIONS.max_fragment_count as u64,
+            constants.at(&file_bank.fragment_count())? as u64,
+        ),
+        (
+            "ss58_prefix",
+            ASSUMPTIONS.ss58_prefix as u64,
+            constants.at(&polkadot::constants().system().ss58_prefix())? as u64,
+        ),
+    ];
+
+    for (field, assumed, chain) in observed {
+        if assumed != chain {
+            return Err(Error::ProtocolMismatch {
+                field,
+                assumed: assumed.to_string(),
+                chain: chain.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Runs [`verify_against_chain`] the first time a client is connected in this process.
+pub(crate) fn verify_once(api: &OnlineClient<PolkadotConfig>) -> Result<(), Error> {
+    if VERIFIED.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+
+    match verify_against_chain(api) {
+        Err(e @ Error::ProtocolMismatch { .. }) => {
+            if mismatch_policy() == MismatchPolicy::Error {
+                return Err(e);
+            }
+            warn!(target: "SDK", "The connected chain breaks an SDK protocol assumption: {}", e);
+        }
+        Err(e) => {
+            warn!(target: "SDK", "Could not verify SDK protocol assumptions: {}", e);
+            return Ok(());
+        }
+        Ok(()) => {}
+    }
+    VERIFIED.store(true, Ordering::Relaxed);
+    Ok(())
+}
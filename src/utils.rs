@@ -1,17 +1,26 @@
 pub mod account;
 pub mod bucket;
+pub mod cost;
 pub mod file;
 pub mod ip;
 pub mod str;
 
-use crate::init_api;
+use crate::constants::TOKEN_PRECISION_CESS;
+use crate::core::Error;
 use crate::polkadot::runtime_types::cp_cess_common::Hash;
+use crate::{connection, init_api};
+use futures::future;
+use std::sync::Arc;
 use subxt::{
     blocks::Extrinsics,
     ext::sp_core::crypto::{AccountId32, Ss58AddressFormat, Ss58AddressFormatRegistry, Ss58Codec},
     utils::H256,
     OnlineClient, PolkadotConfig,
 };
+use tokio::sync::Semaphore;
+
+/// Most blocks [`get_extrinsics_in_range`] fetches at once.
+const MAX_CONCURRENT_BLOCK_FETCHES: usize = 8;
 
 pub fn get_ss58_address(account_str: &str) -> Result<String, Box<dyn std::error::Error>> {
     let ss58_address = AccountId32::from_string(account_str)?;
@@ -23,21 +32,52 @@ pub fn get_ss58_address(account_str: &str) -> Result<String, Box<dyn std::error:
 }
 
 // returns cp_cess_common::Hash([u8; 64])
-pub fn hash_from_string(v: &str) -> Result<Hash, Box<dyn std::error::Error>> {
+pub fn hash_from_string(v: &str) -> Result<Hash, Error> {
     // Check if the hash starts with "0x"
-    let v = if v.starts_with("0x") {
-        v.strip_prefix("0x").unwrap_or(v)
-    } else {
-        v
-    };
+    let v = v.strip_prefix("0x").unwrap_or(v);
 
-    // Convert to bytes and try to convert into a fixed-size array
-    let bytes = v.as_bytes();
-    let hash_array: [u8; 64] = bytes.try_into()?;
+    // The chain stores the lowercase hex text itself, not the decoded bytes
+    let hash_array: [u8; 64] = v
+        .to_ascii_lowercase()
+        .into_bytes()
+        .try_into()
+        .map_err(|_| Error::InvalidHashLength {
+            expected: 64,
+            got: v.len(),
+        })?;
+    if !hash_array.iter().all(u8::is_ascii_hexdigit) {
+        return Err(Error::Custom(format!("Hash {} is not hex encoded", v)));
+    }
 
     Ok(Hash(hash_array))
 }
 
+/// Inverse of [`hash_from_string`], without the `0x` prefix.
+pub fn hash_to_string(hash: &Hash) -> String {
+    String::from_utf8_lossy(&hash.0).to_string()
+}
+
+impl TryFrom<&str> for Hash {
+    type Error = Error;
+
+    fn try_from(v: &str) -> Result<Self, Self::Error> {
+        hash_from_string(v)
+    }
+}
+
+/// Formats an amount in the smallest unit as CESS, e.g. `1_500_000_000_000` as `"1.5"`.
+pub fn format_cess(amount: u128) -> String {
+    let unit = 10u128.pow(TOKEN_PRECISION_CESS.len() as u32);
+    let whole = amount / unit;
+    let fraction = amount % unit;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{:0width$}", fraction, width = TOKEN_PRECISION_CESS.len());
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
 pub fn block_hex_string_to_h256(hex: &str) -> H256 {
     let hex_without_prefix = if let Some(hex_without_prefix) = hex.strip_prefix("0x") {
         hex_without_prefix
@@ -68,3 +108,42 @@ pub async fn get_extrinsics_at(
 
     Ok(extrinsics)
 }
+
+pub async fn get_block_hash_by_number(n: u64) -> Result<H256, Error> {
+    let rpc = connection().await?.legacy_rpc()?;
+
+    match rpc.chain_get_block_hash(Some(n.into())).await? {
+        Some(hash) => Ok(hash),
+        None => Err(format!("Block #{} not found", n).into()),
+    }
+}
+
+pub async fn get_extrinsics_by_number(
+    n: u64,
+) -> Result<Extrinsics<PolkadotConfig, OnlineClient<PolkadotConfig>>, Error> {
+    let api = init_api().await?;
+    let hash = get_block_hash_by_number(n).await?;
+    let block = api.blocks().at(hash).await?;
+
+    Ok(block.extrinsics().await?)
+}
+
+/// Extrinsics of the blocks `from..=to`, in block order.
+pub async fn get_extrinsics_in_range(
+    from: u64,
+    to: u64,
+) -> Result<Vec<Extrinsics<PolkadotConfig, OnlineClient<PolkadotConfig>>>, Error> {
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_BLOCK_FETCHES));
+    let fetches = (from..=to).map(|n| {
+        let permits = permits.clone();
+        async move {
+            let _permit = permits
+                .acquire()
+                .await
+                .map_err(|e| Error::Custom(e.to_string()))?;
+            get_extrinsics_by_number(n).await
+        }
+    });
+
+    future::try_join_all(fetches).await
+}
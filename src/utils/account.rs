@@ -1,5 +1,10 @@
+use crate::core::Error;
 use blake2::{Blake2b512, Digest};
 use sp_keyring::sr25519::sr25519::Pair;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
 use subxt::{
     ext::sp_core::{
         crypto::{AccountId32, Ss58AddressFormat, Ss58AddressFormatRegistry, Ss58Codec},
@@ -91,6 +96,11 @@ pub fn encode_public_key_as_cess_account(
     encode_public_key_as_account(public_key, &CESS_PREFIX)
 }
 
+/// Ed25519 public keys map to accounts the same way sr25519 keys do.
+pub fn encode_ed25519_public_key_as_cess_account(pk: &[u8; 32]) -> Result<String, Error> {
+    encode_public_key_as_account(pk, &CESS_PREFIX).map_err(|e| Error::Custom(e.to_string()))
+}
+
 fn encode_public_key_as_account(
     public_key: &[u8],
     prefix: &[u8],
@@ -142,9 +152,150 @@ pub fn get_pair_address_as_ss58_address(pair: Pair) -> Result<String, Box<dyn st
     Ok(ss58_cess_address)
 }
 
+#[deprecated(note = "panics or truncates on slices that are not 32 bytes, use `IntoAccount`")]
 pub fn account_from_slice(pk: &[u8]) -> SubxtUtilsAccountId32 {
     let mut pk_array = [0u8; 32];
     pk_array.copy_from_slice(&pk[..32]); // Ensure the slice is exactly 32 bytes
 
     SubxtUtilsAccountId32::from(pk_array)
 }
+
+/// Anything the transaction modules accept where an account is expected.
+pub trait IntoAccount {
+    fn into_account(self) -> Result<SubxtUtilsAccountId32, Error>;
+}
+
+impl IntoAccount for &str {
+    fn into_account(self) -> Result<SubxtUtilsAccountId32, Error> {
+        SubxtUtilsAccountId32::from_str(self)
+            .map_err(|e| Error::InvalidInput(format!("Invalid account '{}': {}", self, e)))
+    }
+}
+
+/// A raw public key, rejected unless it is exactly 32 bytes.
+impl IntoAccount for &[u8] {
+    fn into_account(self) -> Result<SubxtUtilsAccountId32, Error> {
+        let bytes: [u8; 32] = self.try_into().map_err(|_| {
+            Error::InvalidInput(format!(
+                "Invalid account: expected 32 bytes, got {}",
+                self.len()
+            ))
+        })?;
+        Ok(SubxtUtilsAccountId32(bytes))
+    }
+}
+
+impl IntoAccount for [u8; 32] {
+    fn into_account(self) -> Result<SubxtUtilsAccountId32, Error> {
+        Ok(SubxtUtilsAccountId32(self))
+    }
+}
+
+impl IntoAccount for SubxtUtilsAccountId32 {
+    fn into_account(self) -> Result<SubxtUtilsAccountId32, Error> {
+        Ok(self)
+    }
+}
+
+/// An alias looked up in an [`AddressBook`].
+pub struct Alias<'a> {
+    pub book: &'a AddressBook,
+    pub name: &'a str,
+}
+
+impl IntoAccount for Alias<'_> {
+    fn into_account(self) -> Result<SubxtUtilsAccountId32, Error> {
+        self.book.resolve(self.name)
+    }
+}
+
+/// Maps human readable aliases ("gateway-sgp", "cold-1") to accounts.
+///
+/// Loaded from a JSON object or a TOML table of `alias: address` pairs. Aliases are matched
+/// case-insensitively and labelled as written.
+#[derive(Debug, Clone, Default)]
+pub struct AddressBook {
+    /// Lowercased alias to the alias as written and its account.
+    entries: BTreeMap<String, (String, SubxtUtilsAccountId32)>,
+}
+
+impl AddressBook {
+    /// Loads a `.toml` file as TOML and anything else as JSON.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::Custom(format!("Failed to read {}: {}", path.display(), e)))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            _ => Self::from_json(&content),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let raw: BTreeMap<String, String> = serde_json::from_str(json)
+            .map_err(|e| Error::Custom(format!("Malformed address book: {}", e)))?;
+        Self::from_entries(raw)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, Error> {
+        let raw: BTreeMap<String, String> = toml::from_str(toml)
+            .map_err(|e| Error::Custom(format!("Malformed address book: {}", e)))?;
+        Self::from_entries(raw)
+    }
+
+    fn from_entries(raw: BTreeMap<String, String>) -> Result<Self, Error> {
+        let mut book = Self::default();
+        for (alias, address) in raw {
+            book.insert(&alias, &address)?;
+        }
+        Ok(book)
+    }
+
+    /// Adds an alias, rejecting invalid addresses and aliases that would make lookups ambiguous.
+    pub fn insert(&mut self, alias: &str, address: &str) -> Result<(), Error> {
+        let key = alias.trim().to_lowercase();
+        if key.is_empty() {
+            return Err("Empty alias in address book".into());
+        }
+        if self.entries.contains_key(&key) {
+            return Err(format!("Ambiguous alias '{}'", alias).into());
+        }
+        let account = address.into_account()?;
+        // An alias spelled like an address must not point somewhere else.
+        if let Ok(other) = alias.into_account() {
+            if other != account {
+                return Err(format!("Alias '{}' is an address of another account", alias).into());
+            }
+        }
+
+        self.entries
+            .insert(key, (alias.trim().to_string(), account));
+        Ok(())
+    }
+
+    /// Resolves an alias, falling back to parsing the input as an SS58 address.
+    pub fn resolve(&self, alias_or_address: &str) -> Result<SubxtUtilsAccountId32, Error> {
+        match self.entries.get(&alias_or_address.trim().to_lowercase()) {
+            Some((_, account)) => Ok(account.clone()),
+            None => alias_or_address.into_account().map_err(|_| {
+                Error::Custom(format!(
+                    "'{}' is neither a known alias nor a valid address",
+                    alias_or_address
+                ))
+            }),
+        }
+    }
+
+    pub fn alias<'a>(&'a self, name: &'a str) -> Alias<'a> {
+        Alias { book: self, name }
+    }
+
+    /// Reverse lookup, for annotating known accounts in output. Returns the alias as written in
+    /// the book.
+    pub fn label_for(&self, account: &SubxtUtilsAccountId32) -> Option<&str> {
+        self.entries
+            .values()
+            .find(|(_, a)| a == account)
+            .map(|(label, _)| label.as_str())
+    }
+}
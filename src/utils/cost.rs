@@ -0,0 +1,72 @@
+use crate::chain::balances;
+use crate::chain::storage_handler::orders::{territory_price, OrderSpec};
+use crate::chain::storage_handler::query::StorageQuery;
+use crate::constants::{DATA_SHARDS, FRAEMENT_SIZE, PAR_SHARDS, SEGMENT_SIZE, SIZE_1_GI_B};
+use crate::core::Error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    /// Territory space the file occupies once erasure coded, in GiB.
+    pub required_space_gib: f64,
+    /// Price of expanding the territory to fit the file, 0 when it already fits.
+    pub estimated_fee_cess: u128,
+    pub has_sufficient_territory: bool,
+    pub territory_remaining_bytes: u64,
+    pub free_balance: u128,
+    /// Whether `free_balance` covers `estimated_fee_cess`.
+    pub has_sufficient_balance: bool,
+}
+
+/// Territory space taken by a file of `file_size` bytes: every started segment is stored as
+/// `DATA_SHARDS + PAR_SHARDS` fragments.
+pub fn required_territory_space(file_size: u64) -> u128 {
+    let segments = file_size.div_ceil(SEGMENT_SIZE as u64) as u128;
+    segments * FRAEMENT_SIZE as u128 * (DATA_SHARDS + PAR_SHARDS) as u128
+}
+
+/// Estimates what storing a file of `file_size_bytes` in `territory_name` of `account` costs.
+pub async fn estimate_upload_cost(
+    file_size_bytes: u64,
+    territory_name: &str,
+    account: &str,
+) -> Result<CostEstimate, Error> {
+    let territory = StorageQuery::territory(account, territory_name, None)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?
+        .ok_or_else(|| Error::TerritoryNotFound {
+            name: territory_name.to_string(),
+        })?;
+    let free_balance = balances::query::StorageQuery::free_balance(account, None)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?;
+
+    let required = required_territory_space(file_size_bytes);
+    let has_sufficient_territory = required <= territory.remaining_space;
+    let estimated_fee_cess = if has_sufficient_territory {
+        0
+    } else {
+        let missing_gib = (required - territory.remaining_space).div_ceil(SIZE_1_GI_B as u128);
+        OrderSpec::expand(territory_name, missing_gib.min(u32::MAX as u128) as u32)
+            .prepare(account)
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?
+    };
+
+    Ok(CostEstimate {
+        required_space_gib: required as f64 / SIZE_1_GI_B as f64,
+        estimated_fee_cess,
+        has_sufficient_territory,
+        territory_remaining_bytes: territory.remaining_space.min(u64::MAX as u128) as u64,
+        free_balance,
+        has_sufficient_balance: free_balance >= estimated_fee_cess,
+    })
+}
+
+/// Price of minting a territory of `gib` GiB for `days` days.
+pub async fn estimate_territory_purchase_cost(gib: u32, days: u32) -> Result<u128, Error> {
+    let unit_price = StorageQuery::unit_price(None)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?
+        .ok_or_else(|| Error::Custom("Unit price not set on chain".into()))?;
+    Ok(territory_price(unit_price, gib, days))
+}
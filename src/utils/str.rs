@@ -1,7 +1,5 @@
-use rand::rngs::StdRng;
+use crate::core::rng::secure_rng;
 use rand::Rng;
-use rand::SeedableRng;
-use std::time::SystemTime;
 
 const LETTER_ID_BITS: i32 = 6;
 const LETTER_ID_MASK: i32 = 1 << (LETTER_ID_BITS - 1);
@@ -10,13 +8,7 @@ const BASE_STR: &str =
     "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!@#$%^&*()[]{}+-*/_=.";
 
 pub fn get_random_code(length: u8) -> Result<String, Box<dyn std::error::Error>> {
-    let seed = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_nanos() as u64
-        + rand::random::<i64>().wrapping_abs() as u64;
-
-    let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    let mut rng = secure_rng();
 
     let mut bytes = Vec::with_capacity(length as usize);
     for _ in 0..length {
@@ -32,12 +24,7 @@ pub fn get_random_code(length: u8) -> Result<String, Box<dyn std::error::Error>>
 }
 
 pub fn rand_str(n: usize) -> String {
-    let seed = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_nanos() as u64;
-
-    let mut rng: StdRng = SeedableRng::seed_from_u64(seed);
+    let mut rng = secure_rng();
 
     let mut rand_str = String::with_capacity(n);
     let mut cache = rng.gen::<i64>();